@@ -1,5 +1,7 @@
 use amethyst::Error;
 
+use gv_core::net::TickRate;
+
 use std::{
     env::current_exe,
     net::SocketAddr,
@@ -48,8 +50,13 @@ impl ServerCommand {
         Self { process: None }
     }
 
-    pub fn start(&mut self, addr: SocketAddr, host_client_addr: SocketAddr) -> Result<(), Error> {
-        self.process = Some(ServerProcess::new(addr, Some(host_client_addr))?);
+    pub fn start(
+        &mut self,
+        addr: SocketAddr,
+        host_client_addr: SocketAddr,
+        tick_rate: TickRate,
+    ) -> Result<(), Error> {
+        self.process = Some(ServerProcess::new(addr, Some(host_client_addr), tick_rate)?);
         Ok(())
     }
 
@@ -76,7 +83,11 @@ pub struct ServerProcess {
 }
 
 impl ServerProcess {
-    pub fn new(addr: SocketAddr, host_client_addr: Option<SocketAddr>) -> Result<Self, Error> {
+    pub fn new(
+        addr: SocketAddr,
+        host_client_addr: Option<SocketAddr>,
+        tick_rate: TickRate,
+    ) -> Result<Self, Error> {
         let executable_path = {
             let mut path = current_exe()?;
             path.pop();
@@ -85,6 +96,9 @@ impl ServerProcess {
 
         let mut command_builder = Command::new(executable_path);
         command_builder.arg("--addr").arg(addr.to_string());
+        command_builder
+            .arg("--tick-rate")
+            .arg(tick_rate.frames_per_second().to_string());
 
         if let Some(host_client_addr) = host_client_addr {
             command_builder
@@ -10,29 +10,33 @@ use std::{
     time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
-use gv_client_shared::ecs::resources::{ConnectionStatus, MultiplayerRoomState};
+use gv_client_shared::{
+    ecs::resources::{ConnectionStatus, MultiplayerRoomState},
+    settings::Settings,
+};
 use gv_core::{
     actions::monster_spawn::SpawnActions,
     ecs::{
         components::NetConnectionModel,
         resources::{
             net::{MultiplayerGameState, MultiplayerRoomPlayer, PlayersNetStatus},
+            stats::{ExperienceAwards, RunSummary},
             world::{
                 FramedUpdates, PlayerActionUpdates, ReceivedPlayerUpdate,
-                ReceivedServerWorldUpdate, ServerWorldUpdate, PAUSE_FRAME_THRESHOLD,
+                ReceivedServerWorldUpdate, ServerWorldUpdate,
             },
-            GameEngineState, NewGameEngineState,
+            GameEngineState, GameLevelState, NewGameEngineState,
         },
         system_data::time::GameTimeService,
     },
     net::{
         client_message::ClientMessagePayload,
         server_message::{DisconnectReason, ServerMessage, ServerMessagePayload},
-        NetEvent, NetIdentifier, INTERPOLATION_FRAME_DELAY,
+        NetEvent, NetIdentifier, TickRate,
     },
 };
 use gv_game::{
-    ecs::resources::ConnectionEvents,
+    ecs::resources::{ConnectionEvents, MonsterDefinitions, SpellDefinitions},
     utils::net::{send_message_reliable, send_message_unreliable},
 };
 
@@ -40,7 +44,7 @@ use crate::ecs::resources::{
     LastAcknowledgedUpdate, ServerCommand, UiNetworkCommand, UiNetworkCommandResource,
 };
 
-const HEARTBEAT_FRAME_INTERVAL: u64 = 10;
+const HEARTBEAT_INTERVAL_SECS: f32 = 1.0 / 6.0;
 
 #[derive(SystemData)]
 pub struct ClientNetworkSystemData<'s> {
@@ -56,8 +60,15 @@ pub struct ClientNetworkSystemData<'s> {
     player_actions_updates: WriteExpect<'s, FramedUpdates<PlayerActionUpdates>>,
     spawn_actions: WriteExpect<'s, FramedUpdates<SpawnActions>>,
     server_command: WriteExpect<'s, ServerCommand>,
+    tick_rate: ReadExpect<'s, TickRate>,
+    monster_definitions: ReadExpect<'s, MonsterDefinitions>,
+    spell_definitions: ReadExpect<'s, SpellDefinitions>,
+    settings: ReadExpect<'s, Settings>,
     ui_network_command: WriteExpect<'s, UiNetworkCommandResource>,
     players_net_status: WriteExpect<'s, PlayersNetStatus>,
+    game_level_state: WriteExpect<'s, GameLevelState>,
+    run_summary: WriteExpect<'s, RunSummary>,
+    experience_awards: WriteExpect<'s, ExperienceAwards>,
     net_connection_models: WriteStorage<'s, NetConnectionModel>,
     transport: Write<'s, TransportResource>,
     laminar_socket: WriteExpect<'s, LaminarSocketResource>,
@@ -104,10 +115,11 @@ impl ClientNetworkSystem {
                     SocketAddr::V4(addr) => addr.set_ip(Ipv4Addr::new(127, 0, 0, 1)),
                     SocketAddr::V6(addr) => addr.set_ip(Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1)),
                 };
-                if let Err(err) = system_data
-                    .server_command
-                    .start(server_addr, host_client_addr)
-                {
+                if let Err(err) = system_data.server_command.start(
+                    server_addr,
+                    host_client_addr,
+                    *system_data.tick_rate,
+                ) {
                     log::error!("Couldn't start the server: {:?}", err);
                     system_data.multiplayer_room_state.connection_status =
                         ConnectionStatus::ServerStartFailed;
@@ -137,6 +149,10 @@ impl ClientNetworkSystem {
                             .duration_since(UNIX_EPOCH)
                             .expect("Expected a duration unix timestamp"),
                         nickname: self.nickname.clone(),
+                        monster_definitions_checksum: system_data.monster_definitions.checksum,
+                        spell_definitions_checksum: system_data.spell_definitions.checksum,
+                        experience: system_data.settings.profile().experience,
+                        upgrades: system_data.settings.profile().upgrades,
                     },
                 );
 
@@ -277,7 +293,7 @@ impl<'s> System<'s> for ClientNetworkSystem {
                     }) => match payload {
                         ServerMessagePayload::Handshake { .. } => true,
                         ServerMessagePayload::UpdateRoomPlayers(_) => true,
-                        ServerMessagePayload::StartGame(_) => true,
+                        ServerMessagePayload::StartGame { .. } => true,
                         _ => false,
                     },
                     _ => false,
@@ -320,6 +336,14 @@ impl<'s> System<'s> for ClientNetworkSystem {
                                             .duration_since(UNIX_EPOCH)
                                             .expect("Expected a duration unix timestamp"),
                                         nickname: self.nickname.clone(),
+                                        monster_definitions_checksum: system_data
+                                            .monster_definitions
+                                            .checksum,
+                                        spell_definitions_checksum: system_data
+                                            .spell_definitions
+                                            .checksum,
+                                        experience: system_data.settings.profile().experience,
+                                        upgrades: system_data.settings.profile().upgrades,
                                     },
                                 );
                             }
@@ -331,9 +355,13 @@ impl<'s> System<'s> for ClientNetworkSystem {
                         ServerMessagePayload::UpdateRoomPlayers(players) => {
                             update_room_players(&mut system_data.multiplayer_game_state, players);
                         }
-                        ServerMessagePayload::StartGame(net_ids_and_players) => {
+                        ServerMessagePayload::StartGame {
+                            tick_rate,
+                            players: net_ids_and_players,
+                        } => {
                             system_data.last_acknowledged_update.frame_number = 0;
                             system_data.last_acknowledged_update.id = 0;
+                            system_data.multiplayer_game_state.tick_rate = tick_rate;
 
                             let (entity_net_ids, players): (
                                 Vec<NetIdentifier>,
@@ -406,6 +434,7 @@ impl<'s> System<'s> for ClientNetworkSystem {
                                 system_data.spawn_actions.reserve_updates(frame_to_reserve);
 
                                 apply_world_updates(
+                                    system_data.game_time_service.interpolation_frame_delay(),
                                     vec![system_data.multiplayer_room_state.player_net_id],
                                     &mut system_data.framed_updates,
                                     &mut system_data.spawn_actions,
@@ -419,6 +448,12 @@ impl<'s> System<'s> for ClientNetworkSystem {
                                 discarded_actions,
                             );
                         }
+                        ServerMessagePayload::ConfirmedKills(kills) => {
+                            // Reliable messages may arrive out of order.
+                            let experience_awards = &mut system_data.experience_awards;
+                            experience_awards.confirmed_kills =
+                                experience_awards.confirmed_kills.max(kills);
+                        }
                         ServerMessagePayload::ReportPlayersNetStatus { id, players } => {
                             if system_data.multiplayer_game_state.players_status_id < id {
                                 system_data.multiplayer_game_state.players_status_id = id;
@@ -454,6 +489,11 @@ impl<'s> System<'s> for ClientNetworkSystem {
                                 system_data.multiplayer_game_state.lagging_players.clear();
                             }
                         }
+                        ServerMessagePayload::GameOver(run_summary) => {
+                            log::info!("Received a GameOver message");
+                            *system_data.run_summary = run_summary;
+                            system_data.game_level_state.is_over = true;
+                        }
                         ServerMessagePayload::Disconnect(disconnect_reason) => {
                             if !system_data
                                 .multiplayer_room_state
@@ -499,7 +539,9 @@ impl<'s> System<'s> for ClientNetworkSystem {
         }
 
         if system_data.game_time_service.engine_time().frame_number() - self.last_heartbeat_frame
-            > HEARTBEAT_FRAME_INTERVAL
+            > system_data
+                .game_time_service
+                .frames_in_seconds(HEARTBEAT_INTERVAL_SECS)
             && !net_connection_model.disconnected
         {
             self.last_heartbeat_frame = system_data.game_time_service.engine_time().frame_number();
@@ -524,18 +566,21 @@ impl<'s> System<'s> for ClientNetworkSystem {
                 .map_or(0, |update| update.frame_number);
 
             system_data.multiplayer_game_state.waiting_for_players =
-                system_data.game_time_service.game_frame_number() + INTERPOLATION_FRAME_DELAY
+                system_data.game_time_service.game_frame_number()
+                    + system_data.game_time_service.interpolation_frame_delay()
                     >= server_frame;
         }
 
         if *system_data.game_engine_state == GameEngineState::Playing
             && system_data.multiplayer_game_state.is_playing
         {
-            // We always skip first INTERPOLATION_FRAME_DELAY frames on game start.
+            // We always skip first interpolation_frame_delay frames on game start.
+            let interpolation_frame_delay =
+                system_data.game_time_service.interpolation_frame_delay();
             match system_data
                 .game_time_service
                 .game_frame_number_absolute()
-                .cmp(&INTERPOLATION_FRAME_DELAY)
+                .cmp(&interpolation_frame_delay)
             {
                 Ordering::Less => {
                     system_data.multiplayer_game_state.waiting_network = true;
@@ -547,7 +592,7 @@ impl<'s> System<'s> for ClientNetworkSystem {
                 _ => {}
             }
 
-            // Wait if we a server is lagging behind for PAUSE_FRAME_THRESHOLD frames.
+            // Wait if we a server is lagging behind for PAUSE_THRESHOLD_SECS.
             let frames_ahead = system_data
                 .game_time_service
                 .game_frame_number()
@@ -555,12 +600,12 @@ impl<'s> System<'s> for ClientNetworkSystem {
                     system_data
                         .last_acknowledged_update
                         .frame_number
-                        .saturating_sub(INTERPOLATION_FRAME_DELAY),
+                        .saturating_sub(interpolation_frame_delay),
                 );
             log::trace!("Frames ahead: {}", frames_ahead);
             if system_data.multiplayer_game_state.waiting_network {
                 system_data.multiplayer_game_state.waiting_network = frames_ahead != 0;
-            } else if frames_ahead > PAUSE_FRAME_THRESHOLD {
+            } else if frames_ahead > system_data.game_time_service.pause_frame_threshold() {
                 system_data.multiplayer_game_state.waiting_network = true;
             }
 
@@ -590,6 +635,7 @@ fn server_connection<'a>(
 
 // Expects incoming_updates to be sorted (lowest frame first).
 fn apply_world_updates(
+    interpolation_frame_delay: u64,
     controlled_players: Vec<NetIdentifier>,
     framed_updates: &mut FramedUpdates<ReceivedServerWorldUpdate>,
     spawn_actions: &mut FramedUpdates<SpawnActions>,
@@ -603,7 +649,7 @@ fn apply_world_updates(
         .first()
         .unwrap()
         .frame_number
-        .saturating_sub(INTERPOLATION_FRAME_DELAY);
+        .saturating_sub(interpolation_frame_delay);
     let first_available_frame_number = framed_updates.updates.front().unwrap().frame_number;
     assert!(
         first_incoming_frame_number >= first_available_frame_number,
@@ -612,8 +658,11 @@ fn apply_world_updates(
         first_available_frame_number,
     );
 
-    let controlled_player_updates =
-        collect_controlled_player_updates(&controlled_players, &mut incoming_updates);
+    let controlled_player_updates = collect_controlled_player_updates(
+        interpolation_frame_delay,
+        &controlled_players,
+        &mut incoming_updates,
+    );
 
     let (controlled_start_frame_number, others_start_frame_number) = incoming_updates
         .first()
@@ -621,7 +670,7 @@ fn apply_world_updates(
             (
                 update
                     .frame_number
-                    .saturating_sub(INTERPOLATION_FRAME_DELAY),
+                    .saturating_sub(interpolation_frame_delay),
                 update.frame_number,
             )
         })
@@ -654,14 +703,15 @@ fn apply_world_updates(
 }
 
 fn collect_controlled_player_updates(
+    interpolation_frame_delay: u64,
     controlled_players: &[NetIdentifier],
     incoming_updates: &mut Vec<ServerWorldUpdate>,
 ) -> Vec<ReceivedPlayerUpdate> {
     incoming_updates
         .iter_mut()
         .skip_while(|update| {
-            // Skips the first 10 frames, as there shouldn't be any player updates on game start.
-            update.frame_number < INTERPOLATION_FRAME_DELAY
+            // Skips the first frames, as there shouldn't be any player updates on game start.
+            update.frame_number < interpolation_frame_delay
         })
         .map(|update| {
             let mut controlled_player_update = ReceivedPlayerUpdate::default();
@@ -0,0 +1,55 @@
+use amethyst::ecs::{Entities, Join, ReadExpect, ReadStorage, System, WriteExpect};
+
+use gv_client_shared::settings::Settings;
+use gv_core::{
+    ecs::{
+        components::ClientPlayerActions,
+        resources::stats::{ExperienceAwards, RunStats},
+    },
+    progression::EXPERIENCE_PER_KILL,
+};
+use gv_game::ecs::system_data::GameStateHelper;
+
+/// Awards the local player with experience for every kill that can't be rolled back anymore.
+/// A server confirms kills in multiplayer, as clients don't count them.
+pub struct ExperienceSystem;
+
+impl<'s> System<'s> for ExperienceSystem {
+    type SystemData = (
+        GameStateHelper<'s>,
+        Entities<'s>,
+        ReadStorage<'s, ClientPlayerActions>,
+        ReadExpect<'s, RunStats>,
+        WriteExpect<'s, ExperienceAwards>,
+        WriteExpect<'s, Settings>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_state_helper,
+            entities,
+            client_player_actions,
+            run_stats,
+            mut experience_awards,
+            mut settings,
+        ): Self::SystemData,
+    ) {
+        if !game_state_helper.is_running() {
+            return;
+        }
+
+        let confirmed_kills = if game_state_helper.is_multiplayer() {
+            experience_awards.confirmed_kills
+        } else {
+            (&entities, &client_player_actions)
+                .join()
+                .next()
+                .map_or(0, |(player, _)| run_stats.confirmed_kills(player))
+        };
+        let new_kills = experience_awards.award(confirmed_kills);
+        if let Err(err) = settings.add_experience(new_kills * EXPERIENCE_PER_KILL) {
+            log::error!("Failed to save the player profile: {:?}", err);
+        }
+    }
+}
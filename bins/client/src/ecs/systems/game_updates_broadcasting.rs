@@ -13,11 +13,11 @@ use gv_core::{
         },
         system_data::time::GameTimeService,
     },
-    net::{client_message::ClientMessagePayload, INTERPOLATION_FRAME_DELAY},
+    net::client_message::ClientMessagePayload,
 };
 use gv_game::{ecs::system_data::GameStateHelper, utils::net::send_message_reliable};
 
-const BROADCAST_FRAME_INTERVAL: u64 = 5;
+const BROADCAST_INTERVAL_SECS: f32 = 1.0 / 12.0;
 
 #[derive(Default)]
 pub struct GameUpdatesBroadcastingSystem {
@@ -61,7 +61,8 @@ impl<'s> System<'s> for GameUpdatesBroadcastingSystem {
                 &mut transport,
                 net_connection,
                 ClientMessagePayload::WalkActions(ImmediatePlayerActionsUpdates {
-                    frame_number: game_time_service.game_frame_number() + INTERPOLATION_FRAME_DELAY,
+                    frame_number: game_time_service.game_frame_number()
+                        + game_time_service.interpolation_frame_delay(),
                     updates: client_world_updates.walk_action_updates.clone(),
                 }),
             );
@@ -73,7 +74,8 @@ impl<'s> System<'s> for GameUpdatesBroadcastingSystem {
                 &mut transport,
                 net_connection,
                 ClientMessagePayload::CastActions(ImmediatePlayerActionsUpdates {
-                    frame_number: game_time_service.game_frame_number() + INTERPOLATION_FRAME_DELAY,
+                    frame_number: game_time_service.game_frame_number()
+                        + game_time_service.interpolation_frame_delay(),
                     updates: client_world_updates.cast_action_updates.clone(),
                 }),
             );
@@ -83,18 +85,20 @@ impl<'s> System<'s> for GameUpdatesBroadcastingSystem {
         let is_time_to_broadcast = game_time_service
             .game_frame_number()
             .wrapping_sub(self.last_broadcasted_frame)
-            > BROADCAST_FRAME_INTERVAL;
+            > game_time_service.frames_in_seconds(BROADCAST_INTERVAL_SECS);
         if !is_time_to_broadcast {
             return;
         }
         self.last_broadcasted_frame = game_time_service.game_frame_number();
 
+        let interpolation_frame_delay = game_time_service.interpolation_frame_delay();
+
         send_message_reliable(
             &mut transport,
             net_connection,
             ClientMessagePayload::LookActions(PlayerLookActionUpdates {
                 updates: Vec::from_iter(client_world_updates.look_actions_updates.drain(..).map(
-                    |(frame_number, update)| (frame_number + INTERPOLATION_FRAME_DELAY, update),
+                    |(frame_number, update)| (frame_number + interpolation_frame_delay, update),
                 )),
             }),
         );
@@ -28,7 +28,7 @@ impl<'s> System<'s> for HealthUiSystem {
         let half_screen_height = screen_dimensions.height() / 2.0;
 
         for (player, health_ui) in (&players, &mut health_uis).join() {
-            health_ui.health = player.health / 100.0;
+            health_ui.health = player.health / player.max_health;
             health_ui.screen_position = Vector2::new(
                 -half_screen_width + HEALTH_UI_SCREEN_PADDING,
                 -half_screen_height + HEALTH_UI_SCREEN_PADDING,
@@ -36,7 +36,17 @@ impl<'s> System<'s> for HealthUiSystem {
 
             if let Some(ui_health_label) = ui_finder.find("ui_health_label") {
                 ui_texts.get_mut(ui_health_label).unwrap().text =
-                    format!("{:.0}/100", num::Float::max(0.0, player.health));
+                    if player.downed_since_frame.is_some() {
+                        "Downed".to_owned()
+                    } else {
+                        format!(
+                            "{:.0}/{:.0}  Mana: {:.0}/{:.0}",
+                            num::Float::max(0.0, player.health),
+                            player.max_health,
+                            player.mana,
+                            player.max_mana
+                        )
+                    };
             }
         }
     }
@@ -8,6 +8,7 @@ use gv_core::ecs::resources::{
     net::{MultiplayerGameState, PlayersNetStatus},
     GameEngineState,
 };
+use gv_game::ecs::resources::RollbackStats;
 
 use crate::ecs::resources::DisplayDebugInfoSettings;
 
@@ -20,6 +21,7 @@ impl<'s> System<'s> for ImguiNetworkDebugInfoSystem {
         ReadExpect<'s, MultiplayerGameState>,
         ReadExpect<'s, DisplayDebugInfoSettings>,
         ReadExpect<'s, PlayersNetStatus>,
+        ReadExpect<'s, RollbackStats>,
     );
 
     fn run(
@@ -30,6 +32,7 @@ impl<'s> System<'s> for ImguiNetworkDebugInfoSystem {
             multiplayer_game_state,
             display_debug_info_settings,
             players_net_status,
+            rollback_stats,
         ): Self::SystemData,
     ) {
         if !game_engine_state.is_playing() {
@@ -63,6 +66,10 @@ impl<'s> System<'s> for ImguiNetworkDebugInfoSystem {
                             return;
                         }
 
+                        ui.text(format!(
+                            "Resimulated frames: {}",
+                            rollback_stats.resimulated_frames
+                        ));
                         ui.columns(4, im_str!("Network Debug Info"), false);
                         ui.text("Name");
                         ui.next_column();
@@ -7,7 +7,6 @@ use amethyst::{
     renderer::Camera,
     shred::{ResourceId, SystemData},
     window::ScreenDimensions,
-    winit::MouseButton,
 };
 
 use gv_core::{
@@ -15,7 +14,7 @@ use gv_core::{
     ecs::components::{ClientPlayerActions, WorldPosition},
     math::Vector2,
 };
-use gv_game::ecs::system_data::GameStateHelper;
+use gv_game::ecs::{resources::SpellDefinitions, system_data::GameStateHelper};
 
 use std::collections::HashSet;
 
@@ -27,8 +26,12 @@ pub struct InputSystemData<'s> {
     screen_dimensions: ReadExpect<'s, ScreenDimensions>,
     transforms: ReadStorage<'s, Transform>,
     display_debug_info_settings: WriteExpect<'s, DisplayDebugInfoSettings>,
+    spell_definitions: ReadExpect<'s, SpellDefinitions>,
 }
 
+/// Actions which cast spells from the corresponding slots, the first one has a priority.
+const CAST_SPELL_ACTIONS: [&str; 3] = ["cast_spell_1", "cast_spell_2", "cast_spell_3"];
+
 #[derive(Default)]
 pub struct InputSystem {
     down_actions: HashSet<String>,
@@ -117,14 +120,16 @@ impl InputSystem {
             direction: mouse_world_position - player_position,
         };
 
-        if system_data.input.mouse_button_is_down(MouseButton::Left) {
-            client_player_actions.cast_action = Some(PlayerCastAction {
-                cast_position: player_position,
-                target_position: mouse_world_position,
-            });
-        } else {
-            client_player_actions.cast_action = None;
-        }
+        let input = &system_data.input;
+        let spell = CAST_SPELL_ACTIONS
+            .iter()
+            .position(|action| input.action_is_down(action).unwrap_or_default())
+            .and_then(|slot| system_data.spell_definitions.slot(slot));
+        client_player_actions.cast_action = spell.map(|spell| PlayerCastAction {
+            cast_position: player_position,
+            target_position: mouse_world_position,
+            spell: spell.id.clone(),
+        });
     }
 
     fn process_keyboard_input(
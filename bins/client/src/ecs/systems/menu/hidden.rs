@@ -25,6 +25,10 @@ impl MenuScreen for HiddenMenuScreen {
             };
         }
 
+        if system_data.game_pause.is_paused {
+            return StateUpdate::new_menu_screen(GameMenuScreen::PauseMenu);
+        }
+
         let disconnected_modal_window_is_shown =
             modal_window_id.map_or(false, |modal_window_id| modal_window_id == DISCONNECTED);
         if !disconnected_modal_window_is_shown {
@@ -0,0 +1,66 @@
+use super::*;
+
+pub struct LevelCompleteMenuScreen;
+
+impl MenuScreen for LevelCompleteMenuScreen {
+    fn elements_to_show(&self, system_data: &MenuSystemData) -> Vec<MenuElement> {
+        if is_campaign_complete(system_data) {
+            vec![UI_LEVEL_COMPLETE_LABEL, UI_MAIN_MENU_BUTTON]
+        } else {
+            vec![
+                UI_LEVEL_COMPLETE_LABEL,
+                UI_NEXT_LEVEL_BUTTON,
+                UI_MAIN_MENU_BUTTON,
+            ]
+        }
+    }
+
+    fn elements_to_hide(&self, _system_data: &MenuSystemData) -> Vec<MenuElement> {
+        vec![
+            UI_LEVEL_COMPLETE_LABEL,
+            UI_NEXT_LEVEL_BUTTON,
+            UI_MAIN_MENU_BUTTON,
+        ]
+    }
+
+    fn show(&mut self, system_data: &mut MenuSystemData) {
+        let title = match *system_data.game_mode {
+            _ if is_campaign_complete(system_data) => "The campaign is complete!".to_owned(),
+            GameMode::Campaign { level } => format!(
+                "\"{}\" is complete",
+                system_data.campaign.levels[level.saturating_sub(1)].name
+            ),
+            GameMode::Survival => "Level complete".to_owned(),
+        };
+        *system_data
+            .ui_finder
+            .get_ui_text_mut(&mut system_data.ui_texts, UI_LEVEL_COMPLETE_LABEL)
+            .expect("Expected a level complete label") = title;
+    }
+
+    fn update(
+        &mut self,
+        _system_data: &mut MenuSystemData,
+        button_pressed: Option<&str>,
+        _modal_window_id: Option<&str>,
+    ) -> StateUpdate {
+        match button_pressed {
+            Some(UI_NEXT_LEVEL_BUTTON) => StateUpdate::GameMenuUpdate {
+                game_engine_state: Some(GameEngineState::Playing),
+                menu_screen: Some(GameMenuScreen::Hidden),
+            },
+            Some(UI_MAIN_MENU_BUTTON) => StateUpdate::GameMenuUpdate {
+                game_engine_state: Some(GameEngineState::Menu),
+                menu_screen: Some(GameMenuScreen::MainMenu),
+            },
+            _ => StateUpdate::None,
+        }
+    }
+}
+
+fn is_campaign_complete(system_data: &MenuSystemData) -> bool {
+    match *system_data.game_mode {
+        GameMode::Campaign { level } => level >= system_data.campaign.levels.len(),
+        GameMode::Survival => false,
+    }
+}
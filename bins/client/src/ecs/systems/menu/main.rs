@@ -5,24 +5,42 @@ pub struct MainMenuScreen;
 impl MenuScreen for MainMenuScreen {
     fn elements_to_show(&self, _system_data: &MenuSystemData) -> Vec<MenuElement> {
         vec![
+            UI_CAMPAIGN_BUTTON,
             UI_SINGLE_PLAYER_BUTTON,
             UI_MULTIPLAYER_BUTTON,
+            UI_UPGRADES_BUTTON,
             UI_QUIT_BUTTON,
         ]
     }
 
     fn update(
         &mut self,
-        _system_data: &mut MenuSystemData,
+        system_data: &mut MenuSystemData,
         button_pressed: Option<&str>,
         _modal_window_id: Option<&str>,
     ) -> StateUpdate {
         match button_pressed {
-            Some(UI_SINGLE_PLAYER_BUTTON) => StateUpdate::GameMenuUpdate {
-                game_engine_state: Some(GameEngineState::Playing),
-                menu_screen: Some(GameMenuScreen::Hidden),
-            },
+            Some(UI_CAMPAIGN_BUTTON) => {
+                // Continues from the first level that hasn't been completed yet.
+                let last_level = system_data.campaign.levels.len() - 1;
+                let completed_levels = system_data.settings.campaign_progress().completed_levels;
+                *system_data.game_mode = GameMode::Campaign {
+                    level: completed_levels.min(last_level),
+                };
+                StateUpdate::GameMenuUpdate {
+                    game_engine_state: Some(GameEngineState::Playing),
+                    menu_screen: Some(GameMenuScreen::Hidden),
+                }
+            }
+            Some(UI_SINGLE_PLAYER_BUTTON) => {
+                *system_data.game_mode = GameMode::Survival;
+                StateUpdate::GameMenuUpdate {
+                    game_engine_state: Some(GameEngineState::Playing),
+                    menu_screen: Some(GameMenuScreen::Hidden),
+                }
+            }
             Some(UI_MULTIPLAYER_BUTTON) => StateUpdate::new_menu_screen(GameMenuScreen::LobbyMenu),
+            Some(UI_UPGRADES_BUTTON) => StateUpdate::new_menu_screen(GameMenuScreen::UpgradesMenu),
             Some(UI_QUIT_BUTTON) => StateUpdate::new_game_engine_state(GameEngineState::Quit),
             _ => StateUpdate::None,
         }
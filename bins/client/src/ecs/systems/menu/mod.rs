@@ -1,8 +1,11 @@
 mod hidden;
+mod level_complete;
 mod lobby;
 mod main;
 mod multiplayer_room;
+mod pause;
 mod restart;
+mod upgrades;
 
 use amethyst::{
     core::{HiddenPropagate, ParentHierarchy},
@@ -15,18 +18,23 @@ use lazy_static::lazy_static;
 
 use std::{collections::VecDeque, time::Duration};
 
-use gv_client_shared::ecs::resources::MultiplayerRoomState;
+use gv_client_shared::{ecs::resources::MultiplayerRoomState, settings::Settings};
 use gv_core::ecs::{
-    resources::{net::MultiplayerGameState, GameEngineState, GameLevelState, NewGameEngineState},
+    resources::{
+        net::MultiplayerGameState, stats::RunSummary, GameEngineState, GameLevelState, GamePause,
+        NewGameEngineState,
+    },
     system_data::time::GameTimeService,
 };
+use gv_game::ecs::resources::{Campaign, GameMode};
 
 use crate::ecs::{
     resources::UiNetworkCommandResource,
     system_data::ui::UiFinderMut,
     systems::menu::{
-        hidden::HiddenMenuScreen, lobby::LobbyMenuScreen, main::MainMenuScreen,
-        multiplayer_room::MultiplayerRoomMenuScreen, restart::RestartMenuScreen,
+        hidden::HiddenMenuScreen, level_complete::LevelCompleteMenuScreen, lobby::LobbyMenuScreen,
+        main::MainMenuScreen, multiplayer_room::MultiplayerRoomMenuScreen, pause::PauseMenuScreen,
+        restart::RestartMenuScreen, upgrades::UpgradesMenuScreen,
     },
 };
 
@@ -39,12 +47,26 @@ const MODAL_TAG: &str = "_modal";
 const UI_MAIN_CONTAINER: &str = "ui_main_container";
 const UI_LOADING_LABEL: &str = "ui_loading_label";
 
+const UI_CAMPAIGN_BUTTON: &str = "ui_campaign_button";
 const UI_SINGLE_PLAYER_BUTTON: &str = "ui_single_player_button";
 const UI_MULTIPLAYER_BUTTON: &str = "ui_multiplayer_button";
+const UI_UPGRADES_BUTTON: &str = "ui_upgrades_button";
 const UI_QUIT_BUTTON: &str = "ui_quit_button";
 
 const UI_RESTART_BUTTON: &str = "ui_restart_button";
 const UI_MAIN_MENU_BUTTON: &str = "ui_main_menu_button";
+const UI_RUN_SUMMARY_LABEL: &str = "ui_run_summary_label";
+
+const UI_UPGRADES_LABEL: &str = "ui_upgrades_label";
+const UI_UPGRADE_SPELL_DAMAGE_BUTTON: &str = "ui_upgrade_spell_damage_button";
+const UI_UPGRADE_CAST_COOLDOWN_BUTTON: &str = "ui_upgrade_cast_cooldown_button";
+const UI_UPGRADE_MOVE_SPEED_BUTTON: &str = "ui_upgrade_move_speed_button";
+const UI_UPGRADE_MAX_HEALTH_BUTTON: &str = "ui_upgrade_max_health_button";
+
+const UI_LEVEL_COMPLETE_LABEL: &str = "ui_level_complete_label";
+const UI_NEXT_LEVEL_BUTTON: &str = "ui_next_level_button";
+
+const UI_RESUME_BUTTON: &str = "ui_resume_button";
 
 const UI_LOBBY_NICKNAME_LABEL: &str = "ui_lobby_nickname_label";
 const UI_LOBBY_NICKNAME_FIELD: &str = "ui_lobby_nickname_field";
@@ -112,12 +134,14 @@ trait MenuScreen {
 
 lazy_static! {
     static ref MAIN_MENU_ELEMENTS: &'static [&'static str] = &[
+        UI_CAMPAIGN_BUTTON,
         UI_SINGLE_PLAYER_BUTTON,
         UI_MULTIPLAYER_BUTTON,
+        UI_UPGRADES_BUTTON,
         UI_QUIT_BUTTON,
     ];
     static ref RESTART_MENU_ELEMENTS: &'static [&'static str] =
-        &[UI_RESTART_BUTTON, UI_MAIN_MENU_BUTTON];
+        &[UI_RUN_SUMMARY_LABEL, UI_RESTART_BUTTON, UI_MAIN_MENU_BUTTON];
     static ref LOBBY_MENU_ELEMENTS: &'static [&'static str] = &[
         UI_LOBBY_NICKNAME_LABEL,
         UI_LOBBY_NICKNAME_FIELD,
@@ -171,9 +195,14 @@ pub struct MenuSystemData<'s> {
     game_engine_state: ReadExpect<'s, GameEngineState>,
     new_game_engine_state: WriteExpect<'s, NewGameEngineState>,
     game_level_state: WriteExpect<'s, GameLevelState>,
+    game_pause: WriteExpect<'s, GamePause>,
+    game_mode: WriteExpect<'s, GameMode>,
+    campaign: ReadExpect<'s, Campaign>,
+    run_summary: ReadExpect<'s, RunSummary>,
     ui_network_command: WriteExpect<'s, UiNetworkCommandResource>,
     multiplayer_room_state: ReadExpect<'s, MultiplayerRoomState>,
     multiplayer_game_state: ReadExpect<'s, MultiplayerGameState>,
+    settings: WriteExpect<'s, Settings>,
     ui_events: Write<'s, EventChannel<UiEvent>>,
     ui_texts: WriteStorage<'s, UiText>,
     ui_images: WriteStorage<'s, UiImage>,
@@ -204,6 +233,9 @@ struct MenuScreens {
     main_menu_screen: MainMenuScreen,
     multiplayer_room_menu_screen: MultiplayerRoomMenuScreen,
     restart_menu_screen: RestartMenuScreen,
+    upgrades_menu_screen: UpgradesMenuScreen,
+    level_complete_menu_screen: LevelCompleteMenuScreen,
+    pause_menu_screen: PauseMenuScreen,
     hidden_menu_screen: HiddenMenuScreen,
 }
 
@@ -214,6 +246,9 @@ impl MenuScreens {
             GameMenuScreen::MainMenu => Some(&mut self.main_menu_screen),
             GameMenuScreen::MultiplayerRoomMenu => Some(&mut self.multiplayer_room_menu_screen),
             GameMenuScreen::RestartMenu => Some(&mut self.restart_menu_screen),
+            GameMenuScreen::UpgradesMenu => Some(&mut self.upgrades_menu_screen),
+            GameMenuScreen::LevelCompleteMenu => Some(&mut self.level_complete_menu_screen),
+            GameMenuScreen::PauseMenu => Some(&mut self.pause_menu_screen),
             GameMenuScreen::Hidden => Some(&mut self.hidden_menu_screen),
             GameMenuScreen::Loading => None,
         }
@@ -261,15 +296,26 @@ impl MenuSystem {
                 main_menu_screen: MainMenuScreen,
                 multiplayer_room_menu_screen: MultiplayerRoomMenuScreen::new(),
                 restart_menu_screen: RestartMenuScreen,
+                upgrades_menu_screen: UpgradesMenuScreen,
+                level_complete_menu_screen: LevelCompleteMenuScreen,
+                pause_menu_screen: PauseMenuScreen,
                 hidden_menu_screen: HiddenMenuScreen,
             },
             modal_window_id: None,
             mouse_reactive: vec![
+                UI_CAMPAIGN_BUTTON,
                 UI_SINGLE_PLAYER_BUTTON,
                 UI_MULTIPLAYER_BUTTON,
+                UI_UPGRADES_BUTTON,
                 UI_QUIT_BUTTON,
                 UI_RESTART_BUTTON,
                 UI_MAIN_MENU_BUTTON,
+                UI_NEXT_LEVEL_BUTTON,
+                UI_UPGRADE_SPELL_DAMAGE_BUTTON,
+                UI_UPGRADE_CAST_COOLDOWN_BUTTON,
+                UI_UPGRADE_MOVE_SPEED_BUTTON,
+                UI_UPGRADE_MAX_HEALTH_BUTTON,
+                UI_RESUME_BUTTON,
                 UI_LOBBY_NICKNAME_EDITABLE,
                 UI_LOBBY_HOST_IP_EDITABLE,
                 UI_LOBBY_HOST_BUTTON,
@@ -302,6 +348,9 @@ enum GameMenuScreen {
     Loading,
     MainMenu,
     RestartMenu,
+    UpgradesMenu,
+    LevelCompleteMenu,
+    PauseMenu,
     LobbyMenu,
     MultiplayerRoomMenu,
     Hidden,
@@ -410,6 +459,13 @@ impl<'s> System<'s> for MenuSystem {
                     self.modal_window_id.as_deref(),
                 )
             }
+            (GameEngineState::Playing, GameMenuScreen::PauseMenu) => {
+                self.menu_screens.pause_menu_screen.update(
+                    &mut system_data,
+                    button_pressed.as_deref(),
+                    self.modal_window_id.as_deref(),
+                )
+            }
             (GameEngineState::Playing, menu_screen) if menu_screen != GameMenuScreen::Hidden => {
                 StateUpdate::new_menu_screen(GameMenuScreen::Hidden)
             }
@@ -418,6 +474,16 @@ impl<'s> System<'s> for MenuSystem {
                 button_pressed.as_deref(),
                 self.modal_window_id.as_deref(),
             ),
+            (GameEngineState::LevelComplete, GameMenuScreen::LevelCompleteMenu) => {
+                self.menu_screens.level_complete_menu_screen.update(
+                    &mut system_data,
+                    button_pressed.as_deref(),
+                    self.modal_window_id.as_deref(),
+                )
+            }
+            (GameEngineState::LevelComplete, _) => {
+                StateUpdate::new_menu_screen(GameMenuScreen::LevelCompleteMenu)
+            }
             _ => StateUpdate::None,
         };
 
@@ -0,0 +1,37 @@
+use super::*;
+
+pub struct PauseMenuScreen;
+
+impl MenuScreen for PauseMenuScreen {
+    fn elements_to_show(&self, _system_data: &MenuSystemData) -> Vec<MenuElement> {
+        vec![UI_RESUME_BUTTON, UI_MAIN_MENU_BUTTON]
+    }
+
+    fn update(
+        &mut self,
+        system_data: &mut MenuSystemData,
+        button_pressed: Option<&str>,
+        _modal_window_id: Option<&str>,
+    ) -> StateUpdate {
+        // The game can also be resumed with the pause key.
+        if !system_data.game_pause.is_paused {
+            return StateUpdate::new_menu_screen(GameMenuScreen::Hidden);
+        }
+
+        match button_pressed {
+            Some(UI_RESUME_BUTTON) => {
+                system_data.game_pause.is_paused = false;
+                StateUpdate::new_menu_screen(GameMenuScreen::Hidden)
+            }
+            Some(UI_MAIN_MENU_BUTTON) => {
+                // Makes MenuState clean up the level.
+                system_data.game_level_state.is_over = true;
+                StateUpdate::GameMenuUpdate {
+                    game_engine_state: Some(GameEngineState::Menu),
+                    menu_screen: Some(GameMenuScreen::MainMenu),
+                }
+            }
+            _ => StateUpdate::None,
+        }
+    }
+}
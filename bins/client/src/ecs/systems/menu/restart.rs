@@ -1,22 +1,63 @@
+use gv_core::ecs::resources::stats::RunSummary;
+
 use super::*;
+use crate::ecs::resources::UiNetworkCommand;
 
 pub struct RestartMenuScreen;
 
 impl MenuScreen for RestartMenuScreen {
-    fn elements_to_show(&self, _system_data: &MenuSystemData) -> Vec<MenuElement> {
-        vec![UI_RESTART_BUTTON, UI_MAIN_MENU_BUTTON]
+    fn elements_to_show(&self, system_data: &MenuSystemData) -> Vec<MenuElement> {
+        // A multiplayer game can't be restarted, as the server is already done with it.
+        if system_data.multiplayer_game_state.is_playing {
+            vec![UI_RUN_SUMMARY_LABEL, UI_MAIN_MENU_BUTTON]
+        } else {
+            vec![UI_RUN_SUMMARY_LABEL, UI_RESTART_BUTTON, UI_MAIN_MENU_BUTTON]
+        }
+    }
+
+    fn elements_to_hide(&self, _system_data: &MenuSystemData) -> Vec<MenuElement> {
+        vec![UI_RUN_SUMMARY_LABEL, UI_RESTART_BUTTON, UI_MAIN_MENU_BUTTON]
+    }
+
+    fn show(&mut self, system_data: &mut MenuSystemData) {
+        let run_summary = run_summary_text(&system_data.run_summary);
+        *system_data
+            .ui_finder
+            .get_ui_text_mut(&mut system_data.ui_texts, UI_RUN_SUMMARY_LABEL)
+            .expect("Expected a run summary label") = run_summary;
     }
 
     fn update(
         &mut self,
-        _system_data: &mut MenuSystemData,
+        system_data: &mut MenuSystemData,
         button_pressed: Option<&str>,
         _modal_window_id: Option<&str>,
     ) -> StateUpdate {
         match button_pressed {
             Some(UI_RESTART_BUTTON) => StateUpdate::new_game_engine_state(GameEngineState::Playing),
+            Some(UI_MAIN_MENU_BUTTON) if system_data.multiplayer_game_state.is_playing => {
+                system_data.ui_network_command.command = Some(UiNetworkCommand::Leave);
+                StateUpdate::new_menu_screen(GameMenuScreen::LobbyMenu)
+            }
             Some(UI_MAIN_MENU_BUTTON) => StateUpdate::new_menu_screen(GameMenuScreen::MainMenu),
             _ => StateUpdate::None,
         }
     }
 }
+
+fn run_summary_text(run_summary: &RunSummary) -> String {
+    let mut text = format!("Waves reached: {}", run_summary.waves_reached);
+    for player in &run_summary.players {
+        text += &format!(
+            "\n{}: {} kills, {:.0} damage dealt, {:.0} damage taken, {}/{} missiles hit, survived {:.0}s",
+            player.nickname,
+            player.stats.kills,
+            player.stats.damage_dealt,
+            player.stats.damage_taken,
+            player.stats.missiles_hit,
+            player.stats.missiles_cast,
+            player.time_survived_secs,
+        );
+    }
+    text
+}
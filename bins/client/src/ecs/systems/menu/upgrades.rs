@@ -0,0 +1,97 @@
+use gv_core::progression::{PlayerProfile, Upgrade, EXPERIENCE_PER_LEVEL, MAX_UPGRADE_LEVEL};
+
+use super::*;
+
+const UPGRADE_BUTTONS: [(MenuElement, Upgrade); 4] = [
+    (UI_UPGRADE_SPELL_DAMAGE_BUTTON, Upgrade::SpellDamage),
+    (UI_UPGRADE_CAST_COOLDOWN_BUTTON, Upgrade::CastCooldown),
+    (UI_UPGRADE_MOVE_SPEED_BUTTON, Upgrade::MoveSpeed),
+    (UI_UPGRADE_MAX_HEALTH_BUTTON, Upgrade::MaxHealth),
+];
+
+pub struct UpgradesMenuScreen;
+
+impl MenuScreen for UpgradesMenuScreen {
+    fn elements_to_show(&self, _system_data: &MenuSystemData) -> Vec<MenuElement> {
+        vec![
+            UI_UPGRADES_LABEL,
+            UI_UPGRADE_SPELL_DAMAGE_BUTTON,
+            UI_UPGRADE_CAST_COOLDOWN_BUTTON,
+            UI_UPGRADE_MOVE_SPEED_BUTTON,
+            UI_UPGRADE_MAX_HEALTH_BUTTON,
+            UI_MAIN_MENU_BUTTON,
+        ]
+    }
+
+    fn show(&mut self, system_data: &mut MenuSystemData) {
+        let profile_text = profile_text(system_data.settings.profile());
+        *system_data
+            .ui_finder
+            .get_ui_text_mut(&mut system_data.ui_texts, UI_UPGRADES_LABEL)
+            .expect("Expected an upgrades label") = profile_text;
+    }
+
+    fn update(
+        &mut self,
+        system_data: &mut MenuSystemData,
+        button_pressed: Option<&str>,
+        _modal_window_id: Option<&str>,
+    ) -> StateUpdate {
+        if let Some((button, upgrade)) = UPGRADE_BUTTONS
+            .iter()
+            .find(|(button, _)| Some(*button) == button_pressed)
+        {
+            match system_data.settings.buy_upgrade(*upgrade) {
+                Ok(true) => self.show(system_data),
+                Ok(false) => log::debug!("Can't buy an upgrade: {:?}", upgrade),
+                Err(err) => log::error!("Failed to save the player profile: {:?}", err),
+            }
+            // Makes the button clickable again.
+            return StateUpdate::CustomAnimation {
+                elements_to_hide: vec![],
+                elements_to_show: vec![*button],
+            };
+        }
+
+        match button_pressed {
+            Some(UI_MAIN_MENU_BUTTON) => StateUpdate::new_menu_screen(GameMenuScreen::MainMenu),
+            _ => StateUpdate::None,
+        }
+    }
+}
+
+fn profile_text(profile: &PlayerProfile) -> String {
+    let mut text = format!(
+        "Level {} ({}/{} XP), points to spend: {}",
+        profile.level(),
+        profile.experience % EXPERIENCE_PER_LEVEL,
+        EXPERIENCE_PER_LEVEL,
+        profile.unspent_points(),
+    );
+    let upgrades = &profile.upgrades;
+    text += &format!(
+        "\nSpell damage: {}/{} (+{:.0}%)",
+        upgrades.spell_damage,
+        MAX_UPGRADE_LEVEL,
+        (upgrades.spell_damage_multiplier() - 1.0) * 100.0
+    );
+    text += &format!(
+        "\nCast cooldown: {}/{} (-{:.0}%)",
+        upgrades.cast_cooldown,
+        MAX_UPGRADE_LEVEL,
+        (1.0 - upgrades.cast_cooldown_multiplier()) * 100.0
+    );
+    text += &format!(
+        "\nMove speed: {}/{} ({:.0})",
+        upgrades.move_speed,
+        MAX_UPGRADE_LEVEL,
+        upgrades.move_speed()
+    );
+    text += &format!(
+        "\nMax health: {}/{} ({:.0})",
+        upgrades.max_health,
+        MAX_UPGRADE_LEVEL,
+        upgrades.max_health()
+    );
+    text
+}
@@ -2,6 +2,7 @@ mod animation;
 mod camera_translation;
 mod client_network;
 mod custom_sprite_sorting;
+mod experience;
 mod game_updates_broadcasting;
 mod hud;
 mod imgui_network_debug_info;
@@ -9,12 +10,14 @@ mod input;
 mod menu;
 mod overlay;
 mod particle;
+mod status_effect_tint;
 
 pub use self::{
     animation::AnimationSystem,
     camera_translation::CameraTranslationSystem,
     client_network::ClientNetworkSystem,
     custom_sprite_sorting::{CustomSpriteSortingSystem, SpriteOrdering},
+    experience::ExperienceSystem,
     game_updates_broadcasting::GameUpdatesBroadcastingSystem,
     hud::HealthUiSystem,
     imgui_network_debug_info::ImguiNetworkDebugInfoSystem,
@@ -22,4 +25,5 @@ pub use self::{
     menu::MenuSystem,
     overlay::OverlaySystem,
     particle::ParticleSystem,
+    status_effect_tint::{status_effect_tint, StatusEffectTintSystem},
 };
@@ -9,7 +9,10 @@ use std::f32::consts::PI;
 use gv_client_shared::ecs::components::SpellParticle;
 use gv_core::{
     ecs::{
-        components::{missile::Missile, Dead, WorldPosition},
+        components::{
+            missile::{Missile, MissileBehaviour},
+            Dead, WorldPosition,
+        },
         system_data::time::GameTimeService,
     },
     math::{Vector2, Vector3, ZeroVector},
@@ -49,6 +52,10 @@ impl<'s> System<'s> for ParticleSystem {
         let mut rng = rand::thread_rng();
         let frame_number = game_time_service.game_frame_number();
         for (missile_entity, missile) in (&entities, &missiles).join() {
+            // Novas are drawn as rings by `NovaPlugin`.
+            if let MissileBehaviour::Nova { .. } = missile.spell.behaviour {
+                continue;
+            }
             let is_dead = is_dead(missile_entity, &dead, game_time_service.game_frame_number());
             let missile_energy = missile_energy(
                 missile,
@@ -0,0 +1,78 @@
+use amethyst::{
+    core::Parent,
+    ecs::{Entities, Join, ReadStorage, System, WriteStorage},
+    renderer::{palette::Srgba, resources::Tint, SpriteRender},
+};
+
+use gv_core::ecs::{
+    components::{
+        status_effect::{StatusEffectKind, StatusEffects},
+        Monster,
+    },
+    system_data::time::GameTimeService,
+};
+
+/// Effects which are listed first win when an entity has several of them.
+const STATUS_EFFECT_TINTS: [(StatusEffectKind, [f32; 3]); 4] = [
+    (StatusEffectKind::Stun, [1.0, 1.0, 0.4]),
+    (StatusEffectKind::Burn, [1.0, 0.5, 0.2]),
+    (StatusEffectKind::Poison, [0.5, 1.0, 0.4]),
+    (StatusEffectKind::Slow, [0.5, 0.7, 1.0]),
+];
+
+pub fn status_effect_tint(status_effects: &StatusEffects, frame_number: u64) -> Option<[f32; 3]> {
+    STATUS_EFFECT_TINTS
+        .iter()
+        .find(|(kind, _)| {
+            status_effects
+                .active(frame_number)
+                .any(|effect| effect.kind == *kind)
+        })
+        .map(|(_, tint)| *tint)
+}
+
+/// Tints sprites of monsters which are affected by status effects. Players are tinted
+/// by `DrawFlat2DTransparent`, as it mixes the tint with their colors.
+pub struct StatusEffectTintSystem;
+
+impl<'s> System<'s> for StatusEffectTintSystem {
+    type SystemData = (
+        GameTimeService<'s>,
+        Entities<'s>,
+        ReadStorage<'s, Monster>,
+        ReadStorage<'s, StatusEffects>,
+        ReadStorage<'s, Parent>,
+        ReadStorage<'s, SpriteRender>,
+        WriteStorage<'s, Tint>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_time_service,
+            entities,
+            monsters,
+            status_effects,
+            parents,
+            sprite_renders,
+            mut tints,
+        ): Self::SystemData,
+    ) {
+        let frame_number = game_time_service.game_frame_number();
+        for (entity, parent, _) in (&entities, &parents, &sprite_renders).join() {
+            if !monsters.contains(parent.entity) {
+                continue;
+            }
+            let tint = status_effects
+                .get(parent.entity)
+                .and_then(|status_effects| status_effect_tint(status_effects, frame_number));
+            if let Some(tint) = tint {
+                tints
+                    .insert(entity, Tint(Srgba::new(tint[0], tint[1], tint[2], 1.0)))
+                    .expect("Expected to insert a Tint");
+            } else {
+                tints.remove(entity);
+            }
+        }
+    }
+}
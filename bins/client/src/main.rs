@@ -29,13 +29,17 @@ use std::{
     env,
     io::{Error, ErrorKind},
     path::PathBuf,
+    time::Duration,
 };
 
 use gv_animation_prefabs::{AnimationId, GameSpriteAnimationPrefab};
 use gv_client_shared::{ecs::resources::MultiplayerRoomState, settings::Settings};
-use gv_core::ecs::resources::{
-    net::PlayersNetStatus,
-    world::{ClientWorldUpdates, FramedUpdates, ReceivedServerWorldUpdate},
+use gv_core::{
+    ecs::resources::{
+        net::PlayersNetStatus,
+        world::{ClientWorldUpdates, FramedUpdates, ReceivedServerWorldUpdate},
+    },
+    net::TickRate,
 };
 use gv_game::{
     build_game_logic_systems,
@@ -114,11 +118,26 @@ fn main() -> amethyst::Result<()> {
 
     change_to_resources_parent_dir()?;
 
-    let _cli_matches = clap::App::new("grumpy_visitors")
+    let cli_matches = clap::App::new("grumpy_visitors")
         .version("0.1")
         .author("Vladyslav Batyrenko <mvlabat@gmail.com>")
         .about("A prototype of a top-down EvilInvasion-like 2D arcade/action")
+        .arg(
+            clap::Arg::with_name("tick-rate")
+                .short("t")
+                .long("tick-rate")
+                .value_name("TICK_RATE")
+                .help("Specifies the number of simulated frames per second")
+                .possible_values(&["30", "60", "120"])
+                .default_value("60")
+                .takes_value(true),
+        )
         .get_matches();
+    let tick_rate = cli_matches
+        .value_of("tick-rate")
+        .and_then(|tick_rate| tick_rate.parse().ok())
+        .and_then(TickRate::from_frames_per_second)
+        .expect("Expected a valid tick rate");
 
     let socket_addr = "0.0.0.0:0";
 
@@ -149,6 +168,7 @@ fn main() -> amethyst::Result<()> {
     let mut builder = Application::build("./", LoadingState::default())?;
     builder.world.insert(settings);
     builder.world.insert(ServerCommand::new());
+    builder.world.insert(tick_rate);
 
     // The resources which we need to remember to reset on starting a game.
     builder.world.insert(DisplayDebugInfoSettings::default());
@@ -220,6 +240,16 @@ fn main() -> amethyst::Result<()> {
             &["parent_hierarchy_system"],
         )
         .with(HealthUiSystem, "health_ui_system", &["action_system"])
+        .with(
+            ExperienceSystem,
+            "experience_system",
+            &["dead_cleanup_system"],
+        )
+        .with(
+            StatusEffectTintSystem,
+            "status_effect_tint_system",
+            &["action_system"],
+        )
         .with_bundle(UiBundle::<StringBindings>::new())?
         .with(
             AnimationSystem,
@@ -246,6 +276,7 @@ fn main() -> amethyst::Result<()> {
                 .with_plugin(RenderFlat2D::default())
                 .with_plugin(PaintMagePlugin::default())
                 .with_plugin(MissilePlugin::default())
+                .with_plugin(NovaPlugin::default())
                 .with_plugin(SpellParticlePlugin::default())
                 .with_plugin(MobHealthPlugin::default())
                 .with_plugin(HealthUiPlugin::default())
@@ -254,7 +285,11 @@ fn main() -> amethyst::Result<()> {
         )?;
 
     let mut game = builder
-        .with_frame_limit(FrameRateLimitStrategy::Yield, 60)
+        .with_frame_limit(
+            FrameRateLimitStrategy::Yield,
+            tick_rate.client_frame_rate_limit(),
+        )
+        .with_fixed_step_length(Duration::from_secs_f32(tick_rate.fixed_seconds()))
         .build(game_data_builder)?;
 
     game.run();
@@ -27,16 +27,29 @@ use amethyst::{
     },
 };
 use derivative::Derivative;
-use glsl_layout::{float, vec2, AsStd140};
+use glsl_layout::{float, vec2, vec3, AsStd140};
 
 use std::path::PathBuf;
 
 use gv_core::ecs::{
-    components::{missile::Missile, Dead},
+    components::{
+        damage_history::DamageType,
+        missile::{Missile, MissileBehaviour, MonsterProjectile},
+        pickup::{Pickup, PickupKind},
+        Dead,
+    },
     system_data::time::GameTimeService,
 };
 use gv_game::{ecs::systems::missile::MISSILE_TTL_SECS, utils::entities::missile_energy};
 
+const MISSILE_LIGHT_COLOR: [f32; 3] = [1.0, 0.0, 0.0];
+const FIRE_MISSILE_LIGHT_COLOR: [f32; 3] = [1.0, 0.45, 0.0];
+const FROST_MISSILE_LIGHT_COLOR: [f32; 3] = [0.4, 0.8, 1.0];
+const MONSTER_PROJECTILE_LIGHT_COLOR: [f32; 3] = [0.3, 1.0, 0.1];
+const HEALTH_ORB_LIGHT_COLOR: [f32; 3] = [1.0, 0.2, 0.4];
+const DAMAGE_BOOST_LIGHT_COLOR: [f32; 3] = [1.0, 0.6, 0.0];
+const SPEED_BUFF_LIGHT_COLOR: [f32; 3] = [0.1, 0.6, 1.0];
+
 /// A [RenderPlugin] for drawing 2d objects with flat shading.
 /// Required to display sprites defined with [SpriteRender] component.
 #[derive(Default, Debug)]
@@ -150,6 +163,7 @@ pub struct MissileVertexData {
     pub opacity: float,
     /// Time to live (from 1.0 to 0.0).
     pub ttl: float,
+    pub light_color: vec3,
 }
 
 impl AsVertex for MissileVertexData {
@@ -159,6 +173,7 @@ impl AsVertex for MissileVertexData {
             (Format::R32Sfloat, "seconds_since_spawn"),
             (Format::R32Sfloat, "opacity"),
             (Format::R32Sfloat, "ttl"),
+            (Format::Rgb32Sfloat, "light_color"),
         ))
     }
 }
@@ -181,17 +196,23 @@ impl<B: Backend> RenderGroup<B, World> for DrawMissile<B> {
         _subpass: hal::pass::Subpass<'_, B>,
         world: &World,
     ) -> PrepareResult {
-        let (game_time_service, transforms, missiles, dead) = <(
-            GameTimeService<'_>,
-            ReadStorage<'_, Transform>,
-            ReadStorage<'_, Missile>,
-            ReadStorage<'_, Dead>,
-        )>::fetch(world);
+        let (game_time_service, transforms, missiles, monster_projectiles, pickups, dead) =
+            <(
+                GameTimeService<'_>,
+                ReadStorage<'_, Transform>,
+                ReadStorage<'_, Missile>,
+                ReadStorage<'_, MonsterProjectile>,
+                ReadStorage<'_, Pickup>,
+                ReadStorage<'_, Dead>,
+            )>::fetch(world);
 
         self.env.process(factory, index, world);
 
         let vertices = (&transforms, &missiles, dead.maybe())
             .join()
+            .filter(|(_, missile, _)| {
+                !matches!(missile.spell.behaviour, MissileBehaviour::Nova { .. })
+            })
             .map(|(transform, missile, dead)| {
                 let transform = convert::<_, Matrix4<f32>>(*transform.global_matrix());
                 let pos = (transform * Vector4::new(0.0, 0.0, 0.0, 1.0))
@@ -206,19 +227,55 @@ impl<B: Backend> RenderGroup<B, World> for DrawMissile<B> {
                     &game_time_service,
                     game_time_service.game_frame_number(),
                 );
-                let ttl = dead.map_or(1.0, |dead| {
-                    1.0 - game_time_service
-                        .seconds_to_frame(dead.dead_since_frame)
-                        .clamp(0.0, MISSILE_TTL_SECS)
-                        / MISSILE_TTL_SECS
-                });
                 MissileVertexData {
                     pos,
                     seconds_since_spawn,
                     opacity,
-                    ttl,
+                    ttl: ttl(dead, &game_time_service),
+                    light_color: missile_light_color(missile.spell.damage_type).into(),
                 }
             })
+            .chain(
+                (&transforms, &monster_projectiles, dead.maybe())
+                    .join()
+                    .map(|(transform, projectile, dead)| {
+                        let transform = convert::<_, Matrix4<f32>>(*transform.global_matrix());
+                        let pos = (transform * Vector4::new(0.0, 0.0, 0.0, 1.0))
+                            .xy()
+                            .into_pod();
+                        MissileVertexData {
+                            pos,
+                            seconds_since_spawn: game_time_service
+                                .seconds_to_frame(projectile.frame_spawned),
+                            opacity: 1.0,
+                            ttl: ttl(dead, &game_time_service),
+                            light_color: MONSTER_PROJECTILE_LIGHT_COLOR.into(),
+                        }
+                    }),
+            )
+            .chain(
+                (&transforms, &pickups, dead.maybe())
+                    .join()
+                    .map(|(transform, pickup, dead)| {
+                        let transform = convert::<_, Matrix4<f32>>(*transform.global_matrix());
+                        let pos = (transform * Vector4::new(0.0, 0.0, 0.0, 1.0))
+                            .xy()
+                            .into_pod();
+                        let light_color = match pickup.kind {
+                            PickupKind::HealthOrb => HEALTH_ORB_LIGHT_COLOR,
+                            PickupKind::DamageBoost => DAMAGE_BOOST_LIGHT_COLOR,
+                            PickupKind::SpeedBuff => SPEED_BUFF_LIGHT_COLOR,
+                        };
+                        MissileVertexData {
+                            pos,
+                            seconds_since_spawn: game_time_service
+                                .seconds_to_frame(pickup.frame_spawned),
+                            opacity: 1.0,
+                            ttl: ttl(dead, &game_time_service),
+                            light_color: light_color.into(),
+                        }
+                    }),
+            )
             .collect::<Vec<_>>();
 
         self.missiles_count = vertices.len() as u32;
@@ -256,6 +313,23 @@ impl<B: Backend> RenderGroup<B, World> for DrawMissile<B> {
     }
 }
 
+pub(super) fn missile_light_color(damage_type: DamageType) -> [f32; 3] {
+    match damage_type {
+        DamageType::Fire => FIRE_MISSILE_LIGHT_COLOR,
+        DamageType::Frost => FROST_MISSILE_LIGHT_COLOR,
+        _ => MISSILE_LIGHT_COLOR,
+    }
+}
+
+fn ttl(dead: Option<&Dead>, game_time_service: &GameTimeService<'_>) -> f32 {
+    dead.map_or(1.0, |dead| {
+        1.0 - game_time_service
+            .seconds_to_frame(dead.dead_since_frame)
+            .clamp(0.0, MISSILE_TTL_SECS)
+            / MISSILE_TTL_SECS
+    })
+}
+
 fn build_sprite_pipeline<B: Backend>(
     factory: &Factory<B>,
     subpass: hal::pass::Subpass<'_, B>,
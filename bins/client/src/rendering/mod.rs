@@ -1,11 +1,13 @@
 pub use health_ui::HealthUiPlugin;
 pub use missile::MissilePlugin;
 pub use mob_health::MobHealthPlugin;
+pub use nova::NovaPlugin;
 pub use paint_mage::PaintMagePlugin;
 pub use spell_particle::SpellParticlePlugin;
 
 mod health_ui;
 mod missile;
 mod mob_health;
+mod nova;
 mod paint_mage;
 mod spell_particle;
@@ -35,8 +35,9 @@ use derivative::Derivative;
 use std::path::PathBuf;
 
 use gv_client_shared::ecs::components::PlayerColor;
+use gv_core::ecs::{components::status_effect::StatusEffects, system_data::time::GameTimeService};
 
-use crate::ecs::systems::{CustomSpriteSortingSystem, SpriteOrdering};
+use crate::ecs::systems::{status_effect_tint, CustomSpriteSortingSystem, SpriteOrdering};
 
 /// A [RenderPlugin] for drawing 2d objects with flat shading.
 /// Required to display sprites defined with [SpriteRender] component.
@@ -183,6 +184,8 @@ impl<B: Backend> RenderGroup<B, World> for DrawFlat2DTransparent<B> {
             transforms,
             player_colors,
             parents,
+            status_effects,
+            game_time_service,
         ) = <(
             Read<'_, AssetStorage<SpriteSheet>>,
             Read<'_, AssetStorage<Texture>>,
@@ -191,7 +194,10 @@ impl<B: Backend> RenderGroup<B, World> for DrawFlat2DTransparent<B> {
             ReadStorage<'_, Transform>,
             ReadStorage<'_, PlayerColor>,
             ReadStorage<'_, Parent>,
+            ReadStorage<'_, StatusEffects>,
+            GameTimeService<'_>,
         )>::fetch(world);
+        let frame_number = game_time_service.game_frame_number();
 
         self.env.process(factory, index, world);
         self.sprites.swap_clear();
@@ -208,7 +214,16 @@ impl<B: Backend> RenderGroup<B, World> for DrawFlat2DTransparent<B> {
                 .iter()
                 .filter_map(|e| joined.get_unchecked(e.id()))
                 .filter_map(|(sprite_render, global, parent)| {
-                    let player_color = player_colors.get(parent.entity)?.0;
+                    let mut player_color = player_colors.get(parent.entity)?.0;
+                    // Status effects are shown by mixing their tint with the player color.
+                    if let Some(tint) = status_effects
+                        .get(parent.entity)
+                        .and_then(|status_effects| status_effect_tint(status_effects, frame_number))
+                    {
+                        for (color, tint) in player_color.iter_mut().zip(tint.iter()) {
+                            *color = (*color + tint) / 2.0;
+                        }
+                    }
 
                     let tint_linear = Alpha::<Rgb<Linear<_>>, _>::new(
                         player_color[0],
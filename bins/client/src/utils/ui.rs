@@ -5,6 +5,9 @@ pub fn disconnect_reason_title(disconnect_reason: DisconnectReason) -> String {
         DisconnectReason::Uninitialized => "The server is not initialized yet".to_owned(),
         DisconnectReason::GameIsStarted => "The server has already started the game".to_owned(),
         DisconnectReason::RoomIsFull => "The room is full".to_owned(),
+        DisconnectReason::DefinitionsMismatch => {
+            "The game data differs from the server one".to_owned()
+        }
         DisconnectReason::Kick => "You've been kicked".to_owned(),
         DisconnectReason::Closed => "The host has closed the server".to_owned(),
         DisconnectReason::ServerCrashed(exit_code) => {
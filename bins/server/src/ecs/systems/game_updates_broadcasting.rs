@@ -14,7 +14,7 @@ use gv_game::{ecs::system_data::GameStateHelper, utils::net::send_message_unreli
 
 use crate::ecs::resources::LastBroadcastedFrame;
 
-const BROADCAST_FRAME_INTERVAL: u64 = 5;
+const BROADCAST_INTERVAL_SECS: f32 = 1.0 / 12.0;
 
 #[derive(Default)]
 pub struct GameUpdatesBroadcastingSystem;
@@ -49,7 +49,7 @@ impl<'s> System<'s> for GameUpdatesBroadcastingSystem {
         let is_time_to_broadcast = game_time_service
             .game_frame_number()
             .wrapping_sub(*last_broadcasted_frame)
-            > BROADCAST_FRAME_INTERVAL;
+            > game_time_service.frames_in_seconds(BROADCAST_INTERVAL_SECS);
         if !is_time_to_broadcast {
             return;
         }
@@ -11,40 +11,50 @@ use gv_core::{
     ecs::{
         components::NetConnectionModel,
         resources::{
-            net::{ActionUpdateIdProvider, MultiplayerGameState, MultiplayerRoomPlayer},
+            net::{
+                ActionUpdateIdProvider, EntityNetMetadataStorage, MultiplayerGameState,
+                MultiplayerRoomPlayer,
+            },
+            stats::{RunStats, RunSummary},
             world::{
                 FramedUpdates, ImmediatePlayerActionsUpdates, PlayerLookActionUpdates,
-                ReceivedClientActionUpdates, ServerWorldUpdates, LAG_COMPENSATION_FRAMES_LIMIT,
-                PAUSE_FRAME_THRESHOLD,
+                ReceivedClientActionUpdates, ServerWorldUpdates,
             },
-            GameEngineState, NewGameEngineState,
+            GameEngineState, GameLevelState, NewGameEngineState,
         },
         system_data::time::GameTimeService,
     },
     net::{
         client_message::{ClientMessage, ClientMessagePayload},
         server_message::{DisconnectReason, ServerMessagePayload},
-        NetEvent, NetIdentifier, NetUpdate, INTERPOLATION_FRAME_DELAY,
+        NetEvent, NetIdentifier, NetUpdate,
     },
+    progression::PlayerProfile,
     PLAYER_COLORS,
 };
 use gv_game::{
-    ecs::resources::ConnectionEvents,
-    utils::net::{broadcast_message_reliable, broadcast_message_unreliable, send_message_reliable},
+    ecs::resources::{ConnectionEvents, MonsterDefinitions, SpellDefinitions},
+    utils::net::{
+        broadcast_message_reliable, broadcast_message_unreliable, reject_on_checksum_mismatch,
+        send_message_reliable,
+    },
 };
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use crate::ecs::resources::{HostClientAddress, LastBroadcastedFrame};
 use gv_core::net::server_message::PlayerNetStatus;
 
-const HEARTBEAT_FRAME_INTERVAL: u64 = 2;
-const REPORT_PLAYERS_STATUS_FRAME_INTERVAL: u64 = 50;
+const HEARTBEAT_INTERVAL_SECS: f32 = 1.0 / 30.0;
+const REPORT_PLAYERS_STATUS_INTERVAL_SECS: f32 = 5.0 / 6.0;
 
 pub struct ServerNetworkSystem {
     host_connection_id: Option<NetIdentifier>,
     last_heartbeat_frame: u64,
     last_report_players_status_frame: u64,
+    has_sent_game_over: bool,
+    /// Confirmed kills sent to each connection.
+    reported_kills: HashMap<NetIdentifier, u32>,
 }
 
 impl ServerNetworkSystem {
@@ -53,6 +63,8 @@ impl ServerNetworkSystem {
             host_connection_id: None,
             last_heartbeat_frame: 0,
             last_report_players_status_frame: 0,
+            has_sent_game_over: false,
+            reported_kills: HashMap::new(),
         }
     }
 
@@ -68,7 +80,13 @@ impl<'s> System<'s> for ServerNetworkSystem {
         GameTimeService<'s>,
         Entities<'s>,
         ReadExpect<'s, GameEngineState>,
+        ReadExpect<'s, GameLevelState>,
         ReadExpect<'s, LastBroadcastedFrame>,
+        ReadExpect<'s, MonsterDefinitions>,
+        ReadExpect<'s, SpellDefinitions>,
+        ReadExpect<'s, RunSummary>,
+        ReadExpect<'s, RunStats>,
+        ReadExpect<'s, EntityNetMetadataStorage>,
         WriteExpect<'s, ConnectionEvents>,
         WriteExpect<'s, HostClientAddress>,
         WriteExpect<'s, MultiplayerGameState>,
@@ -87,7 +105,13 @@ impl<'s> System<'s> for ServerNetworkSystem {
             game_time_service,
             entities,
             game_engine_state,
+            game_level_state,
             last_broadcasted_frame,
+            monster_definitions,
+            spell_definitions,
+            run_summary,
+            run_stats,
+            entity_net_metadata_storage,
             mut connection_events,
             mut host_client_address,
             mut multiplayer_game_state,
@@ -226,7 +250,44 @@ impl<'s> System<'s> for ServerNetworkSystem {
                     ClientMessagePayload::JoinRoom {
                         nickname,
                         sent_at: _,
+                        monster_definitions_checksum,
+                        spell_definitions_checksum,
+                        experience,
+                        upgrades,
                     } => {
+                        let is_rejected = reject_on_checksum_mismatch(
+                            &mut transport,
+                            net_connection_model,
+                            "monster",
+                            monster_definitions.checksum,
+                            monster_definitions_checksum,
+                        ) || reject_on_checksum_mismatch(
+                            &mut transport,
+                            net_connection_model,
+                            "spell",
+                            spell_definitions.checksum,
+                            spell_definitions_checksum,
+                        );
+                        if is_rejected {
+                            continue;
+                        }
+
+                        // Profiles live on clients, so an edited one can't be detected,
+                        // but a player never gets more upgrades than their experience allows.
+                        let profile = PlayerProfile {
+                            experience,
+                            upgrades,
+                        };
+                        let upgrades = upgrades.clamped(profile.points());
+                        if upgrades != profile.upgrades {
+                            log::warn!(
+                                "A client ({}) {} has spent more upgrade points than it has ({}), trimming the upgrades",
+                                connection_id,
+                                net_connection_model.addr,
+                                profile.points()
+                            );
+                        }
+
                         let is_host = if multiplayer_game_state.players.is_empty() {
                             if let Some(host_connection_id) = self.host_connection_id {
                                 if host_connection_id != connection_id {
@@ -259,8 +320,9 @@ impl<'s> System<'s> for ServerNetworkSystem {
                             .iter_mut()
                             .find(|player| player.connection_id == connection_id)
                         {
-                            log::info!("The player already existed, updating the nickname only");
+                            log::info!("The player already existed, updating the nickname and upgrades only");
                             player.nickname = nickname;
+                            player.upgrades = upgrades;
                         } else {
                             let new_player_count = multiplayer_game_state.players.len();
                             if new_player_count >= 4 {
@@ -281,6 +343,7 @@ impl<'s> System<'s> for ServerNetworkSystem {
                                     nickname,
                                     is_host: self.is_host(connection_id),
                                     color: PLAYER_COLORS[new_player_count],
+                                    upgrades,
                                 });
                         }
 
@@ -300,6 +363,7 @@ impl<'s> System<'s> for ServerNetworkSystem {
                     {
                         multiplayer_game_state.is_playing = true;
                         new_game_engine_state.0 = GameEngineState::Playing;
+                        self.reported_kills.clear();
                     }
                     ClientMessagePayload::StartHostedGame => {
                         log::warn!(
@@ -314,11 +378,8 @@ impl<'s> System<'s> for ServerNetworkSystem {
                             game_time_service.game_frame_number(),
                             actions
                         );
-                        let discarded_actions = add_walk_actions(
-                            &mut *framed_updates,
-                            actions,
-                            game_time_service.game_frame_number(),
-                        );
+                        let discarded_actions =
+                            add_walk_actions(&mut *framed_updates, actions, &game_time_service);
 
                         if !discarded_actions.is_empty() {
                             log::trace!(
@@ -338,16 +399,12 @@ impl<'s> System<'s> for ServerNetworkSystem {
                             &mut *framed_updates,
                             actions,
                             &mut *action_update_id_provider,
-                            game_time_service.game_frame_number(),
+                            &game_time_service,
                         );
                     }
 
                     ClientMessagePayload::LookActions(actions) => {
-                        add_look_actions(
-                            &mut *framed_updates,
-                            actions,
-                            game_time_service.game_frame_number(),
-                        );
+                        add_look_actions(&mut *framed_updates, actions, &game_time_service);
                     }
 
                     ClientMessagePayload::AcknowledgeWorldUpdate(frame_number) => {
@@ -444,6 +501,43 @@ impl<'s> System<'s> for ServerNetworkSystem {
             return;
         }
 
+        if !game_level_state.is_over {
+            // Starting a new game resets the level state, so it can be over once again.
+            self.has_sent_game_over = false;
+        } else if !self.has_sent_game_over {
+            log::info!("Sending a GameOver message");
+            self.has_sent_game_over = true;
+            broadcast_message_reliable(
+                &mut transport,
+                (&net_connection_models).join(),
+                ServerMessagePayload::GameOver(run_summary.clone()),
+            );
+        }
+
+        // Players get experience for their kills during a game, see `ExperienceAwards`.
+        if multiplayer_game_state.is_playing {
+            for player in &multiplayer_game_state.players {
+                let kills = entity_net_metadata_storage
+                    .get_entity(player.entity_net_id)
+                    .map_or(0, |player_entity| run_stats.confirmed_kills(player_entity));
+                let reported_kills = self.reported_kills.entry(player.connection_id).or_default();
+                if kills <= *reported_kills {
+                    continue;
+                }
+                *reported_kills = kills;
+                if let Some(net_connection_model) = (&net_connection_models)
+                    .join()
+                    .find(|connection_model| connection_model.id == player.connection_id)
+                {
+                    send_message_reliable(
+                        &mut transport,
+                        net_connection_model,
+                        ServerMessagePayload::ConfirmedKills(kills),
+                    );
+                }
+            }
+        }
+
         if let Some(players) = multiplayer_game_state.read_updated_players() {
             broadcast_message_reliable(
                 &mut transport,
@@ -453,7 +547,7 @@ impl<'s> System<'s> for ServerNetworkSystem {
         }
 
         if game_time_service.engine_time().frame_number() - self.last_heartbeat_frame
-            > HEARTBEAT_FRAME_INTERVAL
+            > game_time_service.frames_in_seconds(HEARTBEAT_INTERVAL_SECS)
         {
             self.last_heartbeat_frame = game_time_service.engine_time().frame_number();
             broadcast_message_reliable(
@@ -464,7 +558,7 @@ impl<'s> System<'s> for ServerNetworkSystem {
         }
 
         if game_time_service.engine_time().frame_number() - self.last_report_players_status_frame
-            > REPORT_PLAYERS_STATUS_FRAME_INTERVAL
+            > game_time_service.frames_in_seconds(REPORT_PLAYERS_STATUS_INTERVAL_SECS)
         {
             self.last_report_players_status_frame = game_time_service.engine_time().frame_number();
             broadcast_message_unreliable(
@@ -491,9 +585,7 @@ impl<'s> System<'s> for ServerNetworkSystem {
                                 average_lagging_behind: player_connection_model
                                     .ping_pong_data
                                     .average_lagging_behind(),
-                                latency_ms: player_connection_model
-                                    .ping_pong_data
-                                    .latency_ms(game_time_service.engine_time().delta_seconds()),
+                                latency_ms: player_connection_model.ping_pong_data.latency_ms(),
                             }
                         })
                         .collect(),
@@ -519,7 +611,7 @@ impl<'s> System<'s> for ServerNetworkSystem {
 
                 let expected_client_frame_number = last_broadcasted_frame
                     .0
-                    .saturating_sub(INTERPOLATION_FRAME_DELAY);
+                    .saturating_sub(game_time_service.interpolation_frame_delay());
 
                 let was_lagging = multiplayer_game_state
                     .lagging_players
@@ -547,9 +639,10 @@ impl<'s> System<'s> for ServerNetworkSystem {
                     average_lagging_behind
                 );
 
-                if frames_since_last_pong > PAUSE_FRAME_THRESHOLD
+                let pause_frame_threshold = game_time_service.pause_frame_threshold();
+                if frames_since_last_pong > pause_frame_threshold
                     || was_lagging && is_catching_up
-                    || average_lagging_behind > PAUSE_FRAME_THRESHOLD
+                    || average_lagging_behind > pause_frame_threshold
                 {
                     lagging_players.push(net_connection_model.id);
                 }
@@ -600,19 +693,23 @@ impl<'s> System<'s> for ServerNetworkSystem {
 fn add_walk_actions(
     framed_updates: &mut FramedUpdates<ReceivedClientActionUpdates>,
     actions: ImmediatePlayerActionsUpdates<ClientActionUpdate<PlayerWalkAction>>,
-    frame_number: u64,
+    game_time_service: &GameTimeService,
 ) -> Vec<NetIdentifier> {
     let mut discarded_actions = Vec::new();
+    let frame_number = game_time_service.game_frame_number();
+    let lag_compensation_frames_limit = game_time_service.lag_compensation_frames_limit();
 
     let added_actions_frame_number = actions.frame_number;
 
     // Just ignore these updates, most probably these are lost packages from the previous game,
     // or the client is just bonkers.
-    if added_actions_frame_number.saturating_sub(frame_number) > PAUSE_FRAME_THRESHOLD {
+    if added_actions_frame_number.saturating_sub(frame_number)
+        > game_time_service.pause_frame_threshold()
+    {
         return Vec::new();
     }
 
-    let oldest_possible_frame = frame_number.saturating_sub(LAG_COMPENSATION_FRAMES_LIMIT as u64);
+    let oldest_possible_frame = frame_number.saturating_sub(lag_compensation_frames_limit);
     let are_lag_compensated = added_actions_frame_number > oldest_possible_frame;
     let actual_frame = if are_lag_compensated {
         added_actions_frame_number
@@ -620,8 +717,8 @@ fn add_walk_actions(
         oldest_possible_frame
     };
 
-    let is_badly_late = added_actions_frame_number
-        < frame_number.saturating_sub(LAG_COMPENSATION_FRAMES_LIMIT as u64 * 2);
+    let is_badly_late =
+        added_actions_frame_number < frame_number.saturating_sub(lag_compensation_frames_limit * 2);
     for action in actions.updates {
         let is_added = {
             if is_badly_late {
@@ -645,7 +742,7 @@ fn add_walk_actions(
         if is_added {
             let frames_to_move = oldest_possible_frame.saturating_sub(added_actions_frame_number);
             if !is_badly_late && frames_to_move > 0 {
-                let mut moved_updates = Vec::with_capacity(LAG_COMPENSATION_FRAMES_LIMIT);
+                let mut moved_updates = Vec::with_capacity(lag_compensation_frames_limit as usize);
                 for framed_update in framed_updates
                     .updates
                     .iter_mut()
@@ -703,8 +800,9 @@ fn add_walk_actions(
 fn add_look_actions(
     framed_updates: &mut FramedUpdates<ReceivedClientActionUpdates>,
     actions: PlayerLookActionUpdates,
-    frame_number: u64,
+    game_time_service: &GameTimeService,
 ) {
+    let frame_number = game_time_service.game_frame_number();
     let frame_to_reserve = actions
         .updates
         .iter()
@@ -715,7 +813,7 @@ fn add_look_actions(
     // Just ignore these updates, most probably these are lost packages from the previous game,
     // or the client is just bonkers.
     let is_outdated_update = frame_to_reserve.map_or(true, |frame_to_reserve| {
-        frame_to_reserve.saturating_sub(frame_number) > PAUSE_FRAME_THRESHOLD
+        frame_to_reserve.saturating_sub(frame_number) > game_time_service.pause_frame_threshold()
     });
     if is_outdated_update {
         return;
@@ -726,7 +824,8 @@ fn add_look_actions(
     }
 
     let mut oldest_updated_frame = framed_updates.oldest_updated_frame;
-    let oldest_possible_frame = frame_number.saturating_sub(LAG_COMPENSATION_FRAMES_LIMIT as u64);
+    let oldest_possible_frame =
+        frame_number.saturating_sub(game_time_service.lag_compensation_frames_limit());
     let mut framed_updates_iter = framed_updates.updates_iter_mut(oldest_possible_frame);
 
     'action_updates: for (update_frame_number, updates) in actions.updates {
@@ -782,17 +881,21 @@ fn add_cast_actions(
     framed_updates: &mut FramedUpdates<ReceivedClientActionUpdates>,
     actions: ImmediatePlayerActionsUpdates<ClientActionUpdate<PlayerCastAction>>,
     action_update_id_provider: &mut ActionUpdateIdProvider,
-    frame_number: u64,
+    game_time_service: &GameTimeService,
 ) {
+    let frame_number = game_time_service.game_frame_number();
     let added_actions_frame_number = actions.frame_number;
 
     // Just ignore these updates, most probably these are lost packages from the previous game,
     // or the client is just bonkers.
-    if added_actions_frame_number.saturating_sub(frame_number) > PAUSE_FRAME_THRESHOLD {
+    if added_actions_frame_number.saturating_sub(frame_number)
+        > game_time_service.pause_frame_threshold()
+    {
         return;
     }
 
-    let oldest_possible_frame = frame_number.saturating_sub(LAG_COMPENSATION_FRAMES_LIMIT as u64);
+    let oldest_possible_frame =
+        frame_number.saturating_sub(game_time_service.lag_compensation_frames_limit());
     let are_lag_compensated = added_actions_frame_number > oldest_possible_frame;
     let actual_frame = if are_lag_compensated {
         added_actions_frame_number
@@ -9,8 +9,13 @@ use amethyst::{
     Logger, LoggerConfig,
 };
 
-use gv_core::ecs::resources::world::{
-    DummyFramedUpdate, FramedUpdates, ReceivedClientActionUpdates, ServerWorldUpdates,
+use std::time::Duration;
+
+use gv_core::{
+    ecs::resources::world::{
+        DummyFramedUpdate, FramedUpdates, ReceivedClientActionUpdates, ServerWorldUpdates,
+    },
+    net::TickRate,
 };
 use gv_game::{
     build_game_logic_systems,
@@ -45,6 +50,16 @@ fn main() -> amethyst::Result<()> {
                 .help("Specifies the address of the client hosting the game")
                 .takes_value(true),
         )
+        .arg(
+            clap::Arg::with_name("tick-rate")
+                .short("t")
+                .long("tick-rate")
+                .value_name("TICK_RATE")
+                .help("Specifies the number of simulated frames per second")
+                .possible_values(&["30", "60", "120"])
+                .default_value("60")
+                .takes_value(true),
+        )
         .get_matches();
 
     let socket_addr = cli_matches
@@ -56,6 +71,11 @@ fn main() -> amethyst::Result<()> {
     } else {
         HostClientAddress(None)
     };
+    let tick_rate = cli_matches
+        .value_of("tick-rate")
+        .and_then(|tick_rate| tick_rate.parse().ok())
+        .and_then(TickRate::from_frames_per_second)
+        .expect("Expected a valid tick rate");
 
     let logging_config: LoggerConfig = ::std::fs::read_to_string("server_logging_config.toml")
         .map_err(|err| {
@@ -91,6 +111,7 @@ fn main() -> amethyst::Result<()> {
         .world
         .insert(FramedUpdates::<ReceivedClientActionUpdates>::default());
     builder.world.insert(client_addr);
+    builder.world.insert(tick_rate);
     builder.world.insert(ServerWorldUpdates::default());
     builder.world.insert(LastBroadcastedFrame(0));
 
@@ -123,7 +144,8 @@ fn main() -> amethyst::Result<()> {
         .with_bundle(TransformBundle::new().with_dep(&["world_position_transform_system"]))?;
 
     let mut game = builder
-        .with_frame_limit(FrameRateLimitStrategy::Yield, 60)
+        .with_frame_limit(FrameRateLimitStrategy::Yield, tick_rate.frames_per_second())
+        .with_fixed_step_length(Duration::from_secs_f32(tick_rate.fixed_seconds()))
         .build(game_data_builder)?;
     game.run();
     Ok(())
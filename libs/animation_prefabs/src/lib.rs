@@ -1,6 +1,6 @@
 use amethyst::{
     animation::AnimationSetPrefab,
-    assets::{PrefabData, ProgressCounter},
+    assets::{Prefab, PrefabData, ProgressCounter},
     core::Named,
     derive::PrefabData,
     ecs::{Entity, WriteStorage},
@@ -31,3 +31,24 @@ pub struct GameSpriteAnimationPrefab {
     #[prefab(Component)]
     pub transparent: Transparent,
 }
+
+/// Returns the times (in seconds) at which the keyframes of an animation of a body part start.
+/// Sprite animations are stepped, so the animation ends a keyframe later than the last one starts.
+pub fn animation_keyframes(
+    prefab: &Prefab<GameSpriteAnimationPrefab>,
+    body_part_name: &str,
+    animation_id: AnimationId,
+) -> Option<&[f32]> {
+    prefab
+        .entities()
+        .filter_map(|entity| entity.data())
+        .find(|data| data.name_tag.name == body_part_name)
+        .and_then(|data| {
+            data.animation_set
+                .animations
+                .iter()
+                .find(|(id, _)| *id == animation_id)
+        })
+        .and_then(|(_, animation)| animation.samplers.first())
+        .map(|(_, _, sampler)| sampler.input.as_slice())
+}
@@ -5,7 +5,9 @@ use amethyst::{
     winit::VirtualKeyCode,
 };
 use directories::ProjectDirs;
+use gv_core::progression::{PlayerProfile, Upgrade};
 use ron::ser::PrettyConfig;
+use serde_derive::{Deserialize, Serialize};
 
 use std::{fs, path::PathBuf};
 
@@ -19,6 +21,14 @@ pub struct Settings {
     project_dirs: ProjectDirs,
     bindings: Bindings<StringBindings>,
     display: DisplayConfig,
+    campaign_progress: CampaignProgress,
+    profile: PlayerProfile,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct CampaignProgress {
+    /// Campaign levels are played in order, so it's also the index of the next level to play.
+    pub completed_levels: usize,
 }
 
 impl Settings {
@@ -96,10 +106,24 @@ impl Settings {
             },
         )?;
 
+        // A missing or a broken file means that the campaign hasn't been played yet.
+        let campaign_progress = fs::read(campaign_progress_path(&project_dirs))
+            .ok()
+            .and_then(|bytes| ron::de::from_bytes(&bytes).ok())
+            .unwrap_or_default();
+
+        // Same for the profile: a new player starts from scratch.
+        let profile = fs::read(profile_path(&project_dirs))
+            .ok()
+            .and_then(|bytes| ron::de::from_bytes(&bytes).ok())
+            .unwrap_or_default();
+
         Ok(Self {
             project_dirs,
             bindings,
             display,
+            campaign_progress,
+            profile,
         })
     }
 
@@ -122,6 +146,45 @@ impl Settings {
         &self.display
     }
 
+    pub fn campaign_progress(&self) -> &CampaignProgress {
+        &self.campaign_progress
+    }
+
+    /// Replaying a level doesn't make the progress go back.
+    pub fn save_campaign_progress(&mut self, completed_levels: usize) -> amethyst::Result<()> {
+        if completed_levels <= self.campaign_progress.completed_levels {
+            return Ok(());
+        }
+        self.campaign_progress.completed_levels = completed_levels;
+        fs::create_dir_all(self.project_dirs.config_dir())?;
+        fs::write(
+            campaign_progress_path(&self.project_dirs),
+            ron::ser::to_string_pretty(&self.campaign_progress, PrettyConfig::default())?,
+        )?;
+        Ok(())
+    }
+
+    pub fn profile(&self) -> &PlayerProfile {
+        &self.profile
+    }
+
+    pub fn add_experience(&mut self, experience: u32) -> amethyst::Result<()> {
+        if experience == 0 {
+            return Ok(());
+        }
+        self.profile.experience = self.profile.experience.saturating_add(experience);
+        self.save_profile()
+    }
+
+    /// Returns `false` if the upgrade can't be bought.
+    pub fn buy_upgrade(&mut self, upgrade: Upgrade) -> amethyst::Result<bool> {
+        if !self.profile.buy_upgrade(upgrade) {
+            return Ok(false);
+        }
+        self.save_profile()?;
+        Ok(true)
+    }
+
     #[allow(dead_code)]
     pub fn save_resolution(&mut self, dimensions: (u32, u32)) -> amethyst::Result<()> {
         self.display.dimensions = Some(dimensions);
@@ -152,6 +215,15 @@ impl Settings {
         Ok(())
     }
 
+    fn save_profile(&mut self) -> amethyst::Result<()> {
+        fs::create_dir_all(self.project_dirs.config_dir())?;
+        fs::write(
+            profile_path(&self.project_dirs),
+            ron::ser::to_string_pretty(&self.profile, PrettyConfig::default())?,
+        )?;
+        Ok(())
+    }
+
     #[allow(dead_code)]
     fn bindings_config_path(&self) -> PathBuf {
         bindings_config_path(&self.project_dirs)
@@ -169,3 +241,11 @@ fn bindings_config_path(project_dirs: &ProjectDirs) -> PathBuf {
 fn display_config_path(project_dirs: &ProjectDirs) -> PathBuf {
     project_dirs.config_dir().join("display_config.ron")
 }
+
+fn campaign_progress_path(project_dirs: &ProjectDirs) -> PathBuf {
+    project_dirs.config_dir().join("campaign_progress.ron")
+}
+
+fn profile_path(project_dirs: &ProjectDirs) -> PathBuf {
+    project_dirs.config_dir().join("profile.ron")
+}
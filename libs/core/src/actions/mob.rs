@@ -79,13 +79,17 @@ pub struct MobAttackAction<T> {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum MobAttackType {
-    #[allow(dead_code)]
+    /// Hits after a wind-up, if a target is still in reach, the timing is read from
+    /// the attack animation of a monster.
     Melee,
     SlowMelee {
         cooldown: f32,
     },
-    #[allow(dead_code)]
-    Range,
+    /// Keeps the distance to a target and shoots projectiles at it.
+    Range {
+        cooldown: f32,
+        distance: f32,
+    },
 }
 
 impl MobAttackType {
@@ -96,6 +100,13 @@ impl MobAttackType {
                 let cooldown = rng.gen_range(cooldown * (1.0 - factor), cooldown * (1.0 + factor));
                 MobAttackType::SlowMelee { cooldown }
             }
+            MobAttackType::Range { cooldown, distance } => {
+                let cooldown = rng.gen_range(cooldown * (1.0 - factor), cooldown * (1.0 + factor));
+                MobAttackType::Range {
+                    cooldown,
+                    distance: *distance,
+                }
+            }
             other => other.clone(),
         }
     }
@@ -29,6 +29,8 @@ impl FramedUpdate for SpawnActions {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SpawnAction {
+    /// The name of a monster definition, all the spawned monsters are of this type.
+    pub monster: String,
     pub spawn_type: SpawnType,
 }
 
@@ -50,13 +50,16 @@ impl Default for PlayerLookAction {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlayerCastAction {
+    /// The id of a spell definition.
+    pub spell: String,
     pub cast_position: Vector2,
     pub target_position: Vector2,
 }
 
 impl PartialEq for PlayerCastAction {
     fn eq(&self, other: &Self) -> bool {
-        (self.cast_position - other.cast_position).norm_squared() < 0.001
+        self.spell == other.spell
+            && (self.cast_position - other.cast_position).norm_squared() < 0.001
             && (self.target_position - other.target_position).norm_squared() < 0.001
     }
 }
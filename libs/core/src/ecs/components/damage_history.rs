@@ -1,8 +1,15 @@
-use amethyst::ecs::prelude::{Component, DenseVecStorage, FlaggedStorage};
+use amethyst::ecs::prelude::{Component, DenseVecStorage, Entity, FlaggedStorage};
 use serde_derive::{Deserialize, Serialize};
 
+use std::collections::VecDeque;
+
+use crate::{
+    ecs::components::status_effect::{StatusEffectApplication, StatusEffectKind},
+    net::NetIdentifier,
+};
+
 pub struct DamageHistory {
-    pub history: Vec<DamageHistoryEntries>,
+    pub history: VecDeque<DamageHistoryEntries>,
 }
 
 impl Component for DamageHistory {
@@ -11,9 +18,9 @@ impl Component for DamageHistory {
 
 impl DamageHistory {
     pub fn new(frame_number: u64) -> Self {
-        Self {
-            history: vec![DamageHistoryEntries::new(frame_number)],
-        }
+        let mut history = VecDeque::new();
+        history.push_back(DamageHistoryEntries::new(frame_number));
+        Self { history }
     }
 
     pub fn add_entry(&mut self, frame_number: u64, entry: DamageHistoryEntry) {
@@ -53,25 +60,35 @@ impl DamageHistory {
         damage_entries.entries.clear();
     }
 
-    pub fn get_entries(&self, frame_number: u64) -> &DamageHistoryEntries {
-        let i = frame_number
-            - self
-                .history
-                .first()
-                .expect("Expected at least one reserved entry")
-                .frame_number;
-        &self.history[i as usize]
+    /// Returns `None` if the entries of the frame are forgotten or not reserved yet.
+    pub fn get_entries(&self, frame_number: u64) -> Option<&DamageHistoryEntries> {
+        let front_frame_number = self.history.front()?.frame_number;
+        let i = frame_number.checked_sub(front_frame_number)?;
+        self.history.get(i as usize)
+    }
+
+    /// Drops the entries of the frames which can't be resimulated anymore,
+    /// keeping at least the latest one.
+    pub fn forget_old_entries(&mut self, frame_number: u64, saved_world_states_limit: u64) {
+        let oldest_rollback_frame = frame_number.saturating_sub(saved_world_states_limit);
+        while self.history.len() > 1
+            && self.history.front().map_or(false, |entries| {
+                entries.frame_number < oldest_rollback_frame
+            })
+        {
+            self.history.pop_front();
+        }
     }
 
     fn reserve_entries(&mut self, frame_number: u64) {
         let start_frame_number = self
             .history
-            .last()
+            .back()
             .map(|last_entries| last_entries.frame_number + 1)
             .expect("Expected at least one reserved entry");
         for added_frame_number in start_frame_number..=frame_number {
             self.history
-                .push(DamageHistoryEntries::new(added_frame_number));
+                .push_back(DamageHistoryEntries::new(added_frame_number));
         }
     }
 }
@@ -94,4 +111,57 @@ impl DamageHistoryEntries {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DamageHistoryEntry {
     pub damage: f32,
+    pub damage_type: DamageType,
+    pub cause: DamageCause,
+    /// An entity that has dealt the damage. Entity ids differ on clients and server,
+    /// so clients get it from `source_net_id`.
+    #[serde(skip)]
+    pub source: Option<Entity>,
+    /// Is filled by a server on sending the entry to clients.
+    pub source_net_id: Option<NetIdentifier>,
+    /// Is applied to the damaged entity together with the damage.
+    pub status_effect: Option<StatusEffectApplication>,
+}
+
+impl DamageHistoryEntry {
+    pub fn new(
+        damage: f32,
+        damage_type: DamageType,
+        cause: DamageCause,
+        source: Option<Entity>,
+    ) -> Self {
+        Self {
+            damage,
+            damage_type,
+            cause,
+            source,
+            source_net_id: None,
+            status_effect: None,
+        }
+    }
+
+    pub fn with_status_effect(mut self, status_effect: Option<StatusEffectApplication>) -> Self {
+        self.status_effect = status_effect;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DamageType {
+    Physical,
+    Fire,
+    Frost,
+    Arcane,
+    Poison,
+}
+
+/// What has dealt the damage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DamageCause {
+    Missile,
+    Nova,
+    Melee,
+    Projectile,
+    /// A tick of a damage over time effect.
+    StatusEffect(StatusEffectKind),
 }
@@ -1,38 +1,79 @@
 use amethyst::ecs::{Component, DenseVecStorage, Entity};
+use serde_derive::{Deserialize, Serialize};
 
-use crate::{math::Vector2, net::NetIdentifier};
+use crate::{
+    ecs::components::{damage_history::DamageType, status_effect::StatusEffectApplication},
+    math::Vector2,
+    net::NetIdentifier,
+};
 
 #[derive(Clone, Debug, Component)]
 pub struct Missile {
     pub action_id: NetIdentifier,
+    /// A player entity which has cast the missile.
+    pub caster: Entity,
     pub radius: f32,
     pub target: MissileTarget<Entity>,
     pub velocity: Vector2,
     pub frame_spawned: u64,
-    pub damage: f32,
+    pub spell: MissileSpell,
 }
 
 impl Missile {
     pub fn new(
         action_id: u64,
+        caster: Entity,
         radius: f32,
         target: MissileTarget<Entity>,
         velocity: Vector2,
         frame_spawned: u64,
+        spell: MissileSpell,
     ) -> Self {
         Self {
             action_id,
+            caster,
             radius,
             target,
             velocity,
             frame_spawned,
-            damage: 50.0,
+            spell,
         }
     }
 }
 
+/// Properties of the spell a missile has been cast with.
+#[derive(Clone, Debug)]
+pub struct MissileSpell {
+    pub behaviour: MissileBehaviour,
+    pub damage: f32,
+    pub damage_type: DamageType,
+    pub status_effect: Option<StatusEffectApplication>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum MissileBehaviour {
+    /// Follows the monster closest to the target position.
+    Homing,
+    /// Flies in the direction it's cast in and hits the first monster on its way.
+    Straight,
+    /// Stays at the caster position and hits every monster within the radius at once.
+    Nova { radius: f32 },
+}
+
 #[derive(Clone, Debug)]
 pub enum MissileTarget<T> {
     Target(T),
     Destination(Vector2),
 }
+
+/// A projectile shot by a ranged monster, flies straight and hits players.
+#[derive(Clone, Debug, Component)]
+pub struct MonsterProjectile {
+    /// A monster entity which has shot the projectile.
+    pub shooter: Entity,
+    pub radius: f32,
+    pub velocity: Vector2,
+    pub frame_spawned: u64,
+    pub damage: f32,
+    pub status_effect: Option<StatusEffectApplication>,
+}
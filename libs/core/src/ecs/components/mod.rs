@@ -1,12 +1,14 @@
 pub mod damage_history;
 pub mod missile;
+pub mod pickup;
+pub mod status_effect;
 
 use amethyst::ecs::{Component, DenseVecStorage, Entity, VecStorage};
 use serde_derive::{Deserialize, Serialize};
 use shrinkwraprs::Shrinkwrap;
 
 use std::{
-    collections::VecDeque,
+    collections::{HashMap, VecDeque},
     net::SocketAddr,
     time::{Duration, Instant},
 };
@@ -17,8 +19,10 @@ use crate::{
         player::{PlayerCastAction, PlayerLookAction, PlayerWalkAction},
         Action,
     },
+    ecs::components::pickup::{DAMAGE_BOOST_MULTIPLIER, SPEED_BUFF_MULTIPLIER},
     math::{Vector2, ZeroVector},
     net::NetIdentifier,
+    progression::{PlayerUpgrades, BASE_PLAYER_MANA},
 };
 
 const PING_PONG_STORAGE_LIMIT: usize = 20;
@@ -38,7 +42,7 @@ impl WorldPosition {
 }
 
 /// On client side this component stores a WorldPosition that a player had
-/// `INTERPOLATION_FRAME_DELAY` frames ago.
+/// `INTERPOLATION_DELAY_SECS` ago.
 /// This component isn't used on server side and in single player.
 #[derive(Clone, Debug, Serialize, Deserialize, Shrinkwrap, Component)]
 #[shrinkwrap(mutable)]
@@ -64,6 +68,21 @@ impl From<WorldPosition> for NetWorldPosition {
 #[derive(Clone, Debug, Component)]
 pub struct Player {
     pub health: f32,
+    pub max_health: f32,
+    pub speed: f32,
+    /// Spells spend mana, it regenerates over time.
+    pub mana: f32,
+    pub max_mana: f32,
+    pub spell_damage_multiplier: f32,
+    pub cast_cooldown_multiplier: f32,
+    /// Buffs of collected pickups last until these frames.
+    pub damage_boost_until_frame: u64,
+    pub speed_buff_until_frame: u64,
+    /// Is set in multiplayer once health drops to zero, a downed player bleeds out
+    /// unless a teammate revives them.
+    pub downed_since_frame: Option<u64>,
+    /// Frames a teammate has been standing close to a downed player for.
+    pub revive_frames: u64,
     pub velocity: Vector2,
     pub walking_direction: Vector2,
     pub looking_direction: Vector2,
@@ -71,20 +90,60 @@ pub struct Player {
 }
 
 impl Player {
-    pub fn new() -> Self {
+    pub fn new(upgrades: PlayerUpgrades) -> Self {
         Self {
-            health: 100.0,
+            health: upgrades.max_health(),
+            max_health: upgrades.max_health(),
+            speed: upgrades.move_speed(),
+            mana: BASE_PLAYER_MANA,
+            max_mana: BASE_PLAYER_MANA,
+            spell_damage_multiplier: upgrades.spell_damage_multiplier(),
+            cast_cooldown_multiplier: upgrades.cast_cooldown_multiplier(),
+            damage_boost_until_frame: 0,
+            speed_buff_until_frame: 0,
+            downed_since_frame: None,
+            revive_frames: 0,
             velocity: Vector2::zero(),
             walking_direction: Vector2::new(0.0, 1.0),
             looking_direction: Vector2::new(0.0, 1.0),
             radius: 20.0,
         }
     }
+
+    pub fn current_speed(&self, frame_number: u64) -> f32 {
+        if frame_number < self.speed_buff_until_frame {
+            self.speed * SPEED_BUFF_MULTIPLIER
+        } else {
+            self.speed
+        }
+    }
+
+    pub fn current_damage_multiplier(&self, frame_number: u64) -> f32 {
+        if frame_number < self.damage_boost_until_frame {
+            self.spell_damage_multiplier * DAMAGE_BOOST_MULTIPLIER
+        } else {
+            self.spell_damage_multiplier
+        }
+    }
+
+    pub fn is_downed(&self, frame_number: u64) -> bool {
+        self.downed_since_frame.map_or(false, |downed_since_frame| {
+            frame_number >= downed_since_frame
+        })
+    }
+}
+
+/// Is sent by a server, as only the server decides whether a downed player gets revived.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PlayerDownedUpdate {
+    Downed,
+    Revived,
+    BledOut,
 }
 
 impl Default for Player {
     fn default() -> Self {
-        Self::new()
+        Self::new(PlayerUpgrades::default())
     }
 }
 
@@ -105,10 +164,21 @@ pub struct ClientPlayerActions {
     pub cast_action: Option<PlayerCastAction>,
 }
 
-/// Stores frame numbers.
+/// Stores frame numbers by spell ids.
 #[derive(Clone, Default, Component)]
 pub struct PlayerLastCastedSpells {
-    pub missile: u64,
+    pub spells: HashMap<String, u64>,
+}
+
+impl PlayerLastCastedSpells {
+    /// Returns `None` for spells which haven't been cast yet.
+    pub fn frame_number(&self, spell: &str) -> Option<u64> {
+        self.spells.get(spell).copied()
+    }
+
+    pub fn set_frame_number(&mut self, spell: &str, frame_number: u64) {
+        self.spells.insert(spell.to_owned(), frame_number);
+    }
 }
 
 #[derive(Clone, Debug, Component)]
@@ -119,10 +189,20 @@ pub struct Monster {
     pub facing_direction: Vector2,
     pub velocity: Vector2,
     pub action: Action<MobAction<Entity>>,
+    /// The number of frames a monster has been stunned for since its action started.
+    pub stunned_frames: u64,
     pub name: String,
     pub radius: f32,
 }
 
+impl Monster {
+    /// Attacks don't progress while a monster is stunned, so they are timed from
+    /// the frame the action would have started at without stuns.
+    pub fn attack_started_frame(&self) -> u64 {
+        self.action.frame_number + self.stunned_frames
+    }
+}
+
 #[derive(Clone, Default, Component)]
 #[storage(VecStorage)]
 pub struct Dead {
@@ -189,24 +269,27 @@ impl PingPongData {
         }
     }
 
-    pub fn add_ping(&mut self, ping_id: NetIdentifier, engine_frame_number: u64) {
+    pub fn add_ping(&mut self, ping_id: NetIdentifier) {
         self.last_pinged_at = Instant::now();
         if self.data.len() == PING_PONG_STORAGE_LIMIT {
             self.data.pop_front();
         }
         self.data.push_back(PingPong {
             ping_id,
-            sent_ping_engine_frame: engine_frame_number,
+            sent_ping_at: self.last_pinged_at,
             pong: None,
         })
     }
 
+    /// Latency is measured in real time, as clients render frames independently
+    /// of the tick rate, and is converted to game frames with `fixed_seconds`.
     pub fn add_pong(
         &mut self,
         ping_id: NetIdentifier,
         peer_frame_number: u64,
         engine_frame_number: u64,
         frame_number: u64,
+        fixed_seconds: f32,
     ) {
         if self.last_ponged_frame < engine_frame_number {
             self.last_ponged_frame = engine_frame_number;
@@ -217,11 +300,13 @@ impl PingPongData {
             .iter_mut()
             .find(|ping_pong| ping_pong.ping_id == ping_id)
         {
-            let oneway_latency =
-                engine_frame_number.saturating_sub(ping_pong.sent_ping_engine_frame) / 2;
+            let received_pong_at = Instant::now();
+            let oneway_latency_secs =
+                (received_pong_at - ping_pong.sent_ping_at).as_secs_f32() / 2.0;
+            let oneway_latency = (oneway_latency_secs / fixed_seconds).round() as u64;
             let estimated_peer_frame_number = peer_frame_number + oneway_latency;
             ping_pong.pong = Some(Pong {
-                received_engine_frame: engine_frame_number,
+                received_pong_at,
                 received_game_frame: frame_number,
                 estimated_peer_frame_number,
             })
@@ -264,18 +349,14 @@ impl PingPongData {
             .unwrap_or(0)
     }
 
-    pub fn latency_ms(&self, delta_seconds: f32) -> u32 {
+    pub fn latency_ms(&self) -> u32 {
         self.data
             .iter()
             .rev()
             .find_map(|ping_pong_data| {
                 ping_pong_data.pong.as_ref().map(|pong_data| {
-                    let frame_diff = pong_data
-                        .received_engine_frame
-                        .checked_sub(ping_pong_data.sent_ping_engine_frame)
-                        .expect("Expected a positive result")
-                        as f32;
-                    (frame_diff / 2.0 * delta_seconds * 1000.0) as u32
+                    let round_trip = pong_data.received_pong_at - ping_pong_data.sent_ping_at;
+                    (round_trip.as_millis() / 2) as u32
                 })
             })
             .unwrap_or_default()
@@ -289,13 +370,13 @@ impl PingPongData {
 #[derive(Debug)]
 struct PingPong {
     ping_id: NetIdentifier,
-    sent_ping_engine_frame: u64,
+    sent_ping_at: Instant,
     pong: Option<Pong>,
 }
 
 #[derive(Debug)]
 struct Pong {
-    received_engine_frame: u64,
+    received_pong_at: Instant,
     received_game_frame: u64,
     estimated_peer_frame_number: u64,
 }
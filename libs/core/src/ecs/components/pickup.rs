@@ -0,0 +1,37 @@
+use amethyst::ecs::{Component, DenseVecStorage, Entity};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::math::Vector2;
+
+pub const PICKUP_RADIUS: f32 = 10.0;
+pub const PICKUP_LIFESPAN_SECS: f32 = 20.0;
+
+pub const HEALTH_ORB_HEALING: f32 = 25.0;
+pub const DAMAGE_BOOST_MULTIPLIER: f32 = 1.5;
+pub const DAMAGE_BOOST_SECS: f32 = 10.0;
+pub const SPEED_BUFF_MULTIPLIER: f32 = 1.3;
+pub const SPEED_BUFF_SECS: f32 = 8.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PickupKind {
+    HealthOrb,
+    DamageBoost,
+    SpeedBuff,
+}
+
+/// Is dropped by a dying monster, a player collects it by walking over it.
+#[derive(Clone, Debug, Component)]
+pub struct Pickup {
+    pub kind: PickupKind,
+    /// A monster which has dropped the pickup, clients don't know it.
+    pub dropped_by: Option<Entity>,
+    pub radius: f32,
+    pub frame_spawned: u64,
+}
+
+/// Is sent by a server for every dropped pickup, as clients don't roll the drops themselves.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PickupSpawn {
+    pub kind: PickupKind,
+    pub position: Vector2,
+}
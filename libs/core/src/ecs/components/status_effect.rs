@@ -0,0 +1,126 @@
+use amethyst::ecs::{Component, DenseVecStorage, Entity};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::ecs::components::damage_history::DamageType;
+
+/// Seconds between two ticks of damage over time effects.
+pub const STATUS_EFFECT_TICK_SECS: f32 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StatusEffectKind {
+    Slow,
+    Burn,
+    Stun,
+    Poison,
+}
+
+impl StatusEffectKind {
+    /// Re-applying an effect refreshes its duration, and adds a stack if it's stackable.
+    pub fn max_stacks(self) -> u8 {
+        match self {
+            StatusEffectKind::Poison => 5,
+            StatusEffectKind::Slow | StatusEffectKind::Burn | StatusEffectKind::Stun => 1,
+        }
+    }
+
+    /// Damage dealt by a single stack every tick.
+    pub fn tick_damage(self) -> f32 {
+        match self {
+            StatusEffectKind::Burn => 4.0,
+            StatusEffectKind::Poison => 1.5,
+            StatusEffectKind::Slow | StatusEffectKind::Stun => 0.0,
+        }
+    }
+
+    pub fn damage_type(self) -> DamageType {
+        match self {
+            StatusEffectKind::Burn => DamageType::Fire,
+            StatusEffectKind::Poison => DamageType::Poison,
+            StatusEffectKind::Slow | StatusEffectKind::Stun => DamageType::Physical,
+        }
+    }
+
+    pub fn speed_multiplier(self) -> f32 {
+        match self {
+            StatusEffectKind::Slow => 0.5,
+            StatusEffectKind::Stun => 0.0,
+            StatusEffectKind::Burn | StatusEffectKind::Poison => 1.0,
+        }
+    }
+}
+
+/// Is carried by damage history entries, so that clients apply effects together with the damage.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StatusEffectApplication {
+    pub kind: StatusEffectKind,
+    pub duration_secs: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct StatusEffect {
+    pub kind: StatusEffectKind,
+    pub stacks: u8,
+    /// Ticks are counted from this frame, refreshing the duration doesn't reset it.
+    pub started_frame: u64,
+    pub until_frame: u64,
+    /// An entity that has applied the effect, it gets credited with the tick damage.
+    pub source: Option<Entity>,
+}
+
+impl StatusEffect {
+    pub fn is_active(&self, frame_number: u64) -> bool {
+        self.started_frame <= frame_number && frame_number < self.until_frame
+    }
+}
+
+#[derive(Clone, Debug, Default, Component)]
+pub struct StatusEffects {
+    pub effects: Vec<StatusEffect>,
+}
+
+impl StatusEffects {
+    pub fn apply(
+        &mut self,
+        kind: StatusEffectKind,
+        started_frame: u64,
+        until_frame: u64,
+        source: Option<Entity>,
+    ) {
+        if let Some(effect) = self.effects.iter_mut().find(|effect| effect.kind == kind) {
+            effect.stacks = (effect.stacks + 1).min(kind.max_stacks());
+            effect.until_frame = effect.until_frame.max(until_frame);
+            effect.source = source.or(effect.source);
+        } else {
+            self.effects.push(StatusEffect {
+                kind,
+                stacks: 1,
+                started_frame,
+                until_frame,
+                source,
+            });
+        }
+    }
+
+    pub fn remove_expired(&mut self, frame_number: u64) {
+        self.effects
+            .retain(|effect| frame_number < effect.until_frame);
+    }
+
+    pub fn active(&self, frame_number: u64) -> impl Iterator<Item = &StatusEffect> {
+        self.effects
+            .iter()
+            .filter(move |effect| effect.is_active(frame_number))
+    }
+
+    /// Effects don't add up, the strongest one is applied.
+    pub fn speed_multiplier(&self, frame_number: u64) -> f32 {
+        self.active(frame_number)
+            .map(|effect| effect.kind.speed_multiplier())
+            .fold(1.0, f32::min)
+    }
+
+    pub fn is_stunned(&self, frame_number: u64) -> bool {
+        self.active(frame_number)
+            .any(|effect| effect.kind == StatusEffectKind::Stun)
+    }
+}
@@ -1,4 +1,5 @@
 pub mod net;
+pub mod stats;
 pub mod world;
 
 use std::time::{Duration, Instant};
@@ -21,13 +22,45 @@ impl Default for GameTime {
     }
 }
 
+/// Ticks of the simulation, which happen at the agreed tick rate (`Time::fixed_seconds`)
+/// whatever the frame rate is. A frame simulates one tick at most. It's kept apart from
+/// `GameTime` for the same reason as `GamePause`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SimulationTicks {
+    /// The number of the tick which is being simulated, or is going to be.
+    pub tick_number: u64,
+    /// Fixed updates which haven't been simulated yet.
+    pub pending: u32,
+    /// Whether the current frame simulates a tick.
+    pub is_ticking: bool,
+}
+
+impl SimulationTicks {
+    pub fn add_fixed_update(&mut self) {
+        self.pending += 1;
+    }
+
+    /// Is called before running systems. If frames are rendered late, one tick is
+    /// carried over to the next frame, the others are dropped.
+    pub fn start_frame(&mut self) {
+        if self.is_ticking {
+            self.tick_number += 1;
+        }
+        self.is_ticking = self.pending > 0;
+        if self.is_ticking {
+            self.pending = (self.pending - 1).min(1);
+        }
+    }
+}
+
 pub struct GameLevelState {
     pub dimensions: Vector2,
     pub is_over: bool,
-    pub spawn_level: usize,
-    pub spawn_level_started: Duration,
-    pub last_borderline_spawn: Duration,
-    pub last_random_spawn: Duration,
+    /// The index of the running wave of the level script, `None` until the first one starts.
+    pub wave: Option<usize>,
+    pub wave_started_frame: u64,
+    /// How many times each spawn of the running wave has happened.
+    pub wave_spawns_done: Vec<u32>,
 }
 
 impl GameLevelState {
@@ -41,14 +74,20 @@ impl Default for GameLevelState {
         Self {
             dimensions: Vector2::new(4096.0, 4096.0),
             is_over: false,
-            spawn_level: 1,
-            spawn_level_started: Duration::new(0, 0),
-            last_borderline_spawn: Duration::new(0, 0),
-            last_random_spawn: Duration::new(0, 0),
+            wave: None,
+            wave_started_frame: 0,
+            wave_spawns_done: Vec::new(),
         }
     }
 }
 
+/// Only single player games can be paused. It's kept apart from `GameLevelState`, as
+/// `GameStateHelper` reads it and the systems using the helper write the level state.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct GamePause {
+    pub is_paused: bool,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct NewGameEngineState(pub GameEngineState);
 
@@ -57,7 +96,11 @@ pub enum GameEngineState {
     Loading,
     Menu,
     Playing,
-    ShuttingDown { shutdown_at: Instant },
+    /// A campaign level objective is met.
+    LevelComplete,
+    ShuttingDown {
+        shutdown_at: Instant,
+    },
     Quit,
 }
 
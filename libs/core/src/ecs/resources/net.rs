@@ -6,7 +6,8 @@ use std::{collections::HashMap, ops::Range};
 
 use crate::{
     actions::{player::PlayerCastAction, IdentifiableAction},
-    net::{server_message::PlayerNetStatus, NetIdentifier},
+    net::{server_message::PlayerNetStatus, NetIdentifier, TickRate},
+    progression::PlayerUpgrades,
     PLAYER_COLORS,
 };
 
@@ -19,6 +20,7 @@ pub struct MultiplayerRoomPlayer {
     pub is_host: bool,
     #[derivative(PartialEq = "ignore")]
     pub color: [f32; 3],
+    pub upgrades: PlayerUpgrades,
 }
 
 #[derive(Clone, Serialize, Deserialize, Default)]
@@ -33,6 +35,8 @@ pub struct MultiplayerGameState {
     pub players_status_id: u64,
     pub lagging_players: Vec<NetIdentifier>,
     pub is_disconnected: bool,
+    /// The tick rate agreed with a server on starting a game.
+    pub tick_rate: TickRate,
     players_updated: bool,
 }
 
@@ -47,6 +51,7 @@ impl MultiplayerGameState {
             players_status_id: 0,
             lagging_players: Vec::new(),
             is_disconnected: false,
+            tick_rate: TickRate::default(),
             players_updated: false,
         }
     }
@@ -138,6 +143,10 @@ impl EntityNetMetadataStorage {
         self.mapping.insert(entity_net_id, entity);
     }
 
+    pub fn release_net_id(&mut self, entity_net_id: NetIdentifier) {
+        self.mapping.remove(&entity_net_id);
+    }
+
     pub fn reset(&mut self) {
         self.range = 0..NetIdentifier::max_value();
         self.mapping.clear();
@@ -165,7 +174,13 @@ impl ActionUpdateIdProvider {
 
 #[derive(Default)]
 pub struct CastActionsToExecute {
-    pub actions: Vec<IdentifiableAction<PlayerCastAction>>,
+    pub actions: Vec<CastActionToExecute>,
+}
+
+pub struct CastActionToExecute {
+    pub caster: Entity,
+    pub damage: f32,
+    pub action: IdentifiableAction<PlayerCastAction>,
 }
 
 #[derive(Default)]
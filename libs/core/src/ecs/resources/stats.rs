@@ -0,0 +1,111 @@
+use amethyst::ecs::Entity;
+use serde_derive::{Deserialize, Serialize};
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::AddAssign,
+};
+
+use crate::net::NetIdentifier;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlayerStats {
+    pub kills: u32,
+    pub damage_dealt: f32,
+    pub damage_taken: f32,
+    pub missiles_cast: u32,
+    pub missiles_hit: u32,
+}
+
+impl AddAssign<&PlayerStats> for PlayerStats {
+    fn add_assign(&mut self, rhs: &PlayerStats) {
+        self.kills += rhs.kills;
+        self.damage_dealt += rhs.damage_dealt;
+        self.damage_taken += rhs.damage_taken;
+        self.missiles_cast += rhs.missiles_cast;
+        self.missiles_hit += rhs.missiles_hit;
+    }
+}
+
+/// Player statistics of the running game. The frames that still can be resimulated are stored
+/// separately, so that a rollback doesn't count anything twice.
+#[derive(Default)]
+pub struct RunStats {
+    confirmed: HashMap<Entity, PlayerStats>,
+    frames: BTreeMap<u64, HashMap<Entity, PlayerStats>>,
+}
+
+impl RunStats {
+    pub fn reset_frame(&mut self, frame_number: u64) {
+        self.frames.remove(&frame_number);
+    }
+
+    pub fn player_mut(&mut self, frame_number: u64, player: Entity) -> &mut PlayerStats {
+        self.frames
+            .entry(frame_number)
+            .or_default()
+            .entry(player)
+            .or_default()
+    }
+
+    /// Merges the frames older than `frame_number`, as they can't be resimulated anymore.
+    pub fn confirm_before(&mut self, frame_number: u64) {
+        let unconfirmed_frames = self.frames.split_off(&frame_number);
+        let confirmed_frames = std::mem::replace(&mut self.frames, unconfirmed_frames);
+        for (player, stats) in confirmed_frames.values().flatten() {
+            *self.confirmed.entry(*player).or_default() += stats;
+        }
+    }
+
+    /// Kills that can't be taken back by a rollback anymore.
+    pub fn confirmed_kills(&self, player: Entity) -> u32 {
+        self.confirmed.get(&player).map_or(0, |stats| stats.kills)
+    }
+
+    pub fn player(&self, player: Entity) -> PlayerStats {
+        let mut stats = self.confirmed.get(&player).cloned().unwrap_or_default();
+        for frame_stats in self
+            .frames
+            .values()
+            .filter_map(|players| players.get(&player))
+        {
+            stats += frame_stats;
+        }
+        stats
+    }
+}
+
+/// Clients award experience for the kills of the local player as soon as they are confirmed,
+/// instead of waiting for a game to end.
+#[derive(Debug, Default)]
+pub struct ExperienceAwards {
+    /// A server reports the confirmed kills of a player in multiplayer.
+    pub confirmed_kills: u32,
+    pub awarded_kills: u32,
+}
+
+impl ExperienceAwards {
+    /// Returns the number of kills which haven't been awarded yet and marks them as awarded.
+    pub fn award(&mut self, kills: u32) -> u32 {
+        let new_kills = kills.saturating_sub(self.awarded_kills);
+        self.awarded_kills += new_kills;
+        new_kills
+    }
+}
+
+/// The results of a finished game, a server sends them to clients in multiplayer.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunSummary {
+    pub players: Vec<PlayerRunSummary>,
+    /// The number of started waves of the level script.
+    pub waves_reached: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerRunSummary {
+    /// Lets clients find their own results in multiplayer.
+    pub entity_net_id: Option<NetIdentifier>,
+    pub nickname: String,
+    pub stats: PlayerStats,
+    pub time_survived_secs: f32,
+}
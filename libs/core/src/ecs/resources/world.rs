@@ -11,16 +11,25 @@ use crate::{
         ClientActionUpdate, IdentifiableAction,
     },
     ecs::components::{
-        damage_history::DamageHistoryEntries, missile::Missile, Dead, Monster, Player,
-        PlayerActions, PlayerLastCastedSpells, WorldPosition,
+        damage_history::DamageHistoryEntries,
+        missile::{Missile, MonsterProjectile},
+        pickup::{Pickup, PickupSpawn},
+        status_effect::StatusEffects,
+        Dead, Monster, Player, PlayerActions, PlayerDownedUpdate, PlayerLastCastedSpells,
+        WorldPosition,
     },
-    net::{NetIdentifier, NetUpdate, NetUpdateWithPosition},
+    net::{NetIdentifier, NetUpdate, NetUpdateWithPosition, TickRate},
 };
 
-pub const SAVED_WORLD_STATES_LIMIT: usize = 600;
-pub const LAG_COMPENSATION_FRAMES_LIMIT: usize = 20;
-pub const PAUSE_FRAME_THRESHOLD: u64 =
-    (LAG_COMPENSATION_FRAMES_LIMIT + LAG_COMPENSATION_FRAMES_LIMIT / 2) as u64;
+pub const SAVED_WORLD_STATES_SECS: f32 = 10.0;
+pub const LAG_COMPENSATION_SECS: f32 = 1.0 / 3.0;
+pub const PAUSE_THRESHOLD_SECS: f32 = LAG_COMPENSATION_SECS * 1.5;
+
+/// The number of saved frames before a tick rate is agreed, use `set_frames_limit`
+/// to match the actual one.
+fn default_frames_limit() -> usize {
+    (SAVED_WORLD_STATES_SECS / TickRate::default().fixed_seconds()).round() as usize
+}
 
 #[derive(Debug)]
 pub struct OldFrameError {
@@ -30,24 +39,34 @@ pub struct OldFrameError {
 
 pub struct WorldStates {
     world_states: VecDeque<SavedWorldState>,
+    frames_limit: usize,
 }
 
 impl WorldStates {
-    pub fn add_world_state(&mut self, mut world_state: SavedWorldState) {
-        if self.world_states.is_empty() {
-            world_state.frame_number = 0;
+    pub fn set_frames_limit(&mut self, frames_limit: usize) {
+        self.frames_limit = frames_limit;
+    }
+
+    /// Adds an empty world state for the next frame. The oldest state gets reused once
+    /// the limit is reached, so that its buffers don't have to be allocated again.
+    pub fn add_world_state(&mut self) {
+        let frame_number = self
+            .world_states
+            .back()
+            .map_or(0, |world_state| world_state.frame_number + 1);
+
+        let mut world_state = if self.world_states.len() >= self.frames_limit {
+            self.world_states
+                .pop_front()
+                .expect("Expected the oldest world state")
         } else {
-            world_state.frame_number = self.world_states.back().unwrap().frame_number + 1;
-        }
+            SavedWorldState::default()
+        };
+        world_state.clear();
+        world_state.frame_number = frame_number;
 
-        log::trace!(
-            "Adding a new world state for frame {}",
-            world_state.frame_number
-        );
+        log::trace!("Adding a new world state for frame {}", frame_number);
         self.world_states.push_back(world_state);
-        if self.world_states.len() > SAVED_WORLD_STATES_LIMIT {
-            self.world_states.pop_front();
-        }
     }
 
     pub fn states_iter_mut(
@@ -100,13 +119,20 @@ impl WorldStates {
 
 impl Default for WorldStates {
     fn default() -> Self {
-        let mut world_states = VecDeque::with_capacity(SAVED_WORLD_STATES_LIMIT);
+        let frames_limit = default_frames_limit();
+        let mut world_states = VecDeque::with_capacity(frames_limit);
         world_states.push_back(SavedWorldState::default());
-        Self { world_states }
+        Self {
+            world_states,
+            frames_limit,
+        }
     }
 }
 
-// TODO: benchmark in order to justify the collection choice (BTreeMap vs HashMap vs Vec).
+/// Every component is saved to its own column, which is a plain `Vec`: saving and restoring
+/// only iterate over the whole column, so it beats `HashMap` and `BTreeMap`
+/// (see `benches/world_states.rs` in `gv_game`). The columns keep their capacity
+/// when a state is reused.
 #[derive(Default)]
 pub struct SavedWorldState {
     pub frame_number: u64,
@@ -115,11 +141,27 @@ pub struct SavedWorldState {
     pub player_last_casted_spells: Vec<(Entity, PlayerLastCastedSpells)>,
     pub monsters: Vec<(Entity, Monster)>,
     pub missiles: Vec<(Entity, Missile)>,
+    pub monster_projectiles: Vec<(Entity, MonsterProjectile)>,
+    pub pickups: Vec<(Entity, Pickup)>,
+    pub status_effects: Vec<(Entity, StatusEffects)>,
     pub world_positions: Vec<(Entity, WorldPosition)>,
     pub dead: Vec<(Entity, Dead)>,
 }
 
 impl SavedWorldState {
+    pub fn clear(&mut self) {
+        self.players.clear();
+        self.player_actions.clear();
+        self.player_last_casted_spells.clear();
+        self.monsters.clear();
+        self.missiles.clear();
+        self.monster_projectiles.clear();
+        self.pickups.clear();
+        self.status_effects.clear();
+        self.world_positions.clear();
+        self.dead.clear();
+    }
+
     pub fn copy_from_storage<T: Clone + Component>(
         entities: &Entities,
         storage: &ReadStorage<T>,
@@ -131,15 +173,18 @@ impl SavedWorldState {
         )
     }
 
-    pub fn copy_from_write_storage<T: Clone + Component>(
+    /// Overwrites the saved components, reusing the already allocated column.
+    pub fn save_storage_to<T: Clone + Component>(
+        saved_components: &mut Vec<(Entity, T)>,
         entities: &Entities,
         storage: &WriteStorage<T>,
-    ) -> Vec<(Entity, T)> {
-        Vec::from_iter(
+    ) {
+        saved_components.clear();
+        saved_components.extend(
             (entities, storage)
                 .join()
                 .map(|(entity, component)| (entity, component.clone())),
-        )
+        );
     }
 
     pub fn load_storage_from<T: Clone + Component>(
@@ -160,16 +205,18 @@ impl SavedWorldState {
 pub struct FramedUpdates<T> {
     pub oldest_updated_frame: u64,
     pub updates: VecDeque<T>,
+    frames_limit: usize,
+}
+
+impl<T> FramedUpdates<T> {
+    pub fn set_frames_limit(&mut self, frames_limit: usize) {
+        self.frames_limit = frames_limit;
+    }
 }
 
 impl<T: FramedUpdate + ::std::fmt::Debug> FramedUpdates<T> {
     pub fn reserve_updates(&mut self, frame_number: u64) {
-        if frame_number == 0 && self.updates.is_empty() {
-            self.add_update();
-            return;
-        }
-
-        let frames_to_add = frame_number.saturating_sub(self.latest_frame());
+        let frames_to_add = (frame_number + 1).saturating_sub(self.next_frame());
         for _ in 0..frames_to_add {
             self.add_update();
         }
@@ -243,7 +290,7 @@ impl<T: FramedUpdate + ::std::fmt::Debug> FramedUpdates<T> {
     }
 
     fn add_update(&mut self) {
-        if self.updates.len() == SAVED_WORLD_STATES_LIMIT {
+        while self.updates.len() >= self.frames_limit {
             let removed_update = self.updates.pop_front().unwrap();
             if removed_update.frame_number() == self.oldest_updated_frame {
                 self.oldest_updated_frame += 1;
@@ -257,9 +304,11 @@ impl<T: FramedUpdate + ::std::fmt::Debug> FramedUpdates<T> {
 
 impl<T> Default for FramedUpdates<T> {
     fn default() -> Self {
+        let frames_limit = default_frames_limit();
         Self {
             oldest_updated_frame: 0,
-            updates: VecDeque::with_capacity(SAVED_WORLD_STATES_LIMIT),
+            updates: VecDeque::with_capacity(frames_limit),
+            frames_limit,
         }
     }
 }
@@ -378,7 +427,7 @@ impl FramedUpdate for PlayerActionUpdates {
     }
 }
 
-/// Server uses it as the main resource of client updates, stores SAVED_WORLD_STATES_LIMIT of them.
+/// Server uses it as the main resource of client updates, stores SAVED_WORLD_STATES_SECS worth of them.
 #[derive(Debug)]
 pub struct ReceivedClientActionUpdates {
     pub frame_number: u64,
@@ -434,6 +483,10 @@ pub struct ServerWorldUpdate {
     pub damage_histories_updates: Vec<NetUpdate<DamageHistoryEntries>>,
     //    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub spawn_actions: Vec<SpawnAction>,
+    pub pickup_spawns: Vec<NetUpdate<PickupSpawn>>,
+    /// Net ids of collected pickups with net ids of players who've collected them.
+    pub pickup_collections: Vec<NetUpdate<NetIdentifier>>,
+    pub player_downed_updates: Vec<NetUpdate<PlayerDownedUpdate>>,
 }
 
 impl ServerWorldUpdate {
@@ -446,6 +499,9 @@ impl ServerWorldUpdate {
             mob_actions_updates: Vec::new(),
             damage_histories_updates: Vec::new(),
             spawn_actions: Vec::new(),
+            pickup_spawns: Vec::new(),
+            pickup_collections: Vec::new(),
+            player_downed_updates: Vec::new(),
         }
     }
 }
@@ -458,6 +514,9 @@ pub struct ReceivedServerWorldUpdate {
     pub controlled_player_updates: ReceivedPlayerUpdate,
     pub mob_actions_updates: Vec<NetUpdateWithPosition<MobAction<NetIdentifier>>>,
     pub damage_histories_updates: Vec<NetUpdate<DamageHistoryEntries>>,
+    pub pickup_spawns: Vec<NetUpdate<PickupSpawn>>,
+    pub pickup_collections: Vec<NetUpdate<NetIdentifier>>,
+    pub player_downed_updates: Vec<NetUpdate<PlayerDownedUpdate>>,
 }
 
 impl ReceivedServerWorldUpdate {
@@ -468,6 +527,9 @@ impl ReceivedServerWorldUpdate {
         self.player_updates.player_cast_actions_updates = server_update.player_cast_actions_updates;
         self.mob_actions_updates = server_update.mob_actions_updates;
         self.damage_histories_updates = server_update.damage_histories_updates;
+        self.pickup_spawns = server_update.pickup_spawns;
+        self.pickup_collections = server_update.pickup_collections;
+        self.player_downed_updates = server_update.player_downed_updates;
     }
 }
 
@@ -489,6 +551,9 @@ impl FramedUpdate for ReceivedServerWorldUpdate {
             controlled_player_updates: ReceivedPlayerUpdate::default(),
             mob_actions_updates: Vec::new(),
             damage_histories_updates: Vec::new(),
+            pickup_spawns: Vec::new(),
+            pickup_collections: Vec::new(),
+            player_downed_updates: Vec::new(),
         }
     }
 
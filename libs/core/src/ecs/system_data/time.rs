@@ -6,18 +6,25 @@ use amethyst::{
 
 use std::time::Duration;
 
-use crate::ecs::resources::GameTime;
+use crate::{
+    ecs::resources::{
+        world::{LAG_COMPENSATION_SECS, PAUSE_THRESHOLD_SECS, SAVED_WORLD_STATES_SECS},
+        GameTime, SimulationTicks,
+    },
+    net::INTERPOLATION_DELAY_SECS,
+};
 
 #[derive(SystemData)]
 pub struct GameTimeService<'a> {
     engine_time: ReadExpect<'a, Time>,
+    simulation_ticks: ReadExpect<'a, SimulationTicks>,
     game_time: WriteExpect<'a, GameTime>,
 }
 
 impl<'a> GameTimeService<'a> {
     pub fn set_game_start_time(&mut self) {
         self.game_time.level_started_at = self.engine_time.absolute_time();
-        self.game_time.started_at_frame_number = self.engine_time.frame_number();
+        self.game_time.started_at_frame_number = self.simulation_ticks.tick_number;
         self.game_time.frames_skipped = 0;
     }
 
@@ -32,12 +39,13 @@ impl<'a> GameTimeService<'a> {
     }
 
     pub fn game_frame_number(&self) -> u64 {
-        (self.engine_time.frame_number() - self.game_time.started_at_frame_number)
+        self.game_frame_number_absolute()
             .saturating_sub(self.game_time.frames_skipped)
     }
 
+    /// Game frames are counted in simulation ticks, not in rendered frames.
     pub fn game_frame_number_absolute(&self) -> u64 {
-        self.engine_time.frame_number() - self.game_time.started_at_frame_number
+        self.simulation_ticks.tick_number - self.game_time.started_at_frame_number
     }
 
     pub fn seconds_to_frame(&self, game_frame_number: u64) -> f32 {
@@ -54,4 +62,24 @@ impl<'a> GameTimeService<'a> {
     pub fn seconds_between_frames(&self, lhs: u64, rhs: u64) -> f32 {
         (lhs as f32 - rhs as f32).abs() * self.engine_time.fixed_seconds()
     }
+
+    pub fn frames_in_seconds(&self, seconds: f32) -> u64 {
+        (seconds / self.engine_time.fixed_seconds()).round() as u64
+    }
+
+    pub fn interpolation_frame_delay(&self) -> u64 {
+        self.frames_in_seconds(INTERPOLATION_DELAY_SECS)
+    }
+
+    pub fn saved_world_states_limit(&self) -> u64 {
+        self.frames_in_seconds(SAVED_WORLD_STATES_SECS)
+    }
+
+    pub fn lag_compensation_frames_limit(&self) -> u64 {
+        self.frames_in_seconds(LAG_COMPENSATION_SECS)
+    }
+
+    pub fn pause_frame_threshold(&self) -> u64 {
+        self.frames_in_seconds(PAUSE_THRESHOLD_SECS)
+    }
 }
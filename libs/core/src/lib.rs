@@ -2,6 +2,7 @@ pub mod actions;
 pub mod ecs;
 pub mod math;
 pub mod net;
+pub mod progression;
 
 pub static PLAYER_COLORS: [[f32; 3]; 5] = [
     [0.64, 0.12, 0.11],
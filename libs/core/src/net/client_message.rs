@@ -9,6 +9,7 @@ use crate::{
     },
     ecs::resources::world::{ImmediatePlayerActionsUpdates, PlayerLookActionUpdates},
     net::NetIdentifier,
+    progression::PlayerUpgrades,
 };
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -26,6 +27,14 @@ pub enum ClientMessagePayload {
         // in case there are duplicates of reliable messages.
         sent_at: Duration,
         nickname: String,
+        /// A server rejects clients whose monster definitions differ from its own.
+        monster_definitions_checksum: u64,
+        /// The same goes for spell definitions.
+        spell_definitions_checksum: u64,
+        /// Profiles are stored by clients, so a server only checks that the upgrades are
+        /// affordable with this experience, but can't verify the experience itself.
+        experience: u32,
+        upgrades: PlayerUpgrades,
     },
     StartHostedGame,
     AcknowledgeWorldUpdate(u64),
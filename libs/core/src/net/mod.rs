@@ -10,7 +10,56 @@ use crate::ecs::components::WorldPosition;
 pub type EncodedMessage = Bytes;
 pub type NetIdentifier = u64;
 
-pub const INTERPOLATION_FRAME_DELAY: u64 = 10;
+/// Clients run ahead of a server for this time, so that their actions reach it in time.
+/// It's 10 frames with the default tick rate.
+pub const INTERPOLATION_DELAY_SECS: f32 = 1.0 / 6.0;
+
+/// The number of frames rendered by clients per second, unless the tick rate is higher.
+pub const CLIENT_FRAME_RATE_LIMIT: u32 = 60;
+
+/// The number of frames simulated per second.
+/// A server sends its tick rate to clients with the StartGame message.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TickRate {
+    Hz30,
+    Hz60,
+    Hz120,
+}
+
+impl TickRate {
+    pub fn from_frames_per_second(frames_per_second: u32) -> Option<Self> {
+        match frames_per_second {
+            30 => Some(TickRate::Hz30),
+            60 => Some(TickRate::Hz60),
+            120 => Some(TickRate::Hz120),
+            _ => None,
+        }
+    }
+
+    pub fn frames_per_second(self) -> u32 {
+        match self {
+            TickRate::Hz30 => 30,
+            TickRate::Hz60 => 60,
+            TickRate::Hz120 => 120,
+        }
+    }
+
+    pub fn fixed_seconds(self) -> f32 {
+        1.0 / self.frames_per_second() as f32
+    }
+
+    /// A rendered frame simulates one tick at most, so clients render at least as many frames
+    /// per second as they simulate.
+    pub fn client_frame_rate_limit(self) -> u32 {
+        self.frames_per_second().max(CLIENT_FRAME_RATE_LIMIT)
+    }
+}
+
+impl Default for TickRate {
+    fn default() -> Self {
+        TickRate::Hz60
+    }
+}
 
 pub struct ConnectionNetEvent<T> {
     pub connection_id: NetIdentifier,
@@ -1,8 +1,8 @@
 use serde_derive::{Deserialize, Serialize};
 
 use crate::{
-    ecs::resources::{net::MultiplayerRoomPlayer, world::ServerWorldUpdate},
-    net::NetIdentifier,
+    ecs::resources::{net::MultiplayerRoomPlayer, stats::RunSummary, world::ServerWorldUpdate},
+    net::{NetIdentifier, TickRate},
 };
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -15,8 +15,11 @@ pub struct ServerMessage {
 pub enum ServerMessagePayload {
     Heartbeat,
     UpdateRoomPlayers(Vec<MultiplayerRoomPlayer>),
-    /// Contains pairs of server (entity) ids and their corresponding players.
-    StartGame(Vec<(NetIdentifier, MultiplayerRoomPlayer)>),
+    StartGame {
+        tick_rate: TickRate,
+        /// Contains pairs of server (entity) ids and their corresponding players.
+        players: Vec<(NetIdentifier, MultiplayerRoomPlayer)>,
+    },
     Handshake {
         net_id: NetIdentifier,
         is_host: bool,
@@ -41,6 +44,11 @@ pub enum ServerMessagePayload {
         players: Vec<NetIdentifier>,
     },
     UnpauseWaitingForPlayers(NetIdentifier),
+    /// The kills of a player that can't be rolled back anymore, so that the player gets
+    /// experience for them during a game.
+    ConfirmedKills(u32),
+    /// Is sent once all players are dead.
+    GameOver(RunSummary),
     Disconnect(DisconnectReason),
 }
 
@@ -59,6 +67,8 @@ pub enum DisconnectReason {
     Uninitialized,
     GameIsStarted,
     RoomIsFull,
+    /// Monster or spell definitions of a client don't match the server ones.
+    DefinitionsMismatch,
     Kick,
     Closed,
     ServerCrashed(i32),
@@ -0,0 +1,138 @@
+use serde_derive::{Deserialize, Serialize};
+
+pub const BASE_PLAYER_HEALTH: f32 = 100.0;
+pub const BASE_PLAYER_SPEED: f32 = 200.0;
+pub const BASE_PLAYER_MANA: f32 = 100.0;
+pub const MANA_REGEN_PER_SEC: f32 = 10.0;
+
+pub const EXPERIENCE_PER_KILL: u32 = 10;
+pub const EXPERIENCE_PER_LEVEL: u32 = 200;
+pub const MAX_UPGRADE_LEVEL: u8 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Upgrade {
+    SpellDamage,
+    CastCooldown,
+    MoveSpeed,
+    MaxHealth,
+}
+
+impl Upgrade {
+    pub const ALL: [Upgrade; 4] = [
+        Upgrade::SpellDamage,
+        Upgrade::CastCooldown,
+        Upgrade::MoveSpeed,
+        Upgrade::MaxHealth,
+    ];
+}
+
+/// Upgrade levels bought with the points a player gets for leveling up.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerUpgrades {
+    /// Profiles saved by older versions call it `missile_damage`.
+    #[serde(alias = "missile_damage")]
+    pub spell_damage: u8,
+    pub cast_cooldown: u8,
+    pub move_speed: u8,
+    pub max_health: u8,
+}
+
+impl PlayerUpgrades {
+    pub fn level(&self, upgrade: Upgrade) -> u8 {
+        match upgrade {
+            Upgrade::SpellDamage => self.spell_damage,
+            Upgrade::CastCooldown => self.cast_cooldown,
+            Upgrade::MoveSpeed => self.move_speed,
+            Upgrade::MaxHealth => self.max_health,
+        }
+    }
+
+    pub fn points_spent(&self) -> u32 {
+        Upgrade::ALL
+            .iter()
+            .map(|upgrade| u32::from(self.level(*upgrade)))
+            .sum()
+    }
+
+    /// A server can't trust the levels sent by clients, so it caps them by the maximum level
+    /// and takes back the points a player doesn't have, starting from the last upgrades.
+    pub fn clamped(self, points: u32) -> Self {
+        let mut upgrades = Self {
+            spell_damage: self.spell_damage.min(MAX_UPGRADE_LEVEL),
+            cast_cooldown: self.cast_cooldown.min(MAX_UPGRADE_LEVEL),
+            move_speed: self.move_speed.min(MAX_UPGRADE_LEVEL),
+            max_health: self.max_health.min(MAX_UPGRADE_LEVEL),
+        };
+        let mut excess_points = upgrades.points_spent().saturating_sub(points);
+        for upgrade in Upgrade::ALL.iter().rev() {
+            let level = upgrades.level_mut(*upgrade);
+            let taken_points = excess_points.min(u32::from(*level));
+            *level -= taken_points as u8;
+            excess_points -= taken_points;
+        }
+        upgrades
+    }
+
+    pub fn max_health(&self) -> f32 {
+        BASE_PLAYER_HEALTH + 20.0 * f32::from(self.max_health)
+    }
+
+    pub fn move_speed(&self) -> f32 {
+        BASE_PLAYER_SPEED * (1.0 + 0.05 * f32::from(self.move_speed))
+    }
+
+    /// Damage of every spell is multiplied by this.
+    pub fn spell_damage_multiplier(&self) -> f32 {
+        1.0 + 0.1 * f32::from(self.spell_damage)
+    }
+
+    /// Cooldowns of every spell are multiplied by this.
+    pub fn cast_cooldown_multiplier(&self) -> f32 {
+        1.0 - 0.08 * f32::from(self.cast_cooldown)
+    }
+
+    fn level_mut(&mut self, upgrade: Upgrade) -> &mut u8 {
+        match upgrade {
+            Upgrade::SpellDamage => &mut self.spell_damage,
+            Upgrade::CastCooldown => &mut self.cast_cooldown,
+            Upgrade::MoveSpeed => &mut self.move_speed,
+            Upgrade::MaxHealth => &mut self.max_health,
+        }
+    }
+}
+
+/// The progress of a player that persists between runs.
+///
+/// Profiles are stored by clients, servers don't keep anything between rooms. So in multiplayer
+/// a server awards experience only for the kills it confirms, but it trusts the experience
+/// a client reports on joining a room, making sure just that the upgrades are affordable with it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlayerProfile {
+    pub experience: u32,
+    pub upgrades: PlayerUpgrades,
+}
+
+impl PlayerProfile {
+    /// Starts with 1, each level gives a point to spend on upgrades.
+    pub fn level(&self) -> u32 {
+        1 + self.experience / EXPERIENCE_PER_LEVEL
+    }
+
+    /// The points given for every level, spent or not.
+    pub fn points(&self) -> u32 {
+        self.level() - 1
+    }
+
+    pub fn unspent_points(&self) -> u32 {
+        self.points().saturating_sub(self.upgrades.points_spent())
+    }
+
+    /// Returns `false` if there are no points to spend or the upgrade is maxed out.
+    pub fn buy_upgrade(&mut self, upgrade: Upgrade) -> bool {
+        if self.unspent_points() == 0 || self.upgrades.level(upgrade) >= MAX_UPGRADE_LEVEL {
+            return false;
+        }
+        *self.upgrades.level_mut(upgrade) += 1;
+        true
+    }
+}
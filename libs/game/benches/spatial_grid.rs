@@ -0,0 +1,185 @@
+//! Compares monster queries done with `SpatialGrid` against linear search.
+//!
+//! Linear search runs over a plain `Vec`, which is cheaper than the storage joins used before,
+//! so the measured speedup is a lower bound.
+#![feature(test)]
+
+extern crate test;
+
+use amethyst::ecs::{Builder, Entity, World, WorldExt};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use test::{black_box, Bencher};
+
+use gv_core::math::Vector2;
+use gv_game::{
+    ecs::resources::{SpatialGrid, SpatialGridEntry, SpatialGrids},
+    utils::world::{closest_monster, find_first_hit_monster},
+};
+
+const LEVEL_HALF_SIZE: f32 = 2048.0;
+const QUERIES: usize = 100;
+const MONSTER_RADIUS: f32 = 12.0;
+const MISSILE_RADIUS: f32 = 5.0;
+
+struct Scene {
+    monsters: Vec<SpatialGridEntry>,
+    grid: SpatialGrid,
+    queries: Vec<Vector2>,
+}
+
+fn scene(monsters_count: usize) -> Scene {
+    let mut world = World::new();
+    let mut rng = StdRng::seed_from_u64(42);
+    let mut random_position = move || {
+        Vector2::new(
+            rng.gen_range(-LEVEL_HALF_SIZE, LEVEL_HALF_SIZE),
+            rng.gen_range(-LEVEL_HALF_SIZE, LEVEL_HALF_SIZE),
+        )
+    };
+
+    let monsters: Vec<SpatialGridEntry> = (0..monsters_count)
+        .map(|_| SpatialGridEntry {
+            entity: world.create_entity().build(),
+            position: random_position(),
+            radius: MONSTER_RADIUS,
+        })
+        .collect();
+    let mut grid = SpatialGrids::default().monsters;
+    grid.rebuild(monsters.iter().cloned());
+    let queries = (0..QUERIES).map(|_| random_position()).collect();
+
+    Scene {
+        monsters,
+        grid,
+        queries,
+    }
+}
+
+fn closest_monster_linear(position: Vector2, monsters: &[SpatialGridEntry]) -> Option<Entity> {
+    monsters
+        .iter()
+        .fold(None, |res: Option<&SpatialGridEntry>, monster| {
+            if let Some(closest_monster) = res {
+                if (closest_monster.position - position).norm_squared()
+                    > (monster.position - position).norm_squared()
+                {
+                    Some(monster)
+                } else {
+                    Some(closest_monster)
+                }
+            } else {
+                Some(monster)
+            }
+        })
+        .map(|monster| monster.entity)
+}
+
+fn find_first_hit_monster_linear(
+    position: Vector2,
+    radius: f32,
+    monsters: &[SpatialGridEntry],
+) -> Option<Entity> {
+    monsters
+        .iter()
+        .find(|monster| {
+            let impact_distance = radius + monster.radius;
+            (position - monster.position).norm_squared() <= impact_distance * impact_distance
+        })
+        .map(|monster| monster.entity)
+}
+
+fn bench_closest_linear(b: &mut Bencher, monsters_count: usize) {
+    let scene = scene(monsters_count);
+    b.iter(|| {
+        for query in &scene.queries {
+            black_box(closest_monster_linear(*query, &scene.monsters));
+        }
+    });
+}
+
+fn bench_closest_grid(b: &mut Bencher, monsters_count: usize) {
+    let scene = scene(monsters_count);
+    b.iter(|| {
+        for query in &scene.queries {
+            black_box(closest_monster(*query, &scene.grid));
+        }
+    });
+}
+
+fn bench_first_hit_linear(b: &mut Bencher, monsters_count: usize) {
+    let scene = scene(monsters_count);
+    b.iter(|| {
+        for query in &scene.queries {
+            black_box(find_first_hit_monster_linear(
+                *query,
+                MISSILE_RADIUS,
+                &scene.monsters,
+            ));
+        }
+    });
+}
+
+fn bench_first_hit_grid(b: &mut Bencher, monsters_count: usize) {
+    let scene = scene(monsters_count);
+    b.iter(|| {
+        for query in &scene.queries {
+            black_box(find_first_hit_monster(*query, MISSILE_RADIUS, &scene.grid));
+        }
+    });
+}
+
+fn bench_rebuild(b: &mut Bencher, monsters_count: usize) {
+    let mut scene = scene(monsters_count);
+    let monsters = scene.monsters.clone();
+    b.iter(|| scene.grid.rebuild(monsters.iter().cloned()));
+}
+
+#[bench]
+fn closest_monster_linear_1k(b: &mut Bencher) {
+    bench_closest_linear(b, 1000);
+}
+
+#[bench]
+fn closest_monster_grid_1k(b: &mut Bencher) {
+    bench_closest_grid(b, 1000);
+}
+
+#[bench]
+fn closest_monster_linear_5k(b: &mut Bencher) {
+    bench_closest_linear(b, 5000);
+}
+
+#[bench]
+fn closest_monster_grid_5k(b: &mut Bencher) {
+    bench_closest_grid(b, 5000);
+}
+
+#[bench]
+fn first_hit_monster_linear_1k(b: &mut Bencher) {
+    bench_first_hit_linear(b, 1000);
+}
+
+#[bench]
+fn first_hit_monster_grid_1k(b: &mut Bencher) {
+    bench_first_hit_grid(b, 1000);
+}
+
+#[bench]
+fn first_hit_monster_linear_5k(b: &mut Bencher) {
+    bench_first_hit_linear(b, 5000);
+}
+
+#[bench]
+fn first_hit_monster_grid_5k(b: &mut Bencher) {
+    bench_first_hit_grid(b, 5000);
+}
+
+#[bench]
+fn grid_rebuild_1k(b: &mut Bencher) {
+    bench_rebuild(b, 1000);
+}
+
+#[bench]
+fn grid_rebuild_5k(b: &mut Bencher) {
+    bench_rebuild(b, 5000);
+}
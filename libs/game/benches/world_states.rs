@@ -0,0 +1,331 @@
+//! Compares collections for storing saved components of `SavedWorldState`.
+//!
+//! Saving copies a whole storage each frame, restoring inserts every saved component back,
+//! so neither of them needs lookups by an entity. Every collection saves and restores the same
+//! mix of components, the one of a busy survival wave.
+#![feature(test)]
+
+extern crate test;
+
+use amethyst::ecs::{Builder, Component, Entity, Join, World, WorldExt, WriteStorage};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use test::{black_box, Bencher};
+
+use std::collections::{BTreeMap, HashMap};
+
+use gv_core::{
+    actions::{mob::MobAction, Action},
+    ecs::{
+        components::{
+            damage_history::DamageType,
+            missile::{Missile, MissileBehaviour, MissileSpell, MissileTarget},
+            pickup::{Pickup, PickupKind, PICKUP_RADIUS},
+            status_effect::{StatusEffectKind, StatusEffects},
+            Dead, Monster, Player, PlayerActions, PlayerLastCastedSpells, WorldPosition,
+        },
+        resources::world::SavedWorldState,
+    },
+    math::Vector2,
+};
+
+const PLAYERS_COUNT: usize = 4;
+const MONSTERS_COUNT: usize = 500;
+const MISSILES_COUNT: usize = 200;
+const PICKUPS_COUNT: usize = 50;
+
+fn random_position(rng: &mut StdRng) -> Vector2 {
+    Vector2::new(
+        rng.gen_range(-1024.0, 1024.0),
+        rng.gen_range(-1024.0, 1024.0),
+    )
+}
+
+/// Every entity has a `WorldPosition`, some of the monsters are slowed or already dead.
+fn world() -> World {
+    let mut world = World::new();
+    world.register::<Player>();
+    world.register::<PlayerActions>();
+    world.register::<PlayerLastCastedSpells>();
+    world.register::<Monster>();
+    world.register::<Missile>();
+    world.register::<Pickup>();
+    world.register::<StatusEffects>();
+    world.register::<WorldPosition>();
+    world.register::<Dead>();
+    let mut rng = StdRng::seed_from_u64(42);
+
+    let mut players = Vec::with_capacity(PLAYERS_COUNT);
+    for _ in 0..PLAYERS_COUNT {
+        let mut last_casted_spells = PlayerLastCastedSpells::default();
+        last_casted_spells.set_frame_number("magic_missile", 0);
+        last_casted_spells.set_frame_number("fireball", 0);
+        let player = world
+            .create_entity()
+            .with(Player::default())
+            .with(PlayerActions::default())
+            .with(last_casted_spells)
+            .with(WorldPosition::new(random_position(&mut rng)))
+            .build();
+        players.push(player);
+    }
+
+    for i in 0..MONSTERS_COUNT {
+        let position = random_position(&mut rng);
+        let mut status_effects = StatusEffects::default();
+        if i % 3 == 0 {
+            status_effects.apply(StatusEffectKind::Slow, 0, 120, Some(players[0]));
+        }
+        let monster = world
+            .create_entity()
+            .with(Monster {
+                health: 100.0,
+                attack_damage: 10.0,
+                destination: position,
+                facing_direction: Vector2::new(0.0, 1.0),
+                velocity: Vector2::new(0.0, 0.0),
+                action: Action {
+                    frame_number: 0,
+                    action: MobAction::Idle,
+                },
+                stunned_frames: 0,
+                name: "Beetle".to_owned(),
+                radius: 16.0,
+            })
+            .with(status_effects)
+            .with(WorldPosition::new(position));
+        if i % 10 == 0 {
+            monster.with(Dead::new(0, 0)).build();
+        } else {
+            monster.build();
+        }
+    }
+
+    for i in 0..MISSILES_COUNT {
+        let position = random_position(&mut rng);
+        world
+            .create_entity()
+            .with(Missile::new(
+                i as u64,
+                players[i % PLAYERS_COUNT],
+                5.0,
+                MissileTarget::Destination(position),
+                Vector2::new(0.0, 1.0),
+                0,
+                MissileSpell {
+                    behaviour: MissileBehaviour::Homing,
+                    damage: 50.0,
+                    damage_type: DamageType::Fire,
+                    status_effect: None,
+                },
+            ))
+            .with(WorldPosition::new(position))
+            .build();
+    }
+
+    for _ in 0..PICKUPS_COUNT {
+        world
+            .create_entity()
+            .with(Pickup {
+                kind: PickupKind::HealthOrb,
+                dropped_by: None,
+                radius: PICKUP_RADIUS,
+                frame_spawned: 0,
+            })
+            .with(WorldPosition::new(random_position(&mut rng)))
+            .build();
+    }
+
+    world
+}
+
+/// Saved components of a single storage.
+trait SavedColumn {
+    fn save(&mut self, world: &World);
+
+    fn restore(&self, world: &World);
+}
+
+struct PooledVecColumn<T>(Vec<(Entity, T)>);
+
+impl<T: Clone + Component> SavedColumn for PooledVecColumn<T> {
+    fn save(&mut self, world: &World) {
+        SavedWorldState::save_storage_to(
+            &mut self.0,
+            &world.entities(),
+            &world.write_storage::<T>(),
+        );
+    }
+
+    fn restore(&self, world: &World) {
+        SavedWorldState::load_storage_from(&mut world.write_storage::<T>(), &self.0);
+    }
+}
+
+struct VecColumn<T>(Vec<(Entity, T)>);
+
+impl<T: Clone + Component> SavedColumn for VecColumn<T> {
+    fn save(&mut self, world: &World) {
+        self.0 = (&world.entities(), &world.read_storage::<T>())
+            .join()
+            .map(|(entity, component)| (entity, component.clone()))
+            .collect();
+    }
+
+    fn restore(&self, world: &World) {
+        SavedWorldState::load_storage_from(&mut world.write_storage::<T>(), &self.0);
+    }
+}
+
+struct HashMapColumn<T>(HashMap<Entity, T>);
+
+impl<T: Clone + Component> SavedColumn for HashMapColumn<T> {
+    fn save(&mut self, world: &World) {
+        self.0 = (&world.entities(), &world.read_storage::<T>())
+            .join()
+            .map(|(entity, component)| (entity, component.clone()))
+            .collect();
+    }
+
+    fn restore(&self, world: &World) {
+        restore(&mut world.write_storage::<T>(), self.0.iter());
+    }
+}
+
+struct BTreeMapColumn<T>(BTreeMap<Entity, T>);
+
+impl<T: Clone + Component> SavedColumn for BTreeMapColumn<T> {
+    fn save(&mut self, world: &World) {
+        self.0 = (&world.entities(), &world.read_storage::<T>())
+            .join()
+            .map(|(entity, component)| (entity, component.clone()))
+            .collect();
+    }
+
+    fn restore(&self, world: &World) {
+        restore(&mut world.write_storage::<T>(), self.0.iter());
+    }
+}
+
+/// Does the same as `SavedWorldState::load_storage_from`, but for maps.
+fn restore<'a, T: Clone + Component>(
+    storage: &mut WriteStorage<T>,
+    saved_components: impl Iterator<Item = (&'a Entity, &'a T)>,
+) {
+    for (entity, component) in saved_components {
+        let is_the_same_generation = storage.contains(*entity);
+        if is_the_same_generation {
+            storage
+                .insert(*entity, component.clone())
+                .expect("Expected to insert a saved component");
+        }
+    }
+}
+
+trait ColumnKind {
+    fn column<T: Clone + Component>() -> Box<dyn SavedColumn>;
+}
+
+struct PooledVecKind;
+
+impl ColumnKind for PooledVecKind {
+    fn column<T: Clone + Component>() -> Box<dyn SavedColumn> {
+        Box::new(PooledVecColumn::<T>(Vec::new()))
+    }
+}
+
+struct VecKind;
+
+impl ColumnKind for VecKind {
+    fn column<T: Clone + Component>() -> Box<dyn SavedColumn> {
+        Box::new(VecColumn::<T>(Vec::new()))
+    }
+}
+
+struct HashMapKind;
+
+impl ColumnKind for HashMapKind {
+    fn column<T: Clone + Component>() -> Box<dyn SavedColumn> {
+        Box::new(HashMapColumn::<T>(HashMap::new()))
+    }
+}
+
+struct BTreeMapKind;
+
+impl ColumnKind for BTreeMapKind {
+    fn column<T: Clone + Component>() -> Box<dyn SavedColumn> {
+        Box::new(BTreeMapColumn::<T>(BTreeMap::new()))
+    }
+}
+
+fn saved_columns<K: ColumnKind>() -> Vec<Box<dyn SavedColumn>> {
+    vec![
+        K::column::<Player>(),
+        K::column::<PlayerActions>(),
+        K::column::<PlayerLastCastedSpells>(),
+        K::column::<Monster>(),
+        K::column::<Missile>(),
+        K::column::<Pickup>(),
+        K::column::<StatusEffects>(),
+        K::column::<WorldPosition>(),
+        K::column::<Dead>(),
+    ]
+}
+
+fn bench_save<K: ColumnKind>(b: &mut Bencher) {
+    let world = world();
+    let mut columns = saved_columns::<K>();
+    b.iter(|| {
+        for column in &mut columns {
+            column.save(&world);
+        }
+        black_box(&columns);
+    });
+}
+
+fn bench_restore<K: ColumnKind>(b: &mut Bencher) {
+    let world = world();
+    let mut columns = saved_columns::<K>();
+    for column in &mut columns {
+        column.save(&world);
+    }
+    b.iter(|| {
+        for column in &columns {
+            column.restore(&world);
+        }
+    });
+}
+
+#[bench]
+fn save_vec_pooled(b: &mut Bencher) {
+    bench_save::<PooledVecKind>(b);
+}
+
+#[bench]
+fn save_vec(b: &mut Bencher) {
+    bench_save::<VecKind>(b);
+}
+
+#[bench]
+fn save_hash_map(b: &mut Bencher) {
+    bench_save::<HashMapKind>(b);
+}
+
+#[bench]
+fn save_btree_map(b: &mut Bencher) {
+    bench_save::<BTreeMapKind>(b);
+}
+
+#[bench]
+fn restore_vec(b: &mut Bencher) {
+    bench_restore::<PooledVecKind>(b);
+}
+
+#[bench]
+fn restore_hash_map(b: &mut Bencher) {
+    bench_restore::<HashMapKind>(b);
+}
+
+#[bench]
+fn restore_btree_map(b: &mut Bencher) {
+    bench_restore::<BTreeMapKind>(b);
+}
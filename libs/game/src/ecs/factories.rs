@@ -17,16 +17,21 @@ use amethyst::{
 use gv_animation_prefabs::GameSpriteAnimationPrefab;
 #[cfg(feature = "client")]
 use gv_client_shared::ecs::resources::AssetHandles;
+#[cfg(feature = "client")]
+use gv_core::math::Vector3;
 use gv_core::{
     actions::{mob::MobAction, Action},
     ecs::{
-        components::{damage_history::DamageHistory, *},
+        components::{damage_history::DamageHistory, status_effect::StatusEffects, *},
         tags::*,
     },
     math::{Vector2, ZeroVector},
+    progression::PlayerUpgrades,
 };
 
 use crate::ecs::resources::MonsterDefinition;
+#[cfg(feature = "client")]
+use crate::ecs::resources::MonsterPrefab;
 
 #[derive(SystemData)]
 pub struct PlayerFactory<'s> {
@@ -38,10 +43,11 @@ pub struct PlayerFactory<'s> {
     players: WriteStorage<'s, Player>,
     player_last_casted_spells: WriteStorage<'s, PlayerLastCastedSpells>,
     damage_histories: WriteStorage<'s, DamageHistory>,
+    status_effects: WriteStorage<'s, StatusEffects>,
 }
 
 impl<'s> PlayerFactory<'s> {
-    pub fn create(&mut self) -> Entity {
+    pub fn create(&mut self, upgrades: PlayerUpgrades) -> Entity {
         let mut transform = Transform::default();
         transform.set_translation_z(10.0);
 
@@ -57,12 +63,13 @@ impl<'s> PlayerFactory<'s> {
                 NetWorldPosition::new(Vector2::zero()),
                 &mut self.net_world_positions,
             )
-            .with(Player::new(), &mut self.players)
+            .with(Player::new(upgrades), &mut self.players)
             .with(
                 PlayerLastCastedSpells::default(),
                 &mut self.player_last_casted_spells,
             )
             .with(DamageHistory::new(0), &mut self.damage_histories)
+            .with(StatusEffects::default(), &mut self.status_effects)
             .build()
     }
 }
@@ -123,6 +130,7 @@ pub struct MonsterFactory<'s> {
     pub sprite_animation_handles: WriteStorage<'s, Handle<Prefab<GameSpriteAnimationPrefab>>>,
     pub monsters: WriteStorage<'s, Monster>,
     pub damage_histories: WriteStorage<'s, DamageHistory>,
+    pub status_effects: WriteStorage<'s, StatusEffects>,
     pub world_positions: WriteStorage<'s, WorldPosition>,
 }
 
@@ -145,9 +153,15 @@ impl<'s> MonsterFactory<'s> {
             base_speed: _base_speed,
             base_attack_damage: attack_damage,
             collision_radius: radius,
+            prefab,
+            scale,
             ..
         } = definition;
-        let beetle_prefab = self.asset_handles.as_ref().unwrap().beetle_prefab.clone();
+        transform.set_scale(Vector3::new(scale, scale, 1.0));
+        let asset_handles = self.asset_handles.as_ref().unwrap();
+        let prefab = match prefab {
+            MonsterPrefab::Beetle => asset_handles.beetle_prefab.clone(),
+        };
 
         let facing_direction = destination - position;
         let facing_direction = if facing_direction.norm_squared() > 0.0 {
@@ -158,7 +172,7 @@ impl<'s> MonsterFactory<'s> {
 
         self.entities
             .build_entity()
-            .with(beetle_prefab, &mut self.sprite_animation_handles)
+            .with(prefab, &mut self.sprite_animation_handles)
             .with(transform, &mut self.transforms)
             .with(WorldPosition::new(position), &mut self.world_positions)
             .with(
@@ -169,6 +183,7 @@ impl<'s> MonsterFactory<'s> {
                     facing_direction,
                     velocity: Vector2::zero(),
                     action,
+                    stunned_frames: 0,
                     name,
                     radius,
                 },
@@ -178,6 +193,7 @@ impl<'s> MonsterFactory<'s> {
                 DamageHistory::new(frame_spawned),
                 &mut self.damage_histories,
             )
+            .with(StatusEffects::default(), &mut self.status_effects)
             .build()
     }
 
@@ -221,6 +237,7 @@ impl<'s> MonsterFactory<'s> {
                     facing_direction,
                     velocity: Vector2::zero(),
                     action,
+                    stunned_frames: 0,
                     name,
                     radius,
                 },
@@ -230,6 +247,7 @@ impl<'s> MonsterFactory<'s> {
                 DamageHistory::new(frame_spawned),
                 &mut self.damage_histories,
             )
+            .with(StatusEffects::default(), &mut self.status_effects)
             .build()
     }
 }
@@ -0,0 +1,222 @@
+use serde::de::Error as _;
+use serde_derive::Deserialize;
+
+use gv_core::{actions::monster_spawn::Side, ecs::resources::GameLevelState};
+
+use super::{MonsterDefinitions, ObstacleDefinition};
+
+const CAMPAIGN_BYTES: &[u8] = include_bytes!("../../../../../resources/campaign.ron");
+/// Survival games and multiplayer ones are always played on this level.
+pub const SURVIVAL_LEVEL: &str = "desert";
+const LEVELS: &[(&str, &[u8])] = &[
+    (
+        SURVIVAL_LEVEL,
+        include_bytes!("../../../../../resources/levels/desert.ron"),
+    ),
+    (
+        "outskirts",
+        include_bytes!("../../../../../resources/levels/outskirts.ron"),
+    ),
+    (
+        "dunes",
+        include_bytes!("../../../../../resources/levels/dunes.ron"),
+    ),
+    (
+        "ghoul_lair",
+        include_bytes!("../../../../../resources/levels/ghoul_lair.ron"),
+    ),
+];
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LevelDefinition {
+    /// Describes the landscape texture, the other levels reuse the one of the survival level.
+    #[serde(default)]
+    pub landscape: Option<LandscapeDefinition>,
+    pub obstacles: Vec<ObstacleDefinition>,
+    /// Areas which monsters walk around, but which don't block anyone physically.
+    #[serde(default)]
+    pub blocking_areas: Vec<ObstacleDefinition>,
+    #[serde(default)]
+    pub waves: Vec<WaveDefinition>,
+}
+
+/// The landscape is drawn as a single sprite covering the whole texture.
+#[derive(Debug, Clone, Deserialize)]
+pub struct LandscapeDefinition {
+    pub texture_width: u32,
+    pub texture_height: u32,
+}
+
+impl LevelDefinition {
+    pub fn load(name: &str) -> Result<Self, ron::de::Error> {
+        let (_, bytes) = LEVELS
+            .iter()
+            .find(|(level_name, _)| *level_name == name)
+            .ok_or_else(|| ron::de::Error::custom(format!("Unknown level {}", name)))?;
+        let level: Self = ron::de::from_bytes(bytes)?;
+        level.validate_waves(name, &MonsterDefinitions::load()?)?;
+        Ok(level)
+    }
+
+    /// LevelSystem picks a monster of a wave spawn by its weight, so every monster has to be
+    /// defined and at least one of them has to have a positive weight.
+    fn validate_waves(
+        &self,
+        name: &str,
+        monster_definitions: &MonsterDefinitions,
+    ) -> Result<(), ron::de::Error> {
+        for (wave_index, wave) in self.waves.iter().enumerate() {
+            for wave_spawn in &wave.spawns {
+                for (monster, weight) in &wave_spawn.monsters {
+                    if monster_definitions.get(monster).is_none() {
+                        return Err(ron::de::Error::custom(format!(
+                            "Unknown monster {} in wave {} of level {}",
+                            monster, wave_index, name
+                        )));
+                    }
+                    if !weight.is_finite() || *weight < 0.0 {
+                        return Err(ron::de::Error::custom(format!(
+                            "Invalid weight {} of {} in wave {} of level {}",
+                            weight, monster, wave_index, name
+                        )));
+                    }
+                }
+                if !wave_spawn.monsters.iter().any(|(_, weight)| *weight > 0.0) {
+                    return Err(ron::de::Error::custom(format!(
+                        "Expected a monster with a positive weight in wave {} of level {}",
+                        wave_index, name
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GameMode {
+    Survival,
+    /// Contains the index of the campaign level being played.
+    Campaign {
+        level: usize,
+    },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Campaign {
+    pub levels: Vec<CampaignLevel>,
+}
+
+impl Campaign {
+    pub fn load() -> Result<Self, ron::de::Error> {
+        let campaign: Self = ron::de::from_bytes(CAMPAIGN_BYTES)?;
+        for campaign_level in &campaign.levels {
+            LevelDefinition::load(&campaign_level.level)?;
+        }
+        Ok(campaign)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CampaignLevel {
+    pub name: String,
+    /// The name of a level definition.
+    pub level: String,
+    pub objective: LevelObjective,
+}
+
+/// A campaign level is complete once its objective is met.
+#[derive(Debug, Clone, Deserialize)]
+pub enum LevelObjective {
+    /// The N-th wave of the level script has spawned all its one-off spawns and every monster
+    /// is dead, or a later wave has started.
+    SurviveWaves(usize),
+    /// A monster of this definition is dead.
+    KillBoss(String),
+    /// A player has reached the area.
+    ReachArea { center: (f32, f32), radius: f32 },
+}
+
+/// Survival games don't have an objective.
+pub struct ActiveObjective(pub Option<LevelObjective>);
+
+/// Waves of monsters that `LevelSystem` runs one after another. The last wave keeps running
+/// until the game is over.
+pub struct WaveScript(pub Vec<WaveDefinition>);
+
+impl WaveScript {
+    /// Returns true if the running wave has spawned all its one-off spawns,
+    /// or if no wave has started yet.
+    pub fn is_running_wave_spawned(&self, game_level_state: &GameLevelState) -> bool {
+        let wave = match game_level_state.wave {
+            Some(wave) => &self.0[wave],
+            None => return true,
+        };
+        wave.spawns
+            .iter()
+            .zip(game_level_state.wave_spawns_done.iter())
+            .all(|(wave_spawn, spawns_done)| !wave_spawn.is_one_off() || *spawns_done > 0)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WaveDefinition {
+    pub start: WaveCondition,
+    pub spawns: Vec<WaveSpawn>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub enum WaveCondition {
+    /// Seconds since the previous wave started (or since the level started for the first one).
+    AfterSecs(f32),
+    /// The previous wave has spawned all its one-off spawns and every monster is dead.
+    AllDead,
+    /// Whichever of the two comes first.
+    AllDeadOrAfterSecs(f32),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WaveSpawn {
+    /// Names of monster definitions with their weights, a spawn picks one of them randomly.
+    pub monsters: Vec<(String, f32)>,
+    pub at: WaveSpawnPoint,
+    /// Seconds since the wave started.
+    #[serde(default)]
+    pub delay_secs: f32,
+    /// Repeats the spawn with this interval until the next wave starts.
+    #[serde(default)]
+    pub every_secs: Option<f32>,
+}
+
+impl WaveSpawn {
+    pub fn is_one_off(&self) -> bool {
+        self.every_secs.is_none()
+    }
+
+    /// Seconds since the wave started, returns `None` if a one-off spawn has already happened.
+    pub fn next_spawn_secs(&self, spawns_done: u32) -> Option<f32> {
+        match self.every_secs {
+            Some(every_secs) => Some(self.delay_secs + every_secs * spawns_done as f32),
+            None if spawns_done == 0 => Some(self.delay_secs),
+            None => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub enum WaveSpawnPoint {
+    Point {
+        position: (f32, f32),
+        count: u8,
+    },
+    /// Every monster spawns at a random point of a random level side.
+    RandomSides {
+        count: u8,
+    },
+    /// A row of monsters of the same type along a level side, `spacing` units apart.
+    /// The side is random if it isn't specified.
+    Side {
+        side: Option<Side>,
+        spacing: f32,
+    },
+}
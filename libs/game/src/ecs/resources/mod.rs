@@ -0,0 +1,68 @@
+use amethyst::ecs::Entity;
+
+#[cfg(not(feature = "client"))]
+use gv_core::net::client_message::ClientMessage;
+#[cfg(feature = "client")]
+use gv_core::net::server_message::ServerMessage;
+use gv_core::{
+    ecs::components::status_effect::StatusEffectApplication, math::Vector2, net::ConnectionNetEvent,
+};
+
+mod level;
+mod monsters;
+mod navigation;
+mod obstacles;
+mod spatial_grid;
+mod spells;
+
+pub use self::{
+    level::{
+        ActiveObjective, Campaign, CampaignLevel, GameMode, LandscapeDefinition, LevelDefinition,
+        LevelObjective, WaveCondition, WaveDefinition, WaveScript, WaveSpawn, WaveSpawnPoint,
+        SURVIVAL_LEVEL,
+    },
+    monsters::{
+        MeleeAttackTiming, MonsterDefinition, MonsterDefinitions, MonsterDrop, MonsterPrefab,
+    },
+    navigation::NavigationGrid,
+    obstacles::{LevelObstacles, Obstacle, ObstacleDefinition},
+    spatial_grid::{SpatialGrid, SpatialGridEntry, SpatialGrids},
+    spells::{SpellDefinition, SpellDefinitions},
+};
+
+/// FNV-1a, unlike `DefaultHasher`, is guaranteed to give the same result for every build.
+fn checksum(files: &[&[u8]]) -> u64 {
+    files
+        .iter()
+        .flat_map(|bytes| bytes.iter())
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3)
+        })
+}
+
+#[cfg(feature = "client")]
+pub struct ConnectionEvents(pub Vec<ConnectionNetEvent<ServerMessage>>);
+#[cfg(not(feature = "client"))]
+pub struct ConnectionEvents(pub Vec<ConnectionNetEvent<ClientMessage>>);
+
+/// Reported by ActionSystem after each run of RollbackScheduler.
+#[derive(Default)]
+pub struct RollbackStats {
+    /// The number of past frames resimulated during the last tick.
+    pub resimulated_frames: u64,
+}
+
+/// Ranged attacks started by monsters during a frame,
+/// MonsterProjectileSubsystem shoots a projectile for each of them.
+#[derive(Default)]
+pub struct RangedAttacksToExecute {
+    pub attacks: Vec<RangedAttack>,
+}
+
+pub struct RangedAttack {
+    pub shooter: Entity,
+    pub position: Vector2,
+    pub target_position: Vector2,
+    pub damage: f32,
+    pub status_effect: Option<StatusEffectApplication>,
+}
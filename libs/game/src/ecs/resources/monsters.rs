@@ -0,0 +1,161 @@
+use amethyst::assets::Prefab;
+use serde::de::Error as _;
+use serde_derive::Deserialize;
+
+use std::collections::HashMap;
+
+use gv_animation_prefabs::{
+    animation_keyframes, AnimationId, GameSpriteAnimationPrefab, MONSTER_BODY,
+};
+use gv_core::{
+    actions::mob::MobAttackType,
+    ecs::components::{
+        damage_history::DamageType, pickup::PickupKind, status_effect::StatusEffectApplication,
+    },
+};
+
+use super::checksum;
+
+const MONSTERS_BYTES: &[u8] = include_bytes!("../../../../../resources/monsters.ron");
+const BEETLE_PREFAB_BYTES: &[u8] = include_bytes!("../../../../../resources/prefabs/beetle.ron");
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MonsterDefinition {
+    pub name: String,
+    pub base_health: f32,
+    pub base_speed: f32,
+    pub base_attack_damage: f32,
+    pub attack_type: MobAttackType,
+    pub collision_radius: f32,
+    /// A monster starts chasing players who come closer than this.
+    pub aggro_radius: f32,
+    pub prefab: MonsterPrefab,
+    /// Is applied to the prefab sprites.
+    #[serde(default = "default_monster_scale")]
+    pub scale: f32,
+    /// The keyframe of the attack animation at which `Melee` attacks hit.
+    #[serde(default)]
+    pub hit_keyframe: Option<usize>,
+    /// Is read from the attack animation of the prefab for `Melee` attacks.
+    #[serde(skip)]
+    pub melee_timing: Option<MeleeAttackTiming>,
+    #[serde(default)]
+    pub drops: Vec<MonsterDrop>,
+    /// Is applied to players on hits of the monster's attacks.
+    #[serde(default)]
+    pub status_effect: Option<StatusEffectApplication>,
+    /// A part of damage of a type which a monster resists, negative values mark weaknesses.
+    #[serde(default)]
+    pub resistances: HashMap<DamageType, f32>,
+}
+
+impl MonsterDefinition {
+    /// Damage is never healing, even if a monster resists more than 100% of it.
+    pub fn damage_multiplier(&self, damage_type: DamageType) -> f32 {
+        let resistance = self.resistances.get(&damage_type).copied().unwrap_or(0.0);
+        (1.0 - resistance).max(0.0)
+    }
+
+    /// Picks a drop with a single roll in `[0, 1)`, chances of the drops are summed up.
+    pub fn roll_drop(&self, roll: f32) -> Option<PickupKind> {
+        let mut chances_sum = 0.0;
+        self.drops.iter().find_map(|drop| {
+            chances_sum += drop.chance;
+            if roll < chances_sum {
+                Some(drop.pickup)
+            } else {
+                None
+            }
+        })
+    }
+}
+
+fn default_monster_scale() -> f32 {
+    1.0
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct MonsterDrop {
+    pub pickup: PickupKind,
+    pub chance: f32,
+}
+
+/// Animation prefabs which monsters can be rendered with. There's only the beetle one so far,
+/// the other monsters reuse it as a placeholder until they get their own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum MonsterPrefab {
+    Beetle,
+}
+
+impl MonsterPrefab {
+    fn bytes(self) -> &'static [u8] {
+        match self {
+            MonsterPrefab::Beetle => BEETLE_PREFAB_BYTES,
+        }
+    }
+}
+
+/// A melee attack lasts as long as its animation does, players may dodge it
+/// before it hits.
+#[derive(Debug, Clone, Copy)]
+pub struct MeleeAttackTiming {
+    /// Seconds from the start of an attack till its hit.
+    pub wind_up: f32,
+    /// Seconds from the hit till the end of the attack.
+    pub recovery: f32,
+}
+
+impl MeleeAttackTiming {
+    fn read(prefab: MonsterPrefab, hit_keyframe: usize) -> Result<Self, ron::de::Error> {
+        let prefab: Prefab<GameSpriteAnimationPrefab> = ron::de::from_bytes(prefab.bytes())?;
+        let keyframes = animation_keyframes(&prefab, MONSTER_BODY, AnimationId::Attack)
+            .filter(|keyframes| keyframes.len() > 1)
+            .ok_or_else(|| ron::de::Error::custom("Expected an attack animation of a monster"))?;
+        let wind_up = *keyframes.get(hit_keyframe).ok_or_else(|| {
+            ron::de::Error::custom(format!("Attack animation has no keyframe {}", hit_keyframe))
+        })?;
+        let last_keyframe = keyframes[keyframes.len() - 1];
+        let keyframe_duration = last_keyframe - keyframes[keyframes.len() - 2];
+        Ok(Self {
+            wind_up,
+            recovery: last_keyframe + keyframe_duration - wind_up,
+        })
+    }
+}
+
+pub struct MonsterDefinitions {
+    pub definitions: HashMap<String, MonsterDefinition>,
+    /// Is compared in the handshake, so that a client and a server simulate the same monsters.
+    pub checksum: u64,
+}
+
+impl MonsterDefinitions {
+    pub fn load() -> Result<Self, ron::de::Error> {
+        let mut definitions: Vec<MonsterDefinition> = ron::de::from_bytes(MONSTERS_BYTES)?;
+        for definition in &mut definitions {
+            if let MobAttackType::Melee = definition.attack_type {
+                let hit_keyframe = definition.hit_keyframe.ok_or_else(|| {
+                    ron::de::Error::custom(format!(
+                        "Expected a hit keyframe for {}",
+                        definition.name
+                    ))
+                })?;
+                definition.melee_timing =
+                    Some(MeleeAttackTiming::read(definition.prefab, hit_keyframe)?);
+            }
+        }
+
+        Ok(Self {
+            definitions: definitions
+                .into_iter()
+                .map(|definition| (definition.name.clone(), definition))
+                .collect(),
+            // Melee attack timings depend on the prefabs as well.
+            checksum: checksum(&[MONSTERS_BYTES, BEETLE_PREFAB_BYTES]),
+        })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&MonsterDefinition> {
+        self.definitions.get(name)
+    }
+}
@@ -0,0 +1,276 @@
+use amethyst::ecs::Entity;
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, VecDeque},
+};
+
+use gv_core::math::Vector2;
+
+use super::LevelObstacles;
+
+const NAVIGATION_CELL_SIZE: f32 = 32.0;
+/// Monsters keep this distance from obstacles when navigating, it's a bit larger
+/// than the Ghoul's radius, so that they don't graze obstacles.
+const NAVIGATION_CLEARANCE: f32 = 16.0;
+const NAVIGATION_STRAIGHT_COST: u32 = 10;
+const NAVIGATION_DIAGONAL_COST: u32 = 14;
+/// How many cells of a flow field a monster looks ahead to cut corners of a path.
+const NAVIGATION_LOOKAHEAD: usize = 8;
+/// Flow fields of fixed destinations (not the chased players), the least recently used
+/// one gets replaced once the limit is reached.
+const MAX_CACHED_FLOW_FIELDS: usize = 64;
+const NEIGHBOUR_OFFSETS: [(i32, i32); 8] = [
+    (1, 0),
+    (0, 1),
+    (-1, 0),
+    (0, -1),
+    (1, 1),
+    (-1, 1),
+    (-1, -1),
+    (1, -1),
+];
+
+/// Walkable grid of a level, which monsters use to find their way around obstacles.
+///
+/// Flow fields depend only on the cell of a destination, so clients and server get
+/// the same paths. Every chased player has their own field, which gets recomputed only
+/// when the player steps into another cell, so resimulating frames during rollback
+/// mostly reuses them.
+pub struct NavigationGrid {
+    origin: Vector2,
+    width: i32,
+    height: i32,
+    blocked: Vec<bool>,
+    player_flow_fields: HashMap<Entity, FlowField>,
+    /// The most recently used flow field is the last one.
+    flow_fields: VecDeque<FlowField>,
+}
+
+/// Path costs from every cell to the destination cell, `u32::MAX` marks unreachable cells.
+#[derive(Default)]
+struct FlowField {
+    destination_index: usize,
+    costs: Vec<u32>,
+}
+
+impl NavigationGrid {
+    pub fn new(
+        dimensions: Vector2,
+        obstacles: &LevelObstacles,
+        blocking_areas: &LevelObstacles,
+    ) -> Self {
+        let origin = -dimensions / 2.0;
+        let width = (dimensions.x / NAVIGATION_CELL_SIZE).ceil() as i32;
+        let height = (dimensions.y / NAVIGATION_CELL_SIZE).ceil() as i32;
+        let mut grid = Self {
+            origin,
+            width,
+            height,
+            blocked: Vec::new(),
+            player_flow_fields: HashMap::new(),
+            flow_fields: VecDeque::new(),
+        };
+        grid.blocked = (0..width * height)
+            .map(|index| {
+                let center = grid.cell_center(grid.cell_of_index(index as usize));
+                obstacles.intersects(center, NAVIGATION_CLEARANCE)
+                    || blocking_areas.intersects(center, NAVIGATION_CLEARANCE)
+            })
+            .collect();
+        grid
+    }
+
+    pub fn is_walkable(&self, position: Vector2) -> bool {
+        self.cell_index(self.cell(position))
+            .map_or(true, |index| !self.blocked[index])
+    }
+
+    /// Returns the position a monster should walk towards to get to the destination.
+    /// Positions outside of the level aren't navigated, monsters walk there in a straight line.
+    /// `chased_player` is the player standing at the destination, if there's one.
+    pub fn next_waypoint(
+        &mut self,
+        position: Vector2,
+        destination: Vector2,
+        chased_player: Option<Entity>,
+    ) -> Vector2 {
+        if self.is_path_clear(position, destination) {
+            return destination;
+        }
+        let (cell_index, destination_index) = match (
+            self.cell_index(self.cell(position)),
+            self.cell_index(self.cell(destination)),
+        ) {
+            (Some(cell_index), Some(destination_index)) if !self.blocked[destination_index] => {
+                (cell_index, destination_index)
+            }
+            _ => return destination,
+        };
+
+        let mut flow_field = match chased_player {
+            Some(player) => self.player_flow_fields.remove(&player).unwrap_or_default(),
+            None => self.take_cached_flow_field(destination_index),
+        };
+        self.update_flow_field(&mut flow_field, destination_index);
+        let waypoint = self.follow_flow_field(
+            &flow_field,
+            position,
+            destination,
+            cell_index,
+            destination_index,
+        );
+        match chased_player {
+            Some(player) => {
+                self.player_flow_fields.insert(player, flow_field);
+            }
+            None => self.flow_fields.push_back(flow_field),
+        }
+        waypoint
+    }
+
+    /// Takes the flow field of the destination out of the cache. If there's none,
+    /// returns the least recently used one to be recomputed, once the cache is full.
+    fn take_cached_flow_field(&mut self, destination_index: usize) -> FlowField {
+        let cached_index = self
+            .flow_fields
+            .iter()
+            .position(|flow_field| flow_field.destination_index == destination_index);
+        if let Some(cached_index) = cached_index {
+            self.flow_fields
+                .remove(cached_index)
+                .expect("Expected a cached flow field")
+        } else if self.flow_fields.len() >= MAX_CACHED_FLOW_FIELDS {
+            self.flow_fields
+                .pop_front()
+                .expect("Expected a cached flow field")
+        } else {
+            FlowField::default()
+        }
+    }
+
+    fn follow_flow_field(
+        &self,
+        flow_field: &FlowField,
+        position: Vector2,
+        destination: Vector2,
+        cell_index: usize,
+        destination_index: usize,
+    ) -> Vector2 {
+        let mut waypoint = None;
+        let mut current_index = cell_index;
+        for _ in 0..NAVIGATION_LOOKAHEAD {
+            let next_index = self
+                .neighbours(current_index)
+                .filter(|(neighbour_index, _)| {
+                    flow_field.costs[*neighbour_index] < flow_field.costs[current_index]
+                })
+                .min_by_key(|(neighbour_index, _)| flow_field.costs[*neighbour_index]);
+            let next_index = match next_index {
+                Some((next_index, _)) => next_index,
+                None => break,
+            };
+            if next_index == destination_index {
+                return destination;
+            }
+            let next_center = self.cell_center(self.cell_of_index(next_index));
+            if waypoint.is_some() && !self.is_path_clear(position, next_center) {
+                break;
+            }
+            waypoint = Some(next_center);
+            current_index = next_index;
+        }
+        waypoint.unwrap_or(destination)
+    }
+
+    /// Dijkstra's algorithm over the grid, starting with the destination cell.
+    /// Does nothing if the field already leads to the destination cell.
+    fn update_flow_field(&self, flow_field: &mut FlowField, destination_index: usize) {
+        if !flow_field.costs.is_empty() && flow_field.destination_index == destination_index {
+            return;
+        }
+        flow_field.destination_index = destination_index;
+        let costs = &mut flow_field.costs;
+        costs.clear();
+        costs.resize(self.blocked.len(), std::u32::MAX);
+        let mut queue = BinaryHeap::new();
+        costs[destination_index] = 0;
+        queue.push(Reverse((0, destination_index)));
+
+        while let Some(Reverse((cost, index))) = queue.pop() {
+            if cost > costs[index] {
+                continue;
+            }
+            for (neighbour_index, step_cost) in self.neighbours(index) {
+                let neighbour_cost = cost + step_cost;
+                if neighbour_cost < costs[neighbour_index] {
+                    costs[neighbour_index] = neighbour_cost;
+                    queue.push(Reverse((neighbour_cost, neighbour_index)));
+                }
+            }
+        }
+    }
+
+    /// Walkable neighbours of a cell with the costs of stepping to them.
+    /// Diagonal steps aren't allowed to cut corners of blocked cells.
+    fn neighbours(&self, index: usize) -> impl Iterator<Item = (usize, u32)> + '_ {
+        let (x, y) = self.cell_of_index(index);
+        NEIGHBOUR_OFFSETS.iter().filter_map(move |(dx, dy)| {
+            let is_walkable = |cell| {
+                self.cell_index(cell)
+                    .map_or(false, |index| !self.blocked[index])
+            };
+            if !is_walkable((x + dx, y + dy)) {
+                return None;
+            }
+            let is_diagonal = *dx != 0 && *dy != 0;
+            if is_diagonal && !(is_walkable((x + dx, y)) && is_walkable((x, y + dy))) {
+                return None;
+            }
+            let cost = if is_diagonal {
+                NAVIGATION_DIAGONAL_COST
+            } else {
+                NAVIGATION_STRAIGHT_COST
+            };
+            self.cell_index((x + dx, y + dy)).map(|index| (index, cost))
+        })
+    }
+
+    /// Checks the cells along a segment, sampling them twice per cell size.
+    fn is_path_clear(&self, start: Vector2, end: Vector2) -> bool {
+        let samples = ((end - start).norm() / (NAVIGATION_CELL_SIZE / 2.0)).ceil() as usize;
+        (1..=samples).all(|sample| {
+            let position = start + (end - start) * (sample as f32 / samples as f32);
+            self.cell_index(self.cell(position))
+                .map_or(true, |index| !self.blocked[index])
+        })
+    }
+
+    fn cell(&self, position: Vector2) -> (i32, i32) {
+        let position = position - self.origin;
+        (
+            (position.x / NAVIGATION_CELL_SIZE).floor() as i32,
+            (position.y / NAVIGATION_CELL_SIZE).floor() as i32,
+        )
+    }
+
+    fn cell_index(&self, (x, y): (i32, i32)) -> Option<usize> {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            None
+        } else {
+            Some((y * self.width + x) as usize)
+        }
+    }
+
+    fn cell_of_index(&self, index: usize) -> (i32, i32) {
+        (index as i32 % self.width, index as i32 / self.width)
+    }
+
+    fn cell_center(&self, (x, y): (i32, i32)) -> Vector2 {
+        self.origin
+            + Vector2::new(
+                (x as f32 + 0.5) * NAVIGATION_CELL_SIZE,
+                (y as f32 + 0.5) * NAVIGATION_CELL_SIZE,
+            )
+    }
+}
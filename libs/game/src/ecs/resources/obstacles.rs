@@ -0,0 +1,165 @@
+use serde_derive::Deserialize;
+
+use gv_core::math::Vector2;
+
+/// Pushing a circle out of an obstacle may push it into another one,
+/// so obstacles get resolved several times.
+const OBSTACLE_RESOLVE_PASSES: usize = 3;
+
+#[derive(Debug, Clone, Deserialize)]
+pub enum ObstacleDefinition {
+    Circle {
+        center: (f32, f32),
+        radius: f32,
+    },
+    /// Points of a simple polygon, in any winding order.
+    Polygon {
+        points: Vec<(f32, f32)>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub enum Obstacle {
+    Circle { center: Vector2, radius: f32 },
+    Polygon { points: Vec<Vector2> },
+}
+
+/// Static obstacles of a level, which block players, monsters and missiles.
+pub struct LevelObstacles(pub Vec<Obstacle>);
+
+impl LevelObstacles {
+    pub fn new(definitions: Vec<ObstacleDefinition>) -> Self {
+        Self(
+            definitions
+                .into_iter()
+                .map(|definition| match definition {
+                    ObstacleDefinition::Circle { center, radius } => Obstacle::Circle {
+                        center: Vector2::new(center.0, center.1),
+                        radius,
+                    },
+                    ObstacleDefinition::Polygon { points } => Obstacle::Polygon {
+                        points: points
+                            .into_iter()
+                            .map(|(x, y)| Vector2::new(x, y))
+                            .collect(),
+                    },
+                })
+                .collect(),
+        )
+    }
+
+    /// Returns the position of a circle moved out of the obstacles it overlaps with.
+    pub fn resolve(&self, position: Vector2, radius: f32) -> Vector2 {
+        let mut position = position;
+        for _ in 0..OBSTACLE_RESOLVE_PASSES {
+            let mut is_resolved = true;
+            for obstacle in &self.0 {
+                if let Some(pushed_position) = obstacle.push_out(position, radius) {
+                    position = pushed_position;
+                    is_resolved = false;
+                }
+            }
+            if is_resolved {
+                break;
+            }
+        }
+        position
+    }
+
+    pub fn intersects(&self, position: Vector2, radius: f32) -> bool {
+        self.0
+            .iter()
+            .any(|obstacle| obstacle.push_out(position, radius).is_some())
+    }
+}
+
+impl Obstacle {
+    /// If a circle overlaps with the obstacle, returns the closest position where it doesn't.
+    pub fn push_out(&self, position: Vector2, radius: f32) -> Option<Vector2> {
+        match self {
+            Obstacle::Circle {
+                center,
+                radius: obstacle_radius,
+            } => {
+                let offset = position - center;
+                let min_distance = radius + obstacle_radius;
+                if offset.norm_squared() >= min_distance * min_distance {
+                    return None;
+                }
+                Some(center + push_direction(offset) * min_distance)
+            }
+            Obstacle::Polygon { points } => {
+                let is_inside = polygon_contains(points, position);
+                let closest = closest_point_on_polygon(points, position);
+                let offset = position - closest;
+                if !is_inside && offset.norm_squared() >= radius * radius {
+                    return None;
+                }
+                let direction = if offset.norm_squared() == 0.0 {
+                    push_direction(position - polygon_centroid(points))
+                } else if is_inside {
+                    -offset.normalize()
+                } else {
+                    offset.normalize()
+                };
+                Some(closest + direction * radius)
+            }
+        }
+    }
+}
+
+fn push_direction(offset: Vector2) -> Vector2 {
+    if offset.norm_squared() == 0.0 {
+        Vector2::new(1.0, 0.0)
+    } else {
+        offset.normalize()
+    }
+}
+
+fn polygon_edges(points: &[Vector2]) -> impl Iterator<Item = (Vector2, Vector2)> + '_ {
+    points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .map(|(start, end)| (*start, *end))
+}
+
+fn polygon_contains(points: &[Vector2], position: Vector2) -> bool {
+    // Even-odd rule: count the edges crossed by a ray going to the right.
+    polygon_edges(points).fold(false, |is_inside, (start, end)| {
+        let crosses = (start.y > position.y) != (end.y > position.y)
+            && position.x
+                < start.x + (position.y - start.y) / (end.y - start.y) * (end.x - start.x);
+        is_inside != crosses
+    })
+}
+
+fn closest_point_on_polygon(points: &[Vector2], position: Vector2) -> Vector2 {
+    polygon_edges(points)
+        .map(|(start, end)| {
+            let edge = end - start;
+            let t = if edge.norm_squared() == 0.0 {
+                0.0
+            } else {
+                ((position - start).dot(&edge) / edge.norm_squared())
+                    .max(0.0)
+                    .min(1.0)
+            };
+            start + edge * t
+        })
+        .fold(None, |closest: Option<Vector2>, point| match closest {
+            Some(closest)
+                if (closest - position).norm_squared() <= (point - position).norm_squared() =>
+            {
+                Some(closest)
+            }
+            _ => Some(point),
+        })
+        .unwrap_or(position)
+}
+
+fn polygon_centroid(points: &[Vector2]) -> Vector2 {
+    points
+        .iter()
+        .fold(Vector2::new(0.0, 0.0), |sum, point| sum + point)
+        / points.len().max(1) as f32
+}
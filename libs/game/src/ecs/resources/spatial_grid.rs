@@ -0,0 +1,180 @@
+use amethyst::ecs::Entity;
+
+use std::collections::HashMap;
+
+use gv_core::math::Vector2;
+
+const MONSTERS_GRID_CELL_SIZE: f32 = 64.0;
+const PLAYERS_GRID_CELL_SIZE: f32 = 256.0;
+
+/// Spatial hashes of monsters and players, rebuilt by SpatialGridsSubsystem for each frame.
+pub struct SpatialGrids {
+    pub monsters: SpatialGrid,
+    pub players: SpatialGrid,
+}
+
+impl Default for SpatialGrids {
+    fn default() -> Self {
+        Self {
+            monsters: SpatialGrid::new(MONSTERS_GRID_CELL_SIZE),
+            players: SpatialGrid::new(PLAYERS_GRID_CELL_SIZE),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SpatialGridEntry {
+    pub entity: Entity,
+    pub position: Vector2,
+    pub radius: f32,
+}
+
+/// Buckets entities into square cells, so that queries check only the entities nearby.
+/// When several entities match a query equally well, the one with the lowest id wins,
+/// which is the same order as iterating over a joined storage.
+pub struct SpatialGrid {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<SpatialGridEntry>>,
+    max_radius: f32,
+    occupied_bounds: Option<((i32, i32), (i32, i32))>,
+}
+
+impl SpatialGrid {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+            max_radius: 0.0,
+            occupied_bounds: None,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        // Cells are kept to reuse their allocations.
+        for cell in self.cells.values_mut() {
+            cell.clear();
+        }
+        self.max_radius = 0.0;
+        self.occupied_bounds = None;
+    }
+
+    pub fn rebuild(&mut self, entries: impl IntoIterator<Item = SpatialGridEntry>) {
+        self.clear();
+        for entry in entries {
+            self.insert(entry);
+        }
+    }
+
+    pub fn insert(&mut self, entry: SpatialGridEntry) {
+        let cell = self.cell(entry.position);
+        self.max_radius = self.max_radius.max(entry.radius);
+        self.occupied_bounds = Some(match self.occupied_bounds {
+            Some(((min_x, min_y), (max_x, max_y))) => (
+                (min_x.min(cell.0), min_y.min(cell.1)),
+                (max_x.max(cell.0), max_y.max(cell.1)),
+            ),
+            None => (cell, cell),
+        });
+        self.cells.entry(cell).or_insert_with(Vec::new).push(entry);
+    }
+
+    /// Finds an entry which is the closest to `position`, regardless of the distance.
+    pub fn closest(&self, position: Vector2) -> Option<SpatialGridEntry> {
+        let ((min_x, min_y), (max_x, max_y)) = self.occupied_bounds?;
+        let center = self.cell(position);
+        let max_ring = (center.0 - min_x)
+            .abs()
+            .max((center.0 - max_x).abs())
+            .max((center.1 - min_y).abs())
+            .max((center.1 - max_y).abs());
+
+        let mut closest: Option<(f32, SpatialGridEntry)> = None;
+        for ring in 0..=max_ring {
+            for cell in ring_cells(center, ring) {
+                for entry in self.cells.get(&cell).into_iter().flatten() {
+                    let distance_squared = (entry.position - position).norm_squared();
+                    let is_closer = closest.map_or(true, |(closest_distance_squared, closest)| {
+                        (distance_squared, entry.entity.id())
+                            < (closest_distance_squared, closest.entity.id())
+                    });
+                    if is_closer {
+                        closest = Some((distance_squared, *entry));
+                    }
+                }
+            }
+
+            // Entries in the next rings can't be closer than this.
+            let next_ring_distance = ring as f32 * self.cell_size;
+            if let Some((closest_distance_squared, _)) = closest {
+                if closest_distance_squared < next_ring_distance * next_ring_distance {
+                    break;
+                }
+            }
+        }
+        closest.map(|(_, entry)| entry)
+    }
+
+    /// Finds an entry that satisfies the predicate, the predicate is called only for entries
+    /// which bounding circles can be closer to `position` than `search_radius`.
+    pub fn find(
+        &self,
+        position: Vector2,
+        search_radius: f32,
+        predicate: impl Fn(&SpatialGridEntry) -> bool,
+    ) -> Option<SpatialGridEntry> {
+        let search_radius = search_radius + self.max_radius;
+        let min_cell = self.cell(position - Vector2::new(search_radius, search_radius));
+        let max_cell = self.cell(position + Vector2::new(search_radius, search_radius));
+
+        let mut found: Option<SpatialGridEntry> = None;
+        for x in min_cell.0..=max_cell.0 {
+            for y in min_cell.1..=max_cell.1 {
+                for entry in self.cells.get(&(x, y)).into_iter().flatten() {
+                    let has_lower_id =
+                        found.map_or(true, |found| entry.entity.id() < found.entity.id());
+                    if has_lower_id && predicate(entry) {
+                        found = Some(*entry);
+                    }
+                }
+            }
+        }
+        found
+    }
+
+    /// Returns entries which bounding circles are closer to `position` than `radius`.
+    pub fn entries_in_radius(&self, position: Vector2, radius: f32) -> Vec<SpatialGridEntry> {
+        let search_radius = radius + self.max_radius;
+        let min_cell = self.cell(position - Vector2::new(search_radius, search_radius));
+        let max_cell = self.cell(position + Vector2::new(search_radius, search_radius));
+
+        let mut entries = Vec::new();
+        for x in min_cell.0..=max_cell.0 {
+            for y in min_cell.1..=max_cell.1 {
+                for entry in self.cells.get(&(x, y)).into_iter().flatten() {
+                    let distance = radius + entry.radius;
+                    if (entry.position - position).norm_squared() < distance * distance {
+                        entries.push(*entry);
+                    }
+                }
+            }
+        }
+        entries
+    }
+
+    fn cell(&self, position: Vector2) -> (i32, i32) {
+        (
+            (position.x / self.cell_size).floor() as i32,
+            (position.y / self.cell_size).floor() as i32,
+        )
+    }
+}
+
+fn ring_cells(center: (i32, i32), ring: i32) -> impl Iterator<Item = (i32, i32)> {
+    let (x, y) = center;
+    (-ring..=ring).flat_map(move |dx| {
+        let is_vertical_edge = dx.abs() == ring;
+        (-ring..=ring)
+            .filter(move |dy| is_vertical_edge || dy.abs() == ring)
+            .map(move |dy| (x + dx, y + dy))
+    })
+}
@@ -0,0 +1,67 @@
+use serde_derive::Deserialize;
+
+use gv_animation_prefabs::AnimationId;
+use gv_core::ecs::components::{
+    damage_history::DamageType,
+    missile::{MissileBehaviour, MissileSpell},
+    status_effect::StatusEffectApplication,
+};
+
+use super::checksum;
+
+const SPELLS_BYTES: &[u8] = include_bytes!("../../../../../resources/spells.ron");
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpellDefinition {
+    pub id: String,
+    pub cooldown_secs: f32,
+    pub damage: f32,
+    pub damage_type: DamageType,
+    /// Mana spent on casting the spell.
+    #[serde(default)]
+    pub cost: f32,
+    /// One of these is played on casting the spell.
+    pub animations: Vec<AnimationId>,
+    /// Either a projectile kind or a nova with its radius.
+    pub projectile: MissileBehaviour,
+    /// Is applied to monsters on hits.
+    #[serde(default)]
+    pub status_effect: Option<StatusEffectApplication>,
+}
+
+impl SpellDefinition {
+    pub fn missile_spell(&self, damage: f32) -> MissileSpell {
+        MissileSpell {
+            behaviour: self.projectile,
+            damage,
+            damage_type: self.damage_type,
+            status_effect: self.status_effect,
+        }
+    }
+}
+
+/// Spells in the order of the slots they are bound to.
+pub struct SpellDefinitions {
+    pub definitions: Vec<SpellDefinition>,
+    /// Is compared in the handshake, so that a client and a server cast the same spells.
+    pub checksum: u64,
+}
+
+impl SpellDefinitions {
+    pub fn load() -> Result<Self, ron::de::Error> {
+        Ok(Self {
+            definitions: ron::de::from_bytes(SPELLS_BYTES)?,
+            checksum: checksum(&[SPELLS_BYTES]),
+        })
+    }
+
+    pub fn get(&self, id: &str) -> Option<&SpellDefinition> {
+        self.definitions
+            .iter()
+            .find(|definition| definition.id == id)
+    }
+
+    pub fn slot(&self, slot: usize) -> Option<&SpellDefinition> {
+        self.definitions.get(slot)
+    }
+}
@@ -3,13 +3,17 @@ use amethyst::{
     shred::{ResourceId, SystemData},
 };
 
-use gv_core::ecs::resources::{net::MultiplayerGameState, GameEngineState, NewGameEngineState};
+use gv_core::ecs::resources::{
+    net::MultiplayerGameState, GameEngineState, GamePause, NewGameEngineState, SimulationTicks,
+};
 
 #[derive(SystemData)]
 pub struct GameStateHelper<'s> {
     game_engine_state: ReadExpect<'s, GameEngineState>,
     new_game_engine_state: ReadExpect<'s, NewGameEngineState>,
     multiplayer_game_state: ReadExpect<'s, MultiplayerGameState>,
+    game_pause: ReadExpect<'s, GamePause>,
+    simulation_ticks: ReadExpect<'s, SimulationTicks>,
 }
 
 impl<'s> GameStateHelper<'s> {
@@ -18,10 +22,13 @@ impl<'s> GameStateHelper<'s> {
         let multiplayer_is_unpaused = !is_playing_multiplayer
             || (!self.multiplayer_game_state.waiting_network
                 && !self.multiplayer_game_state.waiting_for_players);
+        let single_player_is_unpaused = is_playing_multiplayer || !self.game_pause.is_paused;
 
         *self.game_engine_state == GameEngineState::Playing
             && self.new_game_engine_state.0 == GameEngineState::Playing
+            && self.simulation_ticks.is_ticking
             && multiplayer_is_unpaused
+            && single_player_is_unpaused
     }
 
     pub fn is_multiplayer(&self) -> bool {
@@ -41,6 +48,7 @@ impl<'s> GameStateHelper<'s> {
     pub fn multiplayer_is_running(&self) -> bool {
         *self.game_engine_state == GameEngineState::Playing
             && self.new_game_engine_state.0 == GameEngineState::Playing
+            && self.simulation_ticks.is_ticking
             && self.multiplayer_is_unpaused()
     }
 
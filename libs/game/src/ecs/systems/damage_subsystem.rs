@@ -1,12 +1,16 @@
-use amethyst::ecs::{Entities, Join, WriteStorage};
+use amethyst::ecs::{
+    shred::{ResourceId, SystemData},
+    Entities, Join, ReadExpect, ReadStorage, World, WriteExpect, WriteStorage,
+};
 
 use gv_core::{
     ecs::{
         components::{
             damage_history::{DamageHistory, DamageHistoryEntries},
+            status_effect::StatusEffects,
             Dead, EntityNetMetadata, Monster, Player,
         },
-        resources::net::EntityNetMetadataStorage,
+        resources::{net::EntityNetMetadataStorage, stats::RunStats},
         system_data::time::GameTimeService,
     },
     net::NetUpdate,
@@ -15,32 +19,60 @@ use gv_core::{
 
 use crate::{
     ecs::{
+        resources::MonsterDefinitions,
         system_data::GameStateHelper,
-        systems::{OutcomingNetUpdates, WriteExpectCell, WriteStorageCell},
+        systems::{
+            rollback::{RollbackFrame, RollbackStage},
+            FrameUpdate, OutcomingNetUpdates,
+        },
     },
     utils::entities::is_dead,
 };
 
-pub struct DamageSubsystem<'s> {
-    pub game_state_helper: &'s GameStateHelper<'s>,
-    pub game_time_service: &'s GameTimeService<'s>,
-    pub entities: &'s Entities<'s>,
-    pub entity_net_metadata_storage: WriteExpectCell<'s, EntityNetMetadataStorage>,
-    pub entity_net_metadata: WriteStorageCell<'s, EntityNetMetadata>,
-    pub players: WriteStorageCell<'s, Player>,
-    pub monsters: WriteStorageCell<'s, Monster>,
-    pub damage_histories: WriteStorageCell<'s, DamageHistory>,
-    pub dead: WriteStorageCell<'s, Dead>,
+pub struct DamageSubsystem;
+
+impl<'s> RollbackStage<'s> for DamageSubsystem {
+    type SystemData = DamageSystemData<'s>;
+
+    fn prepare_frame(&mut self, mut system_data: Self::SystemData, frame_number: u64) {
+        system_data.reset_damage_entries(frame_number);
+        system_data.run_stats.reset_frame(frame_number);
+    }
+
+    /// Processes damage history and adds updates, if server.
+    fn run_frame(&mut self, mut system_data: Self::SystemData, frame: &mut RollbackFrame) {
+        system_data.process_damage_history(
+            frame.frame_number,
+            damage_histories_updates(frame.updates),
+            frame.outcoming_net_updates,
+        );
+    }
+}
+
+#[derive(SystemData)]
+pub struct DamageSystemData<'s> {
+    game_state_helper: GameStateHelper<'s>,
+    game_time_service: GameTimeService<'s>,
+    monster_definitions: ReadExpect<'s, MonsterDefinitions>,
+    entities: Entities<'s>,
+    #[cfg_attr(not(feature = "client"), allow(dead_code))]
+    entity_net_metadata_storage: ReadExpect<'s, EntityNetMetadataStorage>,
+    entity_net_metadata: ReadStorage<'s, EntityNetMetadata>,
+    players: WriteStorage<'s, Player>,
+    monsters: WriteStorage<'s, Monster>,
+    damage_histories: WriteStorage<'s, DamageHistory>,
+    status_effects: WriteStorage<'s, StatusEffects>,
+    dead: WriteStorage<'s, Dead>,
+    run_stats: WriteExpect<'s, RunStats>,
 }
 
-impl<'s> DamageSubsystem<'s> {
+impl<'s> DamageSystemData<'s> {
     /// We need to reset damage entries when replaying world state in multiplayer.
-    pub fn reset_damage_entries(&self, frame_number: u64) {
+    pub fn reset_damage_entries(&mut self, frame_number: u64) {
         profile_scope!("DamageSubsystem::reset_damage_entries");
-        let entity_net_metadata = self.entity_net_metadata.borrow();
-        let mut damage_histories = self.damage_histories.borrow_mut();
-        for (damage_history, entity) in (&mut *damage_histories, self.entities).join() {
-            let is_spawned = entity_net_metadata
+        for (damage_history, entity) in (&mut self.damage_histories, &self.entities).join() {
+            let is_spawned = self
+                .entity_net_metadata
                 .get(entity)
                 .map_or(true, |entity_net_metadata| {
                     entity_net_metadata.spawned_frame_number <= frame_number
@@ -54,34 +86,39 @@ impl<'s> DamageSubsystem<'s> {
     }
 
     pub fn process_damage_history(
-        &self,
+        &mut self,
         frame_number: u64,
         damage_histories_updates: Option<&Vec<NetUpdate<DamageHistoryEntries>>>,
         outcoming_net_updates: &mut OutcomingNetUpdates,
     ) {
         profile_scope!("DamageSubsystem::process_damage_history");
-        let mut damage_histories = self.damage_histories.borrow_mut();
+        self.fetch_incoming_net_updates(frame_number, damage_histories_updates);
 
-        self.fetch_incoming_net_updates(
-            frame_number,
-            &mut damage_histories,
-            damage_histories_updates,
-        );
-
-        let entity_net_metadata = self.entity_net_metadata.borrow();
-        let mut players = self.players.borrow_mut();
-        let mut monsters = self.monsters.borrow_mut();
-        let mut dead = self.dead.borrow_mut();
+        let DamageSystemData {
+            game_state_helper,
+            game_time_service,
+            monster_definitions,
+            entities,
+            entity_net_metadata,
+            players,
+            monsters,
+            damage_histories,
+            status_effects,
+            dead,
+            run_stats,
+            ..
+        } = self;
+        let is_authoritative = game_state_helper.is_authoritative();
 
-        for (entity, damage_history) in (self.entities, &*damage_histories).join() {
+        for (entity, damage_history) in (&*entities, &*damage_histories).join() {
             if is_dead(entity, &*dead, frame_number) {
                 continue;
             }
 
-            let entity_net_metadata = entity_net_metadata.get(entity);
+            let entity_metadata = entity_net_metadata.get(entity);
 
-            if self.game_state_helper.is_multiplayer() {
-                let is_not_spawned = entity_net_metadata
+            if game_state_helper.is_multiplayer() {
+                let is_not_spawned = entity_metadata
                     .expect("Expected EntityNetMetadata in multiplayer")
                     .spawned_frame_number
                     > frame_number;
@@ -90,25 +127,98 @@ impl<'s> DamageSubsystem<'s> {
                 }
             }
 
-            if self.game_state_helper.is_multiplayer() && self.game_state_helper.is_authoritative()
-            {
+            let damage_entries =
+                if let Some(damage_entries) = damage_history.get_entries(frame_number) {
+                    damage_entries
+                } else {
+                    log::warn!(
+                        "No damage entries for frame {} (entity: {:?})",
+                        frame_number,
+                        entity
+                    );
+                    continue;
+                };
+
+            if game_state_helper.is_multiplayer() && is_authoritative {
                 put_outcoming_net_updates(
-                    *entity_net_metadata.expect("Expected EntityNetMetadata in multiplayer"),
+                    *entity_metadata.expect("Expected EntityNetMetadata in multiplayer"),
                     outcoming_net_updates,
-                    damage_history.get_entries(frame_number).clone(),
+                    with_source_net_ids(damage_entries.clone(), entity_net_metadata),
                 );
             }
 
-            for damage_history_entry in &damage_history.get_entries(frame_number).entries {
+            // Downed players don't take damage, they can only bleed out.
+            let is_downed = players
+                .get(entity)
+                .map_or(false, |player| player.is_downed(frame_number));
+            if is_downed {
+                continue;
+            }
+
+            for damage_history_entry in &damage_entries.entries {
+                let mut damage = damage_history_entry.damage;
+                // Statistics count only the damage that a target had health left for.
+                let mut counted_damage = 0.0;
                 if let Some(player) = players.get_mut(entity) {
-                    player.health -= damage_history_entry.damage;
+                    counted_damage = damage.min(player.health.max(0.0));
+                    player.health -= damage;
                 } else if let Some(monster) = monsters.get_mut(entity) {
-                    monster.health -= damage_history_entry.damage;
+                    // Entries keep the raw damage, so clients apply resistances on their own.
+                    damage *= monster_definitions
+                        .get(&monster.name)
+                        .expect("Expected a MonsterDefinition")
+                        .damage_multiplier(damage_history_entry.damage_type);
+                    counted_damage = damage.min(monster.health.max(0.0));
+                    monster.health -= damage;
                 };
+
+                // Entries are sent to clients, so they apply status effects on their own.
+                if let (Some(application), Some(status_effects)) = (
+                    damage_history_entry.status_effect,
+                    status_effects.get_mut(entity),
+                ) {
+                    let started_frame = frame_number + 1;
+                    let until_frame = started_frame
+                        + game_time_service.frames_in_seconds(application.duration_secs);
+                    status_effects.apply(
+                        application.kind,
+                        started_frame,
+                        until_frame,
+                        damage_history_entry.source,
+                    );
+                }
+
+                if is_authoritative {
+                    if players.contains(entity) {
+                        run_stats.player_mut(frame_number, entity).damage_taken += counted_damage;
+                    }
+                    if let Some(source) = damage_history_entry
+                        .source
+                        .filter(|source| players.contains(*source))
+                    {
+                        run_stats.player_mut(frame_number, source).damage_dealt += counted_damage;
+                    }
+                }
             }
         }
 
-        for entity in (self.entities).join() {
+        for entity in (&*entities).join() {
+            // Players get downed in multiplayer instead, `ReviveSubsystem` decides whether
+            // they die.
+            if game_state_helper.is_multiplayer() {
+                if let Some(player) = players.get_mut(entity) {
+                    let is_downed_now = player.health < 0.001
+                        && player.downed_since_frame.is_none()
+                        && !is_dead(entity, &*dead, frame_number);
+                    if is_downed_now && is_authoritative {
+                        player.health = 0.0;
+                        player.downed_since_frame = Some(frame_number + 1);
+                        player.revive_frames = 0;
+                    }
+                    continue;
+                }
+            }
+
             let health = {
                 if let Some(player) = players.get_mut(entity) {
                     &mut player.health
@@ -126,9 +236,27 @@ impl<'s> DamageSubsystem<'s> {
                     *health = 0.0;
                     let dead_since_frame = frame_number + 1;
                     let frame_acknowledged =
-                        dead_since_frame.max(self.game_time_service.game_frame_number());
+                        dead_since_frame.max(game_time_service.game_frame_number());
                     dead.insert(entity, Dead::new(dead_since_frame, frame_acknowledged))
                         .expect("Expected to insert Dead component");
+
+                    if is_authoritative && monsters.contains(entity) {
+                        // The last player to deal damage gets credited with the kill.
+                        let killer = damage_histories
+                            .get(entity)
+                            .and_then(|damage_history| damage_history.get_entries(frame_number))
+                            .and_then(|damage_entries| {
+                                damage_entries
+                                    .entries
+                                    .iter()
+                                    .rev()
+                                    .filter_map(|entry| entry.source)
+                                    .find(|source| players.contains(*source))
+                            });
+                        if let Some(killer) = killer {
+                            run_stats.player_mut(frame_number, killer).kills += 1;
+                        }
+                    }
                 }
             } else {
                 // If an entity has Dead component for whatever reason, but it has positive health,
@@ -147,12 +275,11 @@ impl<'s> DamageSubsystem<'s> {
 
     #[cfg(feature = "client")]
     fn fetch_incoming_net_updates(
-        &self,
+        &mut self,
         frame_number: u64,
-        damage_histories: &mut WriteStorage<DamageHistory>,
         incoming_net_updates: Option<&Vec<NetUpdate<DamageHistoryEntries>>>,
     ) {
-        let entity_net_metadata_storage = self.entity_net_metadata_storage.borrow();
+        let entity_net_metadata_storage = &self.entity_net_metadata_storage;
         let incoming_net_updates =
             incoming_net_updates.expect("Expected net updates on client side");
         for net_update in incoming_net_updates {
@@ -166,10 +293,17 @@ impl<'s> DamageSubsystem<'s> {
                 return;
             }
             let entity = entity.unwrap();
-            let damage_history = damage_histories
+            let damage_history = self
+                .damage_histories
                 .get_mut(entity)
                 .expect("Expected DamageHistory component");
-            for damage_history_entry in net_update.data.entries.clone() {
+            for mut damage_history_entry in net_update.data.entries.clone() {
+                damage_history_entry.source =
+                    damage_history_entry
+                        .source_net_id
+                        .and_then(|source_net_id| {
+                            entity_net_metadata_storage.get_entity(source_net_id)
+                        });
                 damage_history.add_entry(frame_number, damage_history_entry);
             }
         }
@@ -177,14 +311,27 @@ impl<'s> DamageSubsystem<'s> {
 
     #[cfg(not(feature = "client"))]
     fn fetch_incoming_net_updates(
-        &self,
+        &mut self,
         _frame_number: u64,
-        _damage_histories: &mut WriteStorage<DamageHistory>,
         _incoming_net_updates: Option<&Vec<NetUpdate<DamageHistoryEntries>>>,
     ) {
     }
 }
 
+/// Entity ids differ on clients and server, so we send net ids of damage sources instead.
+fn with_source_net_ids(
+    mut damage_history_entries: DamageHistoryEntries,
+    entity_net_metadata: &ReadStorage<EntityNetMetadata>,
+) -> DamageHistoryEntries {
+    for entry in &mut damage_history_entries.entries {
+        entry.source_net_id = entry
+            .source
+            .and_then(|source| entity_net_metadata.get(source))
+            .map(|entity_net_metadata| entity_net_metadata.id);
+    }
+    damage_history_entries
+}
+
 #[cfg(feature = "client")]
 fn put_outcoming_net_updates(
     _entity_net_metadata: EntityNetMetadata,
@@ -213,3 +360,17 @@ fn put_outcoming_net_updates(
             })
     }
 }
+
+#[cfg(feature = "client")]
+fn damage_histories_updates(
+    frame_updates: &FrameUpdate,
+) -> Option<&Vec<NetUpdate<DamageHistoryEntries>>> {
+    Some(&frame_updates.damage_histories_updates)
+}
+
+#[cfg(not(feature = "client"))]
+fn damage_histories_updates(
+    _frame_updates: &FrameUpdate,
+) -> Option<&Vec<NetUpdate<DamageHistoryEntries>>> {
+    None
+}
@@ -0,0 +1,70 @@
+use amethyst::ecs::{Entities, Join, ReadStorage, System, WriteExpect, WriteStorage};
+
+use gv_core::ecs::{
+    components::{damage_history::DamageHistory, Dead, EntityNetMetadata, Player},
+    resources::{net::EntityNetMetadataStorage, stats::RunStats},
+    system_data::time::GameTimeService,
+};
+
+use crate::ecs::system_data::GameStateHelper;
+
+/// Deletes dead entities that can't be brought back by a rollback anymore,
+/// trims damage histories to the rollback window and confirms the run statistics outside of it.
+pub struct DeadCleanupSystem;
+
+impl<'s> System<'s> for DeadCleanupSystem {
+    type SystemData = (
+        GameStateHelper<'s>,
+        GameTimeService<'s>,
+        Entities<'s>,
+        WriteExpect<'s, EntityNetMetadataStorage>,
+        WriteExpect<'s, RunStats>,
+        ReadStorage<'s, Dead>,
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, EntityNetMetadata>,
+        WriteStorage<'s, DamageHistory>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_state_helper,
+            game_time_service,
+            entities,
+            mut entity_net_metadata_storage,
+            mut run_stats,
+            dead,
+            players,
+            entity_net_metadata,
+            mut damage_histories,
+        ): Self::SystemData,
+    ) {
+        if !game_state_helper.is_running() {
+            return;
+        }
+
+        let frame_number = game_time_service.game_frame_number();
+        let saved_world_states_limit = game_time_service.saved_world_states_limit();
+        let oldest_rollback_frame = frame_number.saturating_sub(saved_world_states_limit);
+
+        // Players stay as they are, a game is over once they all die anyway.
+        for (entity, dead, _) in (&entities, &dead, !&players).join() {
+            let dead_since_frame = dead.dead_since_frame.max(dead.frame_acknowledged);
+            if dead_since_frame >= oldest_rollback_frame {
+                continue;
+            }
+
+            if let Some(entity_net_metadata) = entity_net_metadata.get(entity) {
+                entity_net_metadata_storage.release_net_id(entity_net_metadata.id);
+            }
+            entities
+                .delete(entity)
+                .expect("Expected to delete a dead entity");
+        }
+
+        for damage_history in (&mut damage_histories).join() {
+            damage_history.forget_old_entries(frame_number, saved_world_states_limit);
+        }
+        run_stats.confirm_before(oldest_rollback_frame);
+    }
+}
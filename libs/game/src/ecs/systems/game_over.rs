@@ -0,0 +1,102 @@
+use amethyst::ecs::{Entities, Join, ReadExpect, ReadStorage, System, WriteExpect};
+
+use gv_core::ecs::{
+    components::{Dead, EntityNetMetadata, Player},
+    resources::{
+        net::MultiplayerGameState,
+        stats::{PlayerRunSummary, RunStats, RunSummary},
+        GameLevelState,
+    },
+    system_data::time::GameTimeService,
+};
+
+use crate::{ecs::system_data::GameStateHelper, utils::entities::is_dead};
+
+/// Ends a game once all players are dead or downed and sums up its statistics.
+pub struct GameOverSystem;
+
+impl<'s> System<'s> for GameOverSystem {
+    type SystemData = (
+        GameStateHelper<'s>,
+        GameTimeService<'s>,
+        Entities<'s>,
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, Dead>,
+        ReadStorage<'s, EntityNetMetadata>,
+        ReadExpect<'s, MultiplayerGameState>,
+        ReadExpect<'s, RunStats>,
+        WriteExpect<'s, GameLevelState>,
+        WriteExpect<'s, RunSummary>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_state_helper,
+            game_time_service,
+            entities,
+            players,
+            dead,
+            entity_net_metadata,
+            multiplayer_game_state,
+            run_stats,
+            mut game_level_state,
+            mut run_summary,
+        ): Self::SystemData,
+    ) {
+        if !game_state_helper.is_running()
+            || !game_state_helper.is_authoritative()
+            || game_level_state.is_over
+        {
+            return;
+        }
+        let frame_number = game_time_service.game_frame_number();
+
+        let mut player_entities = Vec::new();
+        let mut are_all_players_down = true;
+        for (player_entity, player) in (&entities, &players).join() {
+            are_all_players_down &=
+                is_dead(player_entity, &dead, frame_number) || player.is_downed(frame_number);
+            player_entities.push(player_entity);
+        }
+        if player_entities.is_empty() || !are_all_players_down {
+            return;
+        }
+
+        log::info!(
+            "All the players are down, the game is over (frame {})",
+            frame_number
+        );
+        run_summary.waves_reached = game_level_state.wave.map_or(0, |wave| wave + 1);
+        run_summary.players = player_entities
+            .into_iter()
+            .enumerate()
+            .map(|(i, player)| {
+                let entity_net_id = entity_net_metadata
+                    .get(player)
+                    .map(|entity_net_metadata| entity_net_metadata.id);
+                let nickname = entity_net_id
+                    .and_then(|entity_net_id| {
+                        multiplayer_game_state
+                            .players
+                            .iter()
+                            .find(|room_player| room_player.entity_net_id == entity_net_id)
+                    })
+                    .map_or_else(
+                        || format!("Player {}", i + 1),
+                        |room_player| room_player.nickname.clone(),
+                    );
+                let died_at_frame = dead
+                    .get(player)
+                    .map_or(frame_number, |dead| dead.dead_since_frame);
+                PlayerRunSummary {
+                    entity_net_id,
+                    nickname,
+                    stats: run_stats.player(player),
+                    time_survived_secs: game_time_service.seconds_between_frames(died_at_frame, 0),
+                }
+            })
+            .collect();
+        game_level_state.is_over = true;
+    }
+}
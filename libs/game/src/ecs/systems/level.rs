@@ -1,10 +1,10 @@
-use amethyst::ecs::{System, WriteExpect};
-
-use std::time::Duration;
+use amethyst::ecs::{Entities, Join, ReadExpect, ReadStorage, System, WriteExpect};
+use rand::distributions::{Distribution, WeightedIndex};
 
 use gv_core::{
     actions::monster_spawn::{SpawnAction, SpawnActions, SpawnType},
     ecs::{
+        components::{Dead, Monster},
         resources::{net::EntityNetMetadataStorage, world::FramedUpdates, GameLevelState},
         system_data::time::GameTimeService,
     },
@@ -12,14 +12,17 @@ use gv_core::{
 };
 
 use crate::{
-    ecs::system_data::GameStateHelper,
-    utils::world::{random_spawn_position, spawning_side},
+    ecs::{
+        resources::{WaveCondition, WaveScript, WaveSpawn, WaveSpawnPoint},
+        system_data::GameStateHelper,
+    },
+    utils::{
+        entities::is_dead,
+        world::{random_spawn_position, spawning_side},
+    },
 };
 
-const SECS_PER_LEVEL: u64 = 30;
-const MIN_BORDERLINE_INTERVAL_SECS: f32 = 30.0;
-const MAX_BORDERLINE_INTERVAL_SECS: f32 = 5.0;
-
+/// Runs the wave script of a level.
 #[derive(Default)]
 pub struct LevelSystem;
 
@@ -27,6 +30,10 @@ impl<'s> System<'s> for LevelSystem {
     type SystemData = (
         GameStateHelper<'s>,
         GameTimeService<'s>,
+        Entities<'s>,
+        ReadStorage<'s, Monster>,
+        ReadStorage<'s, Dead>,
+        ReadExpect<'s, WaveScript>,
         WriteExpect<'s, GameLevelState>,
         WriteExpect<'s, FramedUpdates<SpawnActions>>,
         WriteExpect<'s, EntityNetMetadataStorage>,
@@ -37,6 +44,10 @@ impl<'s> System<'s> for LevelSystem {
         (
             game_state_helper,
             game_time_service,
+            entities,
+            monsters,
+            dead,
+            wave_script,
             mut game_level_state,
             mut spawn_actions,
             mut entity_net_metadata_storage,
@@ -45,49 +56,103 @@ impl<'s> System<'s> for LevelSystem {
         if !game_state_helper.is_running() || !game_state_helper.is_authoritative() {
             return;
         }
-        spawn_actions.reserve_updates(game_time_service.game_frame_number());
+        let frame_number = game_time_service.game_frame_number();
+        spawn_actions.reserve_updates(frame_number);
         let spawn_actions = spawn_actions
-            .update_frame(game_time_service.game_frame_number())
-            .unwrap_or_else(|| {
-                panic!(
-                    "Expected SpawnActions for frame {}",
-                    game_time_service.game_frame_number()
-                )
-            });
-
-        let now = game_time_service.level_duration();
-
-        if now - game_level_state.spawn_level_started > Duration::from_secs(SECS_PER_LEVEL) {
-            game_level_state.spawn_level += 1;
-            game_level_state.spawn_level_started = now;
+            .update_frame(frame_number)
+            .unwrap_or_else(|| panic!("Expected SpawnActions for frame {}", frame_number));
+
+        let frames_since_wave_started = frame_number - game_level_state.wave_started_frame;
+        let next_wave = game_level_state.wave.map_or(0, |wave| wave + 1);
+        if let Some(wave) = wave_script.0.get(next_wave) {
+            let is_time_up =
+                |secs: f32| frames_since_wave_started >= game_time_service.frames_in_seconds(secs);
+            let are_all_dead = || {
+                wave_script.is_running_wave_spawned(&game_level_state)
+                    && (&entities, &monsters)
+                        .join()
+                        .all(|(monster, _)| is_dead(monster, &dead, frame_number))
+            };
+            let should_start = match wave.start {
+                WaveCondition::AfterSecs(secs) => is_time_up(secs),
+                WaveCondition::AllDead => are_all_dead(),
+                WaveCondition::AllDeadOrAfterSecs(secs) => is_time_up(secs) || are_all_dead(),
+            };
+            if should_start {
+                log::debug!("Starting wave {} at frame {}", next_wave, frame_number);
+                game_level_state.wave = Some(next_wave);
+                game_level_state.wave_started_frame = frame_number;
+                game_level_state.wave_spawns_done = vec![0; wave.spawns.len()];
+            }
         }
 
-        if game_time_service.game_frame_number() == 10 {
-            spawn_actions.spawn_actions.push(SpawnAction {
-                spawn_type: SpawnType::Single {
-                    entity_net_id: Some(entity_net_metadata_storage.reserve_ids(1).start),
-                    position: Vector2::new(0.0, 300.0),
-                },
-            });
+        let wave = match game_level_state.wave {
+            Some(wave) => &wave_script.0[wave],
+            None => return,
+        };
+        let frames_since_wave_started = frame_number - game_level_state.wave_started_frame;
+        let mut wave_spawns_done = std::mem::take(&mut game_level_state.wave_spawns_done);
+        for (wave_spawn, spawns_done) in wave.spawns.iter().zip(wave_spawns_done.iter_mut()) {
+            let is_due = wave_spawn
+                .next_spawn_secs(*spawns_done)
+                .map_or(false, |secs| {
+                    frames_since_wave_started >= game_time_service.frames_in_seconds(secs)
+                });
+            if is_due {
+                *spawns_done += 1;
+                spawn(
+                    wave_spawn,
+                    &game_level_state,
+                    game_state_helper.is_multiplayer(),
+                    &mut *entity_net_metadata_storage,
+                    &mut spawn_actions.spawn_actions,
+                );
+            }
         }
+        game_level_state.wave_spawns_done = wave_spawns_done;
+    }
+}
 
-        let borderline_spawn_interval = MIN_BORDERLINE_INTERVAL_SECS
-            - (game_level_state.spawn_level as f32 / 7.0).atan() / std::f32::consts::PI
-                * 2.0
-                * (MAX_BORDERLINE_INTERVAL_SECS - MIN_BORDERLINE_INTERVAL_SECS);
-        let borderline_spawn_interval =
-            Duration::from_millis((borderline_spawn_interval * 1000.0).round() as u64);
-        if now - game_level_state.last_borderline_spawn > borderline_spawn_interval {
-            game_level_state.last_borderline_spawn = now;
-
-            let side = rand::random();
-
-            let spawn_margin = 50.0;
-            let (side_start, side_end, _) = spawning_side(side, &game_level_state);
-            let d = (side_start - side_end) / spawn_margin;
-            let monsters_to_spawn = num::Float::max(d.x.abs(), d.y.abs()).round() as usize;
-
-            let entity_net_id_range = if game_state_helper.is_multiplayer() {
+fn spawn(
+    wave_spawn: &WaveSpawn,
+    game_level_state: &GameLevelState,
+    is_multiplayer: bool,
+    entity_net_metadata_storage: &mut EntityNetMetadataStorage,
+    spawn_actions: &mut Vec<SpawnAction>,
+) {
+    match wave_spawn.at {
+        WaveSpawnPoint::Point { position, count } => {
+            log::trace!("Spawning {} monster(s) at {:?}", count, position);
+            for _ in 0..count {
+                spawn_actions.push(SpawnAction {
+                    monster: pick_monster(wave_spawn),
+                    spawn_type: SpawnType::Single {
+                        entity_net_id: Some(entity_net_metadata_storage.reserve_ids(1).start),
+                        position: Vector2::new(position.0, position.1),
+                    },
+                });
+            }
+        }
+        WaveSpawnPoint::RandomSides { count } => {
+            log::trace!("Spawning {} monster(s) (SpawnType::Single)", count);
+            for _ in 0..count {
+                spawn_actions.push(SpawnAction {
+                    monster: pick_monster(wave_spawn),
+                    spawn_type: SpawnType::Single {
+                        entity_net_id: Some(entity_net_metadata_storage.reserve_ids(1).start),
+                        position: random_spawn_position(game_level_state),
+                    },
+                });
+            }
+        }
+        WaveSpawnPoint::Side { side, spacing } => {
+            let side = side.unwrap_or_else(rand::random);
+            let (side_start, side_end, _) = spawning_side(side, game_level_state);
+            let d = (side_start - side_end) / spacing;
+            let monsters_to_spawn =
+                num::Float::max(d.x.abs(), d.y.abs()).round().min(255.0) as usize;
+
+            let entity_net_id_range = if is_multiplayer {
                 Some(entity_net_metadata_storage.reserve_ids(monsters_to_spawn))
             } else {
                 None
@@ -97,7 +162,8 @@ impl<'s> System<'s> for LevelSystem {
                 "Spawning {} monster(s) (SpawnType::Borderline)",
                 monsters_to_spawn
             );
-            spawn_actions.spawn_actions.push(SpawnAction {
+            spawn_actions.push(SpawnAction {
+                monster: pick_monster(wave_spawn),
                 spawn_type: SpawnType::Borderline {
                     count: monsters_to_spawn as u8,
                     entity_net_id_range,
@@ -105,23 +171,13 @@ impl<'s> System<'s> for LevelSystem {
                 },
             });
         }
-
-        let random_spawn_interval = Duration::from_secs(1);
-        let monsters_to_spawn = game_level_state.spawn_level.min(255) as u8;
-        if now - game_level_state.last_random_spawn > random_spawn_interval {
-            game_level_state.last_random_spawn = now;
-            log::trace!(
-                "Spawning {} monster(s) (SpawnType::Single)",
-                monsters_to_spawn
-            );
-            for _ in 0..monsters_to_spawn {
-                spawn_actions.spawn_actions.push(SpawnAction {
-                    spawn_type: SpawnType::Single {
-                        entity_net_id: Some(entity_net_metadata_storage.reserve_ids(1).start),
-                        position: random_spawn_position(&game_level_state),
-                    },
-                });
-            }
-        }
     }
 }
+
+fn pick_monster(wave_spawn: &WaveSpawn) -> String {
+    let weights = WeightedIndex::new(wave_spawn.monsters.iter().map(|(_, weight)| *weight))
+        .expect("Expected monster weights to be validated on loading a level");
+    wave_spawn.monsters[weights.sample(&mut rand::thread_rng())]
+        .0
+        .clone()
+}
@@ -1,7 +1,10 @@
 use amethyst::ecs::{Entities, Join, ReadStorage, System};
 
 use gv_core::ecs::{
-    components::{missile::Missile, Dead},
+    components::{
+        missile::{Missile, MonsterProjectile},
+        Dead,
+    },
     system_data::time::GameTimeService,
 };
 
@@ -18,11 +21,12 @@ impl<'s> System<'s> for MissileDyingSystem {
         Entities<'s>,
         ReadStorage<'s, Dead>,
         ReadStorage<'s, Missile>,
+        ReadStorage<'s, MonsterProjectile>,
     );
 
     fn run(
         &mut self,
-        (game_state_helper, game_time_service, entities, dead, missiles): Self::SystemData,
+        (game_state_helper, game_time_service, entities, dead, missiles, monster_projectiles): Self::SystemData,
     ) {
         if !game_state_helper.is_running() {
             return;
@@ -37,5 +41,15 @@ impl<'s> System<'s> for MissileDyingSystem {
                     .expect("Expected to delete a Missile");
             }
         }
+
+        for (projectile_entity, dead, _) in (&entities, &dead, &monster_projectiles).join() {
+            let to_be_deleted =
+                game_time_service.seconds_to_frame(dead.dead_since_frame) > MISSILE_TTL_SECS;
+            if to_be_deleted {
+                entities
+                    .delete(projectile_entity)
+                    .expect("Expected to delete a MonsterProjectile");
+            }
+        }
     }
 }
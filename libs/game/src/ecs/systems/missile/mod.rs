@@ -6,7 +6,7 @@ pub use self::{
     dying::{MissileDyingSystem, MISSILE_TTL_SECS},
     physics_subsystem::{
         MissilePhysicsSubsystem, MISSILE_LIFESPAN_SECS, MISSILE_MAX_SPEED, MISSILE_MIN_SPEED,
-        MISSILE_TIME_TO_FADE,
+        MISSILE_TIME_TO_FADE, NOVA_DURATION_SECS,
     },
     spawner_subsystem::{MissileFactory, MissileSpawnerSubsystem},
 };
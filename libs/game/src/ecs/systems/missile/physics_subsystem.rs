@@ -1,21 +1,31 @@
 use amethyst::{
     core::math::{clamp, Rotation2},
-    ecs::{Entities, Join, ReadExpect},
+    ecs::{
+        shred::{ResourceId, SystemData},
+        Entities, Entity, Join, ReadExpect, ReadStorage, World, WriteExpect, WriteStorage,
+    },
 };
 use gv_core::profile_scope;
 
-use gv_core::ecs::{
-    components::{
-        damage_history::{DamageHistory, DamageHistoryEntry},
-        missile::{Missile, MissileTarget},
-        Dead, Monster, WorldPosition,
+use gv_core::{
+    ecs::{
+        components::{
+            damage_history::{DamageCause, DamageHistory, DamageHistoryEntry},
+            missile::{Missile, MissileBehaviour, MissileTarget},
+            Dead, Monster, WorldPosition,
+        },
+        resources::{stats::RunStats, GameLevelState},
+        system_data::time::GameTimeService,
     },
-    resources::GameLevelState,
-    system_data::time::GameTimeService,
+    math::Vector2,
 };
 
 use crate::{
-    ecs::{system_data::GameStateHelper, systems::WriteStorageCell},
+    ecs::{
+        resources::{LevelObstacles, SpatialGrid, SpatialGrids},
+        system_data::GameStateHelper,
+        systems::rollback::{RollbackFrame, RollbackStage},
+    },
     utils::{
         entities::{is_dead, missile_energy},
         world::{closest_monster, find_first_hit_monster, random_scene_position},
@@ -26,53 +36,99 @@ pub const MISSILE_MAX_SPEED: f32 = 300.0;
 pub const MISSILE_MIN_SPEED: f32 = 80.0;
 pub const MISSILE_TIME_TO_FADE: f32 = 0.5;
 pub const MISSILE_LIFESPAN_SECS: f32 = 5.0;
+/// A nova ring expands during this time and disappears.
+pub const NOVA_DURATION_SECS: f32 = 0.4;
+
+const TIME_TO_ACCELERATE_SECS: f32 = 2.0;
+/// Units per second squared.
+const MISSILE_ACCELERATION: f32 = (MISSILE_MAX_SPEED - MISSILE_MIN_SPEED) / TIME_TO_ACCELERATE_SECS;
+const TIME_TO_ROTATE_SECS: f32 = 1.0;
+/// Radians per second.
+const MAX_ROTATION_SPEED: f32 = std::f32::consts::PI / TIME_TO_ROTATE_SECS;
+
+/// Moves missiles and lets them hit monsters.
+pub struct MissilePhysicsSubsystem;
 
-const MS_PER_FRAME: f32 = 1000.0 / 60.0;
-
-const TIME_TO_ACCELERATE: f32 = 2000.0;
-const MISSILE_ACCELERATION: f32 =
-    (MISSILE_MAX_SPEED - MISSILE_MIN_SPEED) / TIME_TO_ACCELERATE * MS_PER_FRAME;
-const TIME_TO_ROTATE: f32 = 1000.0;
-const MAX_ROTATION: f32 = std::f32::consts::PI / TIME_TO_ROTATE * MS_PER_FRAME;
-
-pub struct MissilePhysicsSubsystem<'s> {
-    pub game_time_service: &'s GameTimeService<'s>,
-    pub game_state_helper: &'s GameStateHelper<'s>,
-    pub game_level_state: &'s ReadExpect<'s, GameLevelState>,
-    pub entities: &'s Entities<'s>,
-    pub monsters: WriteStorageCell<'s, Monster>,
-    pub missiles: WriteStorageCell<'s, Missile>,
-    pub dead: WriteStorageCell<'s, Dead>,
-    pub damage_histories: WriteStorageCell<'s, DamageHistory>,
-    pub world_positions: WriteStorageCell<'s, WorldPosition>,
+impl<'s> RollbackStage<'s> for MissilePhysicsSubsystem {
+    type SystemData = MissilePhysicsSystemData<'s>;
+
+    fn run_frame(&mut self, mut system_data: Self::SystemData, frame: &mut RollbackFrame) {
+        system_data.process_physics(frame.frame_number);
+    }
 }
 
-impl<'s> MissilePhysicsSubsystem<'s> {
-    pub fn process_physics(&self, frame_number: u64) {
+#[derive(SystemData)]
+pub struct MissilePhysicsSystemData<'s> {
+    game_time_service: GameTimeService<'s>,
+    game_state_helper: GameStateHelper<'s>,
+    game_level_state: ReadExpect<'s, GameLevelState>,
+    level_obstacles: ReadExpect<'s, LevelObstacles>,
+    entities: Entities<'s>,
+    monsters: ReadStorage<'s, Monster>,
+    missiles: WriteStorage<'s, Missile>,
+    dead: WriteStorage<'s, Dead>,
+    damage_histories: WriteStorage<'s, DamageHistory>,
+    world_positions: WriteStorage<'s, WorldPosition>,
+    spatial_grids: ReadExpect<'s, SpatialGrids>,
+    run_stats: WriteExpect<'s, RunStats>,
+}
+
+impl<'s> MissilePhysicsSystemData<'s> {
+    pub fn process_physics(&mut self, frame_number: u64) {
         profile_scope!("MissilePhysicsSubsystem::process_physics");
-        let monsters = self.monsters.borrow();
-        let mut missiles = self.missiles.borrow_mut();
-        let mut dead = self.dead.borrow_mut();
-        let mut damage_histories = self.damage_histories.borrow_mut();
-        let mut world_positions = self.world_positions.borrow_mut();
+        let MissilePhysicsSystemData {
+            game_time_service,
+            game_state_helper,
+            game_level_state,
+            level_obstacles,
+            entities,
+            monsters,
+            missiles,
+            dead,
+            damage_histories,
+            world_positions,
+            spatial_grids,
+            run_stats,
+        } = self;
+        let is_authoritative = game_state_helper.is_authoritative();
+        let fixed_seconds = game_time_service.engine_time().fixed_seconds();
+        let max_rotation = MAX_ROTATION_SPEED * fixed_seconds;
+        let acceleration = MISSILE_ACCELERATION * fixed_seconds;
 
-        for (missile_entity, mut missile) in (self.entities, &mut *missiles).join() {
+        for (missile_entity, missile) in (&*entities, &mut *missiles).join() {
             let is_dead = is_dead(missile_entity, &*dead, frame_number);
             if missile.frame_spawned > frame_number || is_dead {
                 continue;
             }
+            // Counting casts here rather than in the spawner, as resimulated frames reuse missiles.
+            if missile.frame_spawned == frame_number && is_authoritative {
+                run_stats
+                    .player_mut(frame_number, missile.caster)
+                    .missiles_cast += 1;
+            }
 
-            let missile_energy =
-                missile_energy(&missile, is_dead, &self.game_time_service, frame_number);
-            if missile_energy == 0.0 {
-                let dead_since_frame = frame_number + 1;
-                let frame_acknowledged =
-                    dead_since_frame.max(self.game_time_service.game_frame_number());
-                dead.insert(
+            if let MissileBehaviour::Nova { .. } = missile.spell.behaviour {
+                let missile_position = **world_positions
+                    .get(missile_entity)
+                    .expect("Expected a missile");
+                process_nova(
+                    game_time_service,
+                    is_authoritative,
+                    frame_number,
                     missile_entity,
-                    Dead::new(dead_since_frame, frame_acknowledged),
-                )
-                .expect("Expected to insert a Dead component");
+                    missile,
+                    missile_position,
+                    &spatial_grids.monsters,
+                    damage_histories,
+                    run_stats,
+                    dead,
+                );
+                continue;
+            }
+
+            let missile_energy = missile_energy(missile, is_dead, game_time_service, frame_number);
+            if missile_energy == 0.0 {
+                kill_missile(game_time_service, frame_number, missile_entity, dead);
                 continue;
             }
 
@@ -80,110 +136,210 @@ impl<'s> MissilePhysicsSubsystem<'s> {
                 .get(missile_entity)
                 .expect("Expected a missile");
 
-            let (destination, new_target) = match missile.target {
-                MissileTarget::Target(target) => {
-                    if let Some(target_position) = world_positions.get(target) {
-                        (**target_position, None)
-                    } else if let Some((target, target_position)) = closest_monster(
-                        missile_position,
-                        &world_positions,
-                        &self.entities,
-                        &monsters,
-                        &*dead,
-                        frame_number,
-                    ) {
-                        (target_position, Some(MissileTarget::Target(target)))
-                    } else {
-                        let target_position = random_scene_position(self.game_level_state);
-                        (
-                            target_position,
-                            Some(MissileTarget::Destination(target_position)),
-                        )
-                    }
-                }
-                MissileTarget::Destination(destination) => {
-                    if let Some((target, target_position)) = closest_monster(
-                        missile_position,
-                        &world_positions,
-                        &self.entities,
-                        &monsters,
-                        &*dead,
-                        frame_number,
-                    ) {
-                        (target_position, Some(MissileTarget::Target(target)))
-                    } else if (destination - missile_position).norm_squared()
-                        > missile.velocity.norm_squared()
-                    {
-                        (destination, None)
-                    } else {
-                        let target_position = random_scene_position(&*self.game_level_state);
-                        (
-                            target_position,
-                            Some(MissileTarget::Destination(target_position)),
-                        )
-                    }
-                }
-            };
-            if let Some(new_target) = new_target {
-                missile.target = new_target;
-            }
-
-            let direction = if let MissileTarget::Target(target) = missile.target {
+            if missile.spell.behaviour == MissileBehaviour::Straight {
                 if missile_energy >= 1.0 {
-                    if let Some(hit_monster) = find_first_hit_monster(
+                    if let Some(hit_monster_entity) = find_first_hit_monster(
                         missile_position,
                         missile.radius,
-                        &monsters,
-                        &world_positions,
-                        &self.entities,
-                        &*dead,
-                        frame_number,
+                        &spatial_grids.monsters,
                     ) {
-                        if self.game_state_helper.is_authoritative() {
-                            damage_histories
-                                .get_mut(hit_monster)
-                                .expect("Expected a DamageHistory")
-                                .add_entry(
-                                    frame_number,
-                                    DamageHistoryEntry {
-                                        damage: missile.damage,
-                                    },
-                                );
-                        }
-                        let dead_since_frame = frame_number + 1;
-                        let frame_acknowledged =
-                            dead_since_frame.max(self.game_time_service.game_frame_number());
-                        dead.insert(
+                        hit_monster(
+                            game_time_service,
+                            is_authoritative,
+                            frame_number,
                             missile_entity,
-                            Dead::new(dead_since_frame, frame_acknowledged),
-                        )
-                        .expect("Expected to insert a Dead component");
+                            missile,
+                            hit_monster_entity,
+                            damage_histories,
+                            run_stats,
+                            dead,
+                        );
                         continue;
                     }
                 }
-                let monster = monsters.get(target).expect("Expected a targeted Monster");
-                destination + monster.velocity - missile_position
             } else {
-                destination
-            };
-            let needed_angle = Rotation2::rotation_between(&missile.velocity, &direction).angle();
-            let angle = needed_angle.abs().min(MAX_ROTATION) * needed_angle.signum();
-            let a = if needed_angle.abs() > angle.abs() {
-                -MISSILE_ACCELERATION
-            } else {
-                MISSILE_ACCELERATION
-            };
-            let current_speed = missile.velocity.norm();
-            let speed = clamp(current_speed + a, MISSILE_MIN_SPEED, MISSILE_MAX_SPEED);
-            let new_direction = Rotation2::new(angle) * missile.velocity.normalize();
+                let (destination, new_target) = match missile.target {
+                    MissileTarget::Target(target) => {
+                        if let Some(target_position) = world_positions.get(target) {
+                            (**target_position, None)
+                        } else if let Some((target, target_position)) =
+                            closest_monster(missile_position, &spatial_grids.monsters)
+                        {
+                            (target_position, Some(MissileTarget::Target(target)))
+                        } else {
+                            let target_position = random_scene_position(game_level_state);
+                            (
+                                target_position,
+                                Some(MissileTarget::Destination(target_position)),
+                            )
+                        }
+                    }
+                    MissileTarget::Destination(destination) => {
+                        if let Some((target, target_position)) =
+                            closest_monster(missile_position, &spatial_grids.monsters)
+                        {
+                            (target_position, Some(MissileTarget::Target(target)))
+                        } else if (destination - missile_position).norm_squared()
+                            > missile.velocity.norm_squared()
+                        {
+                            (destination, None)
+                        } else {
+                            let target_position = random_scene_position(game_level_state);
+                            (
+                                target_position,
+                                Some(MissileTarget::Destination(target_position)),
+                            )
+                        }
+                    }
+                };
+                if let Some(new_target) = new_target {
+                    missile.target = new_target;
+                }
 
-            missile.velocity = new_direction * speed;
+                let direction = if let MissileTarget::Target(target) = missile.target {
+                    if missile_energy >= 1.0 {
+                        if let Some(hit_monster_entity) = find_first_hit_monster(
+                            missile_position,
+                            missile.radius,
+                            &spatial_grids.monsters,
+                        ) {
+                            hit_monster(
+                                game_time_service,
+                                is_authoritative,
+                                frame_number,
+                                missile_entity,
+                                missile,
+                                hit_monster_entity,
+                                damage_histories,
+                                run_stats,
+                                dead,
+                            );
+                            continue;
+                        }
+                    }
+                    let monster = monsters.get(target).expect("Expected a targeted Monster");
+                    destination + monster.velocity - missile_position
+                } else {
+                    destination
+                };
+                let needed_angle =
+                    Rotation2::rotation_between(&missile.velocity, &direction).angle();
+                let angle = needed_angle.abs().min(max_rotation) * needed_angle.signum();
+                let a = if needed_angle.abs() > angle.abs() {
+                    -acceleration
+                } else {
+                    acceleration
+                };
+                let current_speed = missile.velocity.norm();
+                let speed = clamp(current_speed + a, MISSILE_MIN_SPEED, MISSILE_MAX_SPEED);
+                let new_direction = Rotation2::new(angle) * missile.velocity.normalize();
+
+                missile.velocity = new_direction * speed;
+            }
 
             let missile_position = world_positions
                 .get_mut(missile_entity)
                 .expect("Expected a Missile");
-            **missile_position +=
-                missile.velocity * self.game_time_service.engine_time().fixed_seconds();
+            **missile_position += missile.velocity * fixed_seconds;
+
+            // Missiles can't fly through obstacles, they just die hitting them.
+            if level_obstacles.intersects(**missile_position, missile.radius) {
+                kill_missile(game_time_service, frame_number, missile_entity, dead);
+            }
         }
     }
 }
+
+/// Hits monsters during the frame a nova is cast at, the rest of its lifetime
+/// is just for showing the expanding ring.
+///
+/// Like missile hits, nova damage isn't predicted by clients: they receive damage history entries
+/// from a server, and a nova cast by a remote player reaches them too late to hit the same monsters.
+fn process_nova(
+    game_time_service: &GameTimeService,
+    is_authoritative: bool,
+    frame_number: u64,
+    nova_entity: Entity,
+    nova: &Missile,
+    nova_position: Vector2,
+    monsters_grid: &SpatialGrid,
+    damage_histories: &mut WriteStorage<DamageHistory>,
+    run_stats: &mut RunStats,
+    dead: &mut WriteStorage<Dead>,
+) {
+    if nova.frame_spawned == frame_number && is_authoritative {
+        let hit_monsters = monsters_grid.entries_in_radius(nova_position, nova.radius);
+        for hit_monster in &hit_monsters {
+            damage_histories
+                .get_mut(hit_monster.entity)
+                .expect("Expected a DamageHistory")
+                .add_entry(
+                    frame_number,
+                    DamageHistoryEntry::new(
+                        nova.spell.damage,
+                        nova.spell.damage_type,
+                        DamageCause::Nova,
+                        Some(nova.caster),
+                    )
+                    .with_status_effect(nova.spell.status_effect),
+                );
+        }
+        // A nova is counted as a single hit, no matter how many monsters it has hit.
+        if !hit_monsters.is_empty() {
+            run_stats.player_mut(frame_number, nova.caster).missiles_hit += 1;
+        }
+    }
+
+    let lifespan = game_time_service.seconds_between_frames(frame_number, nova.frame_spawned);
+    if lifespan >= NOVA_DURATION_SECS {
+        kill_missile(game_time_service, frame_number, nova_entity, dead);
+    }
+}
+
+fn hit_monster(
+    game_time_service: &GameTimeService,
+    is_authoritative: bool,
+    frame_number: u64,
+    missile_entity: Entity,
+    missile: &Missile,
+    hit_monster: Entity,
+    damage_histories: &mut WriteStorage<DamageHistory>,
+    run_stats: &mut RunStats,
+    dead: &mut WriteStorage<Dead>,
+) {
+    if is_authoritative {
+        damage_histories
+            .get_mut(hit_monster)
+            .expect("Expected a DamageHistory")
+            .add_entry(
+                frame_number,
+                DamageHistoryEntry::new(
+                    missile.spell.damage,
+                    missile.spell.damage_type,
+                    DamageCause::Missile,
+                    Some(missile.caster),
+                )
+                .with_status_effect(missile.spell.status_effect),
+            );
+        run_stats
+            .player_mut(frame_number, missile.caster)
+            .missiles_hit += 1;
+    }
+    kill_missile(game_time_service, frame_number, missile_entity, dead);
+}
+
+/// Marks a missile as dead since the next frame.
+fn kill_missile(
+    game_time_service: &GameTimeService,
+    frame_number: u64,
+    missile_entity: Entity,
+    dead: &mut WriteStorage<Dead>,
+) {
+    let dead_since_frame = frame_number + 1;
+    let frame_acknowledged = dead_since_frame.max(game_time_service.game_frame_number());
+    dead.insert(
+        missile_entity,
+        Dead::new(dead_since_frame, frame_acknowledged),
+    )
+    .expect("Expected to insert a Dead component");
+}
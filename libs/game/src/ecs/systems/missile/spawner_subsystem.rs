@@ -1,155 +1,185 @@
 use amethyst::{
     core::Transform,
-    ecs::{Entities, Entity, WriteStorage},
+    ecs::{
+        shred::{ResourceId, SystemData},
+        Entities, Entity, ReadExpect, World, WriteExpect, WriteStorage,
+    },
 };
 use gv_core::profile_scope;
 
 use gv_core::{
     actions::IdentifiableAction,
     ecs::{
-        components::{missile::*, Dead, Monster, WorldPosition},
-        resources::net::CastActionsToExecute,
-        system_data::time::GameTimeService,
+        components::{missile::*, WorldPosition},
+        resources::net::{CastActionToExecute, CastActionsToExecute},
     },
-    math::Vector2,
+    math::{Vector2, ZeroVector},
 };
 
 use crate::{
     ecs::{
-        system_data::GameStateHelper,
+        resources::{SpatialGrids, SpellDefinitions},
         systems::{
-            missile::physics_subsystem::MISSILE_MAX_SPEED, GraphicsResourceBundle, WriteExpectCell,
-            WriteStorageCell,
+            missile::physics_subsystem::MISSILE_MAX_SPEED,
+            rollback::{RollbackFrame, RollbackStage},
         },
     },
     utils::world::closest_monster,
 };
 
-pub struct MissileSpawnerSubsystem<'a, 's> {
-    pub game_time_service: &'s GameTimeService<'s>,
-    pub game_state_helper: &'s GameStateHelper<'s>,
-    pub entities: &'s Entities<'s>,
-    pub missile_factory: &'a MissileFactory<'a, 's>,
-    pub cast_actions_to_execute: WriteExpectCell<'s, CastActionsToExecute>,
-    pub monsters: WriteStorageCell<'s, Monster>,
-    pub dead: WriteStorageCell<'s, Dead>,
-    pub world_positions: WriteStorageCell<'s, WorldPosition>,
+/// Spawns missiles for the cast actions players have executed during the frame.
+pub struct MissileSpawnerSubsystem;
+
+impl<'s> RollbackStage<'s> for MissileSpawnerSubsystem {
+    type SystemData = MissileSpawnerSystemData<'s>;
+
+    fn run_frame(&mut self, mut system_data: Self::SystemData, frame: &mut RollbackFrame) {
+        system_data.spawn_missiles(frame.frame_number);
+    }
 }
 
-impl<'a, 's> MissileSpawnerSubsystem<'a, 's> {
-    pub fn spawn_missiles(&self, frame_number: u64) {
-        profile_scope!("MissileSpawnerSubsystem::spawn_missiles");
-        let mut world_positions = self.world_positions.borrow_mut();
-        let mut cast_actions_to_execute = self.cast_actions_to_execute.borrow_mut();
-        let dead = self.dead.borrow();
-        let monsters = self.monsters.borrow();
+#[derive(SystemData)]
+pub struct MissileSpawnerSystemData<'s> {
+    spell_definitions: ReadExpect<'s, SpellDefinitions>,
+    missile_factory: MissileFactory<'s>,
+    cast_actions_to_execute: WriteExpect<'s, CastActionsToExecute>,
+    world_positions: WriteStorage<'s, WorldPosition>,
+    spatial_grids: ReadExpect<'s, SpatialGrids>,
+}
 
-        for cast_action in cast_actions_to_execute.actions.drain(..) {
-            let IdentifiableAction {
-                action_id,
-                action: cast_action,
+impl<'s> MissileSpawnerSystemData<'s> {
+    pub fn spawn_missiles(&mut self, frame_number: u64) {
+        profile_scope!("MissileSpawnerSubsystem::spawn_missiles");
+        for cast_action in self.cast_actions_to_execute.actions.drain(..) {
+            let CastActionToExecute {
+                caster,
+                damage,
+                action:
+                    IdentifiableAction {
+                        action_id,
+                        action: cast_action,
+                    },
             } = cast_action;
 
-            let search_result = closest_monster(
-                cast_action.target_position,
-                &*world_positions,
-                &self.entities,
-                &*monsters,
-                &*dead,
-                frame_number,
-            );
-
-            let target = if let Some((monster, _)) = search_result {
-                MissileTarget::Target(monster)
-            } else {
-                MissileTarget::Destination(cast_action.target_position)
+            let spell = match self.spell_definitions.get(&cast_action.spell) {
+                Some(spell) => spell.missile_spell(damage),
+                None => {
+                    log::error!("Couldn't find a spell definition for {}", cast_action.spell);
+                    continue;
+                }
             };
-            let direction = cast_action.target_position - cast_action.cast_position;
-            let velocity = direction.normalize() * MISSILE_MAX_SPEED;
+
+            let (radius, target, velocity) =
+                if let MissileBehaviour::Nova { radius } = spell.behaviour {
+                    // A nova doesn't fly anywhere, it expands around the caster.
+                    (
+                        radius,
+                        MissileTarget::Destination(cast_action.cast_position),
+                        Vector2::zero(),
+                    )
+                } else {
+                    let search_result =
+                        closest_monster(cast_action.target_position, &self.spatial_grids.monsters);
+
+                    let target = match (spell.behaviour, search_result) {
+                        (MissileBehaviour::Homing, Some((monster, _))) => {
+                            MissileTarget::Target(monster)
+                        }
+                        _ => MissileTarget::Destination(cast_action.target_position),
+                    };
+                    let direction = cast_action.target_position - cast_action.cast_position;
+                    (5.0, target, direction.normalize() * MISSILE_MAX_SPEED)
+                };
 
             self.missile_factory.create(
                 action_id,
-                &mut *world_positions,
-                5.0,
+                caster,
+                &mut self.world_positions,
+                radius,
                 target,
                 velocity,
                 frame_number,
                 cast_action.cast_position,
+                spell,
             );
         }
     }
 }
 
-pub struct MissileFactory<'a, 's> {
-    entities: &'s Entities<'s>,
-    transforms: WriteStorageCell<'s, Transform>,
-    missiles: WriteStorageCell<'s, Missile>,
-    //#[cfg_attr(not(feature = "client"), allow(dead_code))]
-    #[allow(dead_code)]
-    graphics_resource_bundle: &'a GraphicsResourceBundle<'s>,
+#[derive(SystemData)]
+pub struct MissileFactory<'s> {
+    entities: Entities<'s>,
+    transforms: WriteStorage<'s, Transform>,
+    missiles: WriteStorage<'s, Missile>,
 }
 
-impl<'a, 's> MissileFactory<'a, 's> {
-    pub fn new(
-        entities: &'s Entities<'s>,
-        transforms: WriteStorageCell<'s, Transform>,
-        missiles: WriteStorageCell<'s, Missile>,
-        graphics_resource_bundle: &'a GraphicsResourceBundle<'s>,
-    ) -> Self {
-        Self {
-            entities,
-            transforms,
-            missiles,
-            graphics_resource_bundle,
-        }
-    }
-
+impl<'s> MissileFactory<'s> {
     #[cfg(feature = "client")]
     pub fn create(
-        &self,
+        &mut self,
         action_id: u64,
+        caster: Entity,
         world_positions: &mut WriteStorage<'s, WorldPosition>,
         radius: f32,
         target: MissileTarget<Entity>,
         velocity: Vector2,
         frame_spawned: u64,
         position: Vector2,
+        spell: MissileSpell,
     ) -> Entity {
         let mut transform = Transform::default();
         transform.set_translation_xyz(position.x, position.y, 50.0);
 
         self.entities
             .build_entity()
-            .with(transform, &mut self.transforms.borrow_mut())
+            .with(transform, &mut self.transforms)
             .with(WorldPosition::new(position), world_positions)
             .with(
-                Missile::new(action_id, radius, target, velocity, frame_spawned),
-                &mut self.missiles.borrow_mut(),
+                Missile::new(
+                    action_id,
+                    caster,
+                    radius,
+                    target,
+                    velocity,
+                    frame_spawned,
+                    spell,
+                ),
+                &mut self.missiles,
             )
             .build()
     }
 
     #[cfg(not(feature = "client"))]
     pub fn create(
-        &self,
+        &mut self,
         action_id: u64,
+        caster: Entity,
         world_positions: &mut WriteStorage<'s, WorldPosition>,
         radius: f32,
         target: MissileTarget<Entity>,
         velocity: Vector2,
         frame_spawned: u64,
         position: Vector2,
+        spell: MissileSpell,
     ) -> Entity {
         let mut transform = Transform::default();
         transform.set_translation_xyz(position.x, position.y, 0.0);
 
         self.entities
             .build_entity()
-            .with(transform, &mut self.transforms.borrow_mut())
+            .with(transform, &mut self.transforms)
             .with(WorldPosition::new(position), world_positions)
             .with(
-                Missile::new(action_id, radius, target, velocity, frame_spawned),
-                &mut self.missiles.borrow_mut(),
+                Missile::new(
+                    action_id,
+                    caster,
+                    radius,
+                    target,
+                    velocity,
+                    frame_spawned,
+                    spell,
+                ),
+                &mut self.missiles,
             )
             .build()
     }
@@ -4,40 +4,52 @@ pub mod player;
 
 mod action;
 mod damage_subsystem;
+mod dead_cleanup;
+mod game_over;
 mod level;
 mod net_connection_manager;
+mod objective;
 mod pause;
+mod pickup_subsystem;
+mod rollback;
+mod spatial_grids_subsystem;
 mod state_switcher;
+mod status_effect_subsystem;
 mod world_position_transform;
 mod world_state_subsystem;
 
 pub use self::{
-    action::ActionSystem,
+    action::{ActionSystem, ActionSystemDesc},
     damage_subsystem::DamageSubsystem,
+    dead_cleanup::DeadCleanupSystem,
+    game_over::GameOverSystem,
     level::LevelSystem,
     net_connection_manager::{NetConnectionManagerDesc, NetConnectionManagerSystem},
+    objective::LevelObjectiveSystem,
     pause::PauseSystem,
+    pickup_subsystem::PickupSubsystem,
+    rollback::{RollbackFrame, RollbackScheduler, RollbackStage, RollbackStages},
+    spatial_grids_subsystem::SpatialGridsSubsystem,
     state_switcher::StateSwitcherSystem,
+    status_effect_subsystem::StatusEffectSubsystem,
     world_position_transform::WorldPositionTransformSystem,
     world_state_subsystem::WorldStateSubsystem,
 };
 
 use amethyst::ecs::{
     shred::{ResourceId, SystemData},
-    Entity, World, WriteExpect, WriteStorage,
+    Entity, World,
 };
 #[cfg(feature = "client")]
 use amethyst::{
     animation::{AnimationControlSet, AnimationSet},
-    assets::Handle,
     core::{Named, ParentHierarchy},
-    ecs::{ReadExpect, ReadStorage},
-    renderer::{sprite::SpriteRender, Material, Mesh},
+    ecs::{ReadExpect, ReadStorage, WriteStorage},
+    renderer::sprite::SpriteRender,
 };
 
 #[cfg(not(feature = "client"))]
 use std::marker::PhantomData;
-use std::{cell::RefCell, rc::Rc};
 
 use gv_animation_prefabs::AnimationId;
 #[cfg(feature = "client")]
@@ -72,21 +84,6 @@ pub type FrameUpdate = ReceivedServerWorldUpdate;
 #[cfg(not(feature = "client"))]
 pub type FrameUpdate = ReceivedClientActionUpdates;
 
-type WriteStorageCell<'s, T> = Rc<RefCell<WriteStorage<'s, T>>>;
-type WriteExpectCell<'s, T> = Rc<RefCell<WriteExpect<'s, T>>>;
-
-#[cfg(feature = "client")]
-#[allow(dead_code)]
-pub struct GraphicsResourceBundle<'s> {
-    meshes: WriteStorageCell<'s, Handle<Mesh>>,
-    materials: WriteStorageCell<'s, Handle<Material>>,
-}
-
-#[cfg(not(feature = "client"))]
-pub struct GraphicsResourceBundle<'s> {
-    _lifetime: PhantomData<&'s ()>,
-}
-
 #[cfg(feature = "client")]
 #[derive(SystemData)]
 pub struct AnimationsSystemData<'s> {
@@ -151,35 +148,3 @@ impl<'s> AnimationsSystemData<'s> {
     ) {
     }
 }
-
-#[cfg(feature = "client")]
-pub struct AnimationsResourceBundle<'s> {
-    pub parent_hierarchy: ReadExpect<'s, ParentHierarchy>,
-    pub named: ReadStorage<'s, Named>,
-    pub animation_sets: ReadStorage<'s, AnimationSet<AnimationId, SpriteRender>>,
-    pub animation_control_sets:
-        WriteStorageCell<'s, AnimationControlSet<AnimationId, SpriteRender>>,
-}
-
-#[cfg(not(feature = "client"))]
-pub struct AnimationsResourceBundle<'s> {
-    _lifetime: PhantomData<&'s ()>,
-}
-
-impl<'s> AnimationsResourceBundle<'s> {
-    #[cfg(feature = "client")]
-    fn play_animation(&self, entity: Entity, body_part_name: &str, animation_id: AnimationId) {
-        play_animation(
-            &self.parent_hierarchy,
-            &self.named,
-            &self.animation_sets,
-            &mut self.animation_control_sets.borrow_mut(),
-            entity,
-            body_part_name,
-            animation_id,
-        );
-    }
-
-    #[cfg(not(feature = "client"))]
-    fn play_animation(&self, _entity: Entity, _body_part_name: &str, _animation_id: AnimationId) {}
-}
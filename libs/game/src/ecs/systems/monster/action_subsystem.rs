@@ -1,6 +1,11 @@
-use amethyst::ecs::{Entities, Entity, Join, ReadExpect, ReadStorage, WriteStorage};
+use amethyst::ecs::{
+    shred::{ResourceId, SystemData},
+    Entities, Entity, Join, ReadExpect, ReadStorage, World, WriteExpect, WriteStorage,
+};
 use gv_core::profile_scope;
 
+use std::cmp::Ordering;
+
 use gv_animation_prefabs::{AnimationId, MONSTER_BODY};
 use gv_core::{
     actions::{
@@ -9,11 +14,14 @@ use gv_core::{
     },
     ecs::{
         components::{
-            damage_history::{DamageHistory, DamageHistoryEntry},
-            ClientPlayerActions, EntityNetMetadata, Monster, NetWorldPosition, Player,
-            WorldPosition,
+            damage_history::{DamageCause, DamageHistory, DamageHistoryEntry, DamageType},
+            status_effect::StatusEffects,
+            ClientPlayerActions, Dead, EntityNetMetadata, Monster, NetWorldPosition, WorldPosition,
+        },
+        resources::{
+            net::{EntityNetMetadataStorage, MultiplayerGameState},
+            GameLevelState,
         },
-        resources::GameLevelState,
         system_data::time::GameTimeService,
     },
     math::{Vector2, ZeroVector},
@@ -22,28 +30,48 @@ use gv_core::{
 
 use crate::{
     ecs::{
-        resources::MonsterDefinitions,
+        resources::{
+            LevelObstacles, MonsterDefinition, MonsterDefinitions, NavigationGrid, RangedAttack,
+            RangedAttacksToExecute, SpatialGrid, SpatialGrids,
+        },
         system_data::GameStateHelper,
-        systems::{AnimationsResourceBundle, OutcomingNetUpdates, WriteStorageCell},
+        systems::{
+            rollback::{RollbackFrame, RollbackStage},
+            AnimationsSystemData, FrameUpdate, OutcomingNetUpdates,
+        },
     },
-    utils::world::random_scene_position,
+    utils::{entities::is_dead, world::random_walkable_position},
 };
 
 const MAX_IDLE_TIME_SECS: f32 = 0.5;
+/// Monsters start pushing each other away a bit before their bodies touch.
+const SEPARATION_MARGIN: f32 = 2.0;
+/// Each of two overlapping monsters resolves half of the overlap.
+const SEPARATION_FACTOR: f32 = 0.5;
 
-pub struct MonsterActionSubsystem<'a, 's> {
-    pub entities: &'s Entities<'s>,
-    pub game_time_service: &'s GameTimeService<'s>,
-    pub game_state_helper: &'s GameStateHelper<'s>,
-    pub monster_definitions: &'s ReadExpect<'s, MonsterDefinitions>,
-    pub game_level_state: &'s ReadExpect<'s, GameLevelState>,
-    pub client_player_actions: &'s ReadStorage<'s, ClientPlayerActions>,
-    pub entity_net_metadata: WriteStorageCell<'s, EntityNetMetadata>,
-    pub players: WriteStorageCell<'s, Player>,
-    pub world_positions: WriteStorageCell<'s, WorldPosition>,
-    pub net_world_positions: WriteStorageCell<'s, NetWorldPosition>,
-    pub damage_histories: WriteStorageCell<'s, DamageHistory>,
-    pub animations_resource_bundle: &'a AnimationsResourceBundle<'s>,
+pub struct MonsterActionSubsystem;
+
+#[derive(SystemData)]
+pub struct MonsterActionSystemData<'s> {
+    entities: Entities<'s>,
+    game_time_service: GameTimeService<'s>,
+    game_state_helper: GameStateHelper<'s>,
+    monster_definitions: ReadExpect<'s, MonsterDefinitions>,
+    game_level_state: ReadExpect<'s, GameLevelState>,
+    level_obstacles: ReadExpect<'s, LevelObstacles>,
+    multiplayer_game_state: ReadExpect<'s, MultiplayerGameState>,
+    client_player_actions: ReadStorage<'s, ClientPlayerActions>,
+    entity_net_metadata_storage: ReadExpect<'s, EntityNetMetadataStorage>,
+    entity_net_metadata: ReadStorage<'s, EntityNetMetadata>,
+    dead: ReadStorage<'s, Dead>,
+    world_positions: WriteStorage<'s, WorldPosition>,
+    net_world_positions: ReadStorage<'s, NetWorldPosition>,
+    damage_histories: WriteStorage<'s, DamageHistory>,
+    status_effects: ReadStorage<'s, StatusEffects>,
+    spatial_grids: ReadExpect<'s, SpatialGrids>,
+    navigation_grid: WriteExpect<'s, NavigationGrid>,
+    ranged_attacks_to_execute: WriteExpect<'s, RangedAttacksToExecute>,
+    animations: AnimationsSystemData<'s>,
 }
 
 pub struct ApplyMonsterActionNetArgs<'a> {
@@ -53,9 +81,71 @@ pub struct ApplyMonsterActionNetArgs<'a> {
     pub updates: Option<(WorldPosition, MobAction<Entity>)>,
 }
 
-impl<'a, 's> MonsterActionSubsystem<'a, 's> {
+impl<'s> RollbackStage<'s> for MonsterActionSubsystem {
+    type SystemData = (WriteStorage<'s, Monster>, MonsterActionSystemData<'s>);
+
+    fn run_frame(&mut self, system_data: Self::SystemData, frame: &mut RollbackFrame) {
+        let (mut monsters, mut system_data) = system_data;
+        let frame_number = frame.frame_number;
+        let dead = &system_data.dead;
+        let monsters_net_metadata = &system_data.entity_net_metadata;
+        for (entity, monster, monster_net_metadata) in (&system_data.entities, &mut monsters)
+            .join()
+            .filter(|(entity, _)| !is_dead(*entity, dead, frame_number))
+            .map(|(entity, monster)| (entity, monster, monsters_net_metadata.get(entity).cloned()))
+            .collect::<Vec<_>>()
+        {
+            let monster_is_spawned = monster_net_metadata
+                .map(|net_metadata| net_metadata.spawned_frame_number <= frame_number)
+                .unwrap_or(true);
+            if !monster_is_spawned {
+                continue;
+            }
+
+            let net_args = if system_data.multiplayer_game_state.is_playing {
+                let monster_net_metadata =
+                    monster_net_metadata.expect("Expected EntityNetMetadata for a monster");
+                let updates = mob_actions_update(
+                    frame.updates,
+                    monster_net_metadata,
+                    &system_data.entity_net_metadata_storage,
+                );
+
+                Some(ApplyMonsterActionNetArgs {
+                    entity_net_id: monster_net_metadata.id,
+                    outcoming_net_updates: frame.outcoming_net_updates,
+                    updates,
+                })
+            } else {
+                None
+            };
+
+            let (is_stunned, speed_multiplier) =
+                system_data
+                    .status_effects
+                    .get(entity)
+                    .map_or((false, 1.0), |status_effects| {
+                        (
+                            status_effects.is_stunned(frame_number),
+                            status_effects.speed_multiplier(frame_number),
+                        )
+                    });
+            // Stunned monsters neither pick new actions nor walk. Their attacks are paused,
+            // so that a melee attack still hits once a stun wears off.
+            if is_stunned {
+                monster.stunned_frames += 1;
+                continue;
+            }
+
+            system_data.decide_monster_action(frame_number, entity, monster, net_args);
+            system_data.process_monster_movement(entity, monster, speed_multiplier);
+        }
+    }
+}
+
+impl<'s> MonsterActionSystemData<'s> {
     pub fn decide_monster_action<'n>(
-        &self,
+        &mut self,
         frame_number: u64,
         entity: Entity,
         monster: &mut Monster,
@@ -66,15 +156,14 @@ impl<'a, 's> MonsterActionSubsystem<'a, 's> {
             .as_ref()
             .and_then(|net_args| net_args.updates.as_ref().map(|update| update.0.clone()));
         let monster_position = if let Some(updated_position) = updated_position {
-            let mut world_positions = self.world_positions.borrow_mut();
-            let monster_position = world_positions
+            let monster_position = self
+                .world_positions
                 .get_mut(entity)
                 .expect("Expected a WorldPosition");
             *monster_position = updated_position;
             monster_position.clone()
         } else {
             self.world_positions
-                .borrow()
                 .get(entity)
                 .expect("Expected a WorldPosition")
                 .clone()
@@ -88,12 +177,13 @@ impl<'a, 's> MonsterActionSubsystem<'a, 's> {
             } = net_args.expect("Expected ApplyMonsterActionNetArgs in multiplayer");
 
             if self.game_state_helper.is_authoritative() {
-                let action = self.new_action(frame_number, &monster, monster_position.clone());
+                let action =
+                    self.new_action(frame_number, entity, monster, monster_position.clone());
                 if let Some(action) = &action {
                     let update = NetUpdateWithPosition {
                         entity_net_id,
                         position: monster_position.clone(),
-                        data: action.load_entity_net_id(&*self.entity_net_metadata.borrow_mut()),
+                        data: action.load_entity_net_id(&self.entity_net_metadata),
                     };
                     add_mob_action_update(outcoming_net_updates, update)
                 }
@@ -102,11 +192,11 @@ impl<'a, 's> MonsterActionSubsystem<'a, 's> {
                 updates.map(|updates| updates.1)
             }
         } else {
-            self.new_action(frame_number, &monster, monster_position.clone())
+            self.new_action(frame_number, entity, monster, monster_position.clone())
         };
 
-        let world_positions = self.world_positions.borrow();
-        let net_world_positions = self.net_world_positions.borrow();
+        let world_positions = &self.world_positions;
+        let net_world_positions = &self.net_world_positions;
         let is_multiplayer = self.game_state_helper.is_multiplayer();
         let new_destination = if let Some(ref new_action) = new_action {
             log::trace!(
@@ -120,8 +210,8 @@ impl<'a, 's> MonsterActionSubsystem<'a, 's> {
                 MobAction::Move(position) => Some(*position),
                 MobAction::Chase(target) => Some(target_position(
                     *target,
-                    &world_positions,
-                    &net_world_positions,
+                    world_positions,
+                    net_world_positions,
                     &self.client_player_actions,
                     is_multiplayer,
                 )),
@@ -129,33 +219,49 @@ impl<'a, 's> MonsterActionSubsystem<'a, 's> {
                     target,
                     attack_type,
                 }) => {
-                    if let MobAttackType::Melee | MobAttackType::SlowMelee { .. } = attack_type {
-                        self.animations_resource_bundle.play_animation(
-                            entity,
-                            MONSTER_BODY,
-                            AnimationId::Attack,
-                        );
-                    }
+                    self.animations
+                        .play_animation(entity, MONSTER_BODY, AnimationId::Attack);
 
                     match attack_type {
-                        MobAttackType::Melee => Some(target_position(
-                            *target,
-                            &world_positions,
-                            &net_world_positions,
-                            &self.client_player_actions,
-                            is_multiplayer,
-                        )),
-                        _ => Some(monster_position.position),
+                        MobAttackType::Range { distance, .. } => {
+                            let target_position = target_position(
+                                *target,
+                                world_positions,
+                                net_world_positions,
+                                &self.client_player_actions,
+                                is_multiplayer,
+                            );
+                            self.ranged_attacks_to_execute.attacks.push(RangedAttack {
+                                shooter: entity,
+                                position: monster_position.position,
+                                target_position,
+                                damage: monster.attack_damage,
+                                status_effect: self
+                                    .monster_definitions
+                                    .get(&monster.name)
+                                    .and_then(|definition| definition.status_effect),
+                            });
+                            Some(keep_distance(
+                                monster_position.position,
+                                target_position,
+                                *distance,
+                            ))
+                        }
+                        // Monsters stand still while attacking, so that players can dodge.
+                        MobAttackType::Melee | MobAttackType::SlowMelee { .. } => {
+                            Some(monster_position.position)
+                        }
                     }
                 }
                 _ => None,
             }
         } else {
             match monster.action.action {
+                MobAction::Move(destination) => Some(destination),
                 MobAction::Chase(target) => Some(target_position(
                     target,
-                    &world_positions,
-                    &net_world_positions,
+                    world_positions,
+                    net_world_positions,
                     &self.client_player_actions,
                     is_multiplayer,
                 )),
@@ -163,36 +269,59 @@ impl<'a, 's> MonsterActionSubsystem<'a, 's> {
             }
         };
 
-        if let Some(destination) = new_destination {
-            monster.destination = destination;
-        }
-
         if let Some(action) = new_action {
             monster.action = Action {
                 frame_number,
                 action,
-            }
+            };
+            monster.stunned_frames = 0;
+        }
+
+        if let Some(destination) = new_destination {
+            monster.destination = match monster.action.action {
+                // Walking towards the next waypoint leads monsters around obstacles.
+                MobAction::Move(_) => {
+                    self.navigation_grid
+                        .next_waypoint(*monster_position, destination, None)
+                }
+                MobAction::Chase(target) => {
+                    self.navigation_grid
+                        .next_waypoint(*monster_position, destination, Some(target))
+                }
+                _ => destination,
+            };
         }
     }
 
-    pub fn process_monster_movement(&self, entity: Entity, monster: &mut Monster) {
+    pub fn process_monster_movement(
+        &mut self,
+        entity: Entity,
+        monster: &mut Monster,
+        speed_multiplier: f32,
+    ) {
         profile_scope!("MonsterActionSubsystem::process_monster_movement");
-        let mut world_positions = self.world_positions.borrow_mut();
-        let monster_position = world_positions
+        let monster_position = self
+            .world_positions
             .get_mut(entity)
             .expect("Expected a WorldPosition");
 
         let monster_definition = self
             .monster_definitions
-            .0
             .get(&monster.name)
             .expect("Expected a MonsterDefinition");
 
         let monster_position = &mut **monster_position;
-        let monster_speed = monster_definition.base_speed;
+        let monster_speed = monster_definition.base_speed * speed_multiplier;
         let time = self.game_time_service.engine_time().fixed_seconds();
         let travel_distance_squared = monster_speed * monster_speed * time * time;
 
+        let separation = separation(
+            &self.spatial_grids.monsters,
+            entity,
+            monster,
+            *monster_position,
+        );
+
         let displacement = monster.destination - *monster_position;
 
         if displacement.norm_squared() > 0.0 {
@@ -206,31 +335,40 @@ impl<'a, 's> MonsterActionSubsystem<'a, 's> {
             monster.velocity = displacement.normalize() * monster_speed * time;
             *monster_position + monster.velocity
         };
+
+        // Separation can't make a monster move faster than it walks.
+        *monster_position += if separation.norm_squared() > travel_distance_squared {
+            separation.normalize() * monster_speed * time
+        } else {
+            separation
+        };
+        *monster_position = self
+            .level_obstacles
+            .resolve(*monster_position, monster.radius);
     }
 
     fn new_action(
-        &self,
+        &mut self,
         frame_number: u64,
+        entity: Entity,
         monster: &Monster,
         monster_position: WorldPosition,
     ) -> Option<MobAction<Entity>> {
         profile_scope!("MonsterActionSubsystem::new_action");
-        let players = self.players.borrow();
-        let world_positions = self.world_positions.borrow();
-        let mut damage_histories = self.damage_histories.borrow_mut();
+        let spatial_grids = &self.spatial_grids;
+        let damage_histories = &mut self.damage_histories;
 
         let monster_definition = self
             .monster_definitions
-            .0
             .get(&monster.name)
             .expect("Expected a MonsterDefinition");
 
         match monster.action.action {
             MobAction::Idle => {
                 if let Some((entity, _player_position)) = find_player_in_radius(
-                    (self.entities, &*players, &*world_positions).join(),
+                    &spatial_grids.players,
                     *monster_position,
-                    200.0,
+                    monster_definition.aggro_radius,
                 ) {
                     Some(MobAction::Chase(entity))
                 } else {
@@ -238,8 +376,9 @@ impl<'a, 's> MonsterActionSubsystem<'a, 's> {
                         .game_time_service
                         .seconds_between_frames(frame_number, monster.action.frame_number);
                     if MAX_IDLE_TIME_SECS < time_being_idle {
-                        Some(MobAction::Move(random_scene_position(
-                            &*self.game_level_state,
+                        Some(MobAction::Move(random_walkable_position(
+                            &self.game_level_state,
+                            &self.navigation_grid,
                         )))
                     } else {
                         None
@@ -248,12 +387,15 @@ impl<'a, 's> MonsterActionSubsystem<'a, 's> {
             }
             MobAction::Move(destination) => {
                 if let Some((entity, _player_position)) = find_player_in_radius(
-                    (self.entities, &*players, &*world_positions).join(),
+                    &spatial_grids.players,
                     *monster_position,
-                    200.0,
+                    monster_definition.aggro_radius,
                 ) {
                     Some(MobAction::Chase(entity))
-                } else if (*monster_position - destination).norm_squared() < 0.01 {
+                } else if (*monster_position - destination).norm_squared()
+                    < monster.radius * monster.radius
+                {
+                    // Monsters push each other, so they may never reach the exact destination.
                     Some(MobAction::Idle)
                 } else {
                     None
@@ -261,19 +403,19 @@ impl<'a, 's> MonsterActionSubsystem<'a, 's> {
             }
             MobAction::Chase(_) => {
                 if let Some((target, _player_position)) = find_player_in_radius(
-                    (self.entities, &*players, &*world_positions).join(),
+                    &spatial_grids.players,
                     *monster_position,
-                    monster.radius,
+                    attack_radius(monster, &monster_definition.attack_type),
                 ) {
-                    if self.game_state_helper.is_authoritative() {
+                    if hits_on_start(&monster_definition.attack_type)
+                        && self.game_state_helper.is_authoritative()
+                    {
                         let damage_history = damage_histories
                             .get_mut(target)
                             .expect("Expected player's DamageHistory");
                         damage_history.add_entry(
                             frame_number,
-                            DamageHistoryEntry {
-                                damage: monster.attack_damage,
-                            },
+                            melee_damage_entry(monster, entity, monster_definition),
                         );
                     }
                     Some(MobAction::Attack(MobAttackAction {
@@ -286,31 +428,61 @@ impl<'a, 's> MonsterActionSubsystem<'a, 's> {
             }
             MobAction::Attack(ref attack_action) => {
                 let is_cooling_down = match attack_action.attack_type {
-                    MobAttackType::SlowMelee { cooldown } => {
+                    MobAttackType::Melee => {
+                        let timing = monster_definition
+                            .melee_timing
+                            .expect("Expected MeleeAttackTiming for a Melee attack");
+                        let frames_attacking =
+                            frame_number.saturating_sub(monster.attack_started_frame());
+                        let hit_frames = self
+                            .game_time_service
+                            .frames_in_seconds(timing.wind_up)
+                            .max(1);
+                        // Players dodge an attack by leaving its reach before it hits.
+                        if frames_attacking == hit_frames
+                            && self.game_state_helper.is_authoritative()
+                            && is_player_in_radius(
+                                &spatial_grids.players,
+                                attack_action.target,
+                                *monster_position,
+                                monster.radius,
+                            )
+                        {
+                            damage_histories
+                                .get_mut(attack_action.target)
+                                .expect("Expected player's DamageHistory")
+                                .add_entry(
+                                    frame_number,
+                                    melee_damage_entry(monster, entity, monster_definition),
+                                );
+                        }
+                        frames_attacking
+                            < self
+                                .game_time_service
+                                .frames_in_seconds(timing.wind_up + timing.recovery)
+                    }
+                    MobAttackType::SlowMelee { cooldown }
+                    | MobAttackType::Range { cooldown, .. } => {
                         self.game_time_service
-                            .seconds_between_frames(frame_number, monster.action.frame_number)
+                            .seconds_between_frames(frame_number, monster.attack_started_frame())
                             < cooldown
                     }
-                    _ => false,
                 };
                 let player_in_radius = find_player_in_radius(
-                    (self.entities, &*players, &*world_positions).join(),
+                    &spatial_grids.players,
                     *monster_position,
-                    monster.radius,
+                    attack_radius(monster, &attack_action.attack_type),
                 );
                 match (&attack_action.attack_type, player_in_radius) {
-                    // TODO: implement cooling down for other attacks as well.
-                    (MobAttackType::SlowMelee { .. }, _) if is_cooling_down => None,
-                    (_, Some((target, _player_position))) => {
-                        if self.game_state_helper.is_authoritative() {
+                    _ if is_cooling_down => None,
+                    (attack_type, Some((target, _player_position))) => {
+                        if hits_on_start(attack_type) && self.game_state_helper.is_authoritative() {
                             let damage_history = damage_histories
                                 .get_mut(target)
                                 .expect("Expected player's DamageHistory");
                             damage_history.add_entry(
                                 frame_number,
-                                DamageHistoryEntry {
-                                    damage: monster.attack_damage,
-                                },
+                                melee_damage_entry(monster, entity, monster_definition),
                             );
                         }
                         Some(MobAction::Attack(MobAttackAction {
@@ -325,25 +497,115 @@ impl<'a, 's> MonsterActionSubsystem<'a, 's> {
     }
 }
 
-fn find_player_in_radius<'a>(
-    mut players: impl Iterator<Item = (Entity, &'a Player, &'a WorldPosition)>,
+/// Calculates the displacement which pushes a monster away from the ones it overlaps with.
+/// Players aren't taken into account, as clients see remote players later than
+/// the server does, so they would push monsters differently.
+fn separation(
+    monsters_grid: &SpatialGrid,
+    entity: Entity,
+    monster: &Monster,
+    monster_position: Vector2,
+) -> Vector2 {
+    let radius = monster.radius + SEPARATION_MARGIN;
+
+    let mut neighbours = monsters_grid.entries_in_radius(monster_position, radius);
+    neighbours.retain(|neighbour| neighbour.entity != entity);
+
+    // Entity ids differ on clients and server, so we sort neighbours by their positions
+    // to sum the displacements in the same order.
+    neighbours.sort_by(|lhs, rhs| {
+        (lhs.position.x, lhs.position.y)
+            .partial_cmp(&(rhs.position.x, rhs.position.y))
+            .unwrap_or(Ordering::Equal)
+    });
+    neighbours
+        .iter()
+        .fold(Vector2::zero(), |separation, neighbour| {
+            let offset = monster_position - neighbour.position;
+            let distance = offset.norm();
+            if distance == 0.0 {
+                return separation;
+            }
+            let overlap = radius + neighbour.radius - distance;
+            separation + offset / distance * overlap * SEPARATION_FACTOR
+        })
+}
+
+/// Melee attacks deal physical damage and apply the status effect of a monster.
+fn melee_damage_entry(
+    monster: &Monster,
+    entity: Entity,
+    monster_definition: &MonsterDefinition,
+) -> DamageHistoryEntry {
+    DamageHistoryEntry::new(
+        monster.attack_damage,
+        DamageType::Physical,
+        DamageCause::Melee,
+        Some(entity),
+    )
+    .with_status_effect(monster_definition.status_effect)
+}
+
+/// `Melee` attacks hit after a wind-up and ranged ones hit with projectiles.
+fn hits_on_start(attack_type: &MobAttackType) -> bool {
+    matches!(*attack_type, MobAttackType::SlowMelee { .. })
+}
+
+/// Melee attackers need to touch a player, ranged ones shoot from the distance.
+fn attack_radius(monster: &Monster, attack_type: &MobAttackType) -> f32 {
+    match *attack_type {
+        MobAttackType::Range { distance, .. } => distance,
+        _ => monster.radius,
+    }
+}
+
+/// Ranged monsters step back from a target which comes closer than a half of the distance
+/// they shoot from.
+fn keep_distance(position: Vector2, target_position: Vector2, distance: f32) -> Vector2 {
+    let offset = position - target_position;
+    if offset.norm_squared() < distance * distance / 4.0 && offset.norm_squared() > 0.0 {
+        target_position + offset.normalize() * distance
+    } else {
+        position
+    }
+}
+
+fn is_player_in_radius(
+    players_grid: &SpatialGrid,
+    player: Entity,
     position: Vector2,
     radius: f32,
-) -> Option<(Entity, &'a WorldPosition)> {
+) -> bool {
     let radius_squared = radius * radius;
-    players
-        .find(|(_, player, player_position)| {
+    players_grid
+        .find(position, radius, |entry| {
+            let player_radius_squared = entry.radius * entry.radius;
+            entry.entity == player
+                && (position - entry.position).norm_squared()
+                    < radius_squared + player_radius_squared
+        })
+        .is_some()
+}
+
+fn find_player_in_radius(
+    players_grid: &SpatialGrid,
+    position: Vector2,
+    radius: f32,
+) -> Option<(Entity, Vector2)> {
+    let radius_squared = radius * radius;
+    players_grid
+        .find(position, radius, |player| {
             let player_radius_squared = player.radius * player.radius;
-            (position - ***player_position).norm_squared() < radius_squared + player_radius_squared
+            (position - player.position).norm_squared() < radius_squared + player_radius_squared
         })
-        .map(|(entity, _, player_position)| (entity, player_position))
+        .map(|player| (player.entity, player.position))
 }
 
 #[cfg(feature = "client")]
 fn target_position(
     entity: Entity,
     world_positions: &WriteStorage<WorldPosition>,
-    net_positions: &WriteStorage<NetWorldPosition>,
+    net_positions: &ReadStorage<NetWorldPosition>,
     client_player_actions: &ReadStorage<ClientPlayerActions>,
     is_multiplayer: bool,
 ) -> Vector2 {
@@ -363,7 +625,7 @@ fn target_position(
 fn target_position(
     entity: Entity,
     world_positions: &WriteStorage<WorldPosition>,
-    _net_positions: &WriteStorage<NetWorldPosition>,
+    _net_positions: &ReadStorage<NetWorldPosition>,
     _client_player_actions: &ReadStorage<ClientPlayerActions>,
     _is_multiplayer: bool,
 ) -> Vector2 {
@@ -386,3 +648,33 @@ fn add_mob_action_update(
 ) {
     outcoming_net_updates.mob_actions_updates.push(action);
 }
+
+#[cfg(feature = "client")]
+fn mob_actions_update<'a>(
+    frame_updates: &'a FrameUpdate,
+    entity_net_metadata: EntityNetMetadata,
+    entity_net_metadata_service: &'a EntityNetMetadataStorage,
+) -> Option<(WorldPosition, MobAction<Entity>)> {
+    frame_updates
+        .mob_actions_updates
+        .iter()
+        .find(|update| update.entity_net_id == entity_net_metadata.id)
+        .map(move |update| {
+            (
+                update.position.clone(),
+                update
+                    .data
+                    .clone()
+                    .load_entity_by_net_id(&entity_net_metadata_service),
+            )
+        })
+}
+
+#[cfg(not(feature = "client"))]
+fn mob_actions_update<'a>(
+    _frame_updates: &'a FrameUpdate,
+    _entity_net_metadata: EntityNetMetadata,
+    _entity_net_metadata_service: &'a EntityNetMetadataStorage,
+) -> Option<(WorldPosition, MobAction<Entity>)> {
+    None
+}
@@ -1,9 +1,14 @@
 mod action_subsystem;
 mod dying;
+mod projectile_subsystem;
 mod spawner;
 
 pub use self::{
     action_subsystem::{ApplyMonsterActionNetArgs, MonsterActionSubsystem},
     dying::MonsterDyingSystem,
+    projectile_subsystem::{
+        MonsterProjectileSubsystem, MONSTER_PROJECTILE_LIFESPAN_SECS, MONSTER_PROJECTILE_RADIUS,
+        MONSTER_PROJECTILE_SPEED,
+    },
     spawner::MonsterSpawnerSystem,
 };
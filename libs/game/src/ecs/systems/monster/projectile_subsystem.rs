@@ -0,0 +1,235 @@
+use amethyst::{
+    core::Transform,
+    ecs::{
+        shred::{ResourceId, SystemData},
+        Entities, Entity, Join, ReadExpect, World, WriteExpect, WriteStorage,
+    },
+};
+use gv_core::profile_scope;
+
+use gv_core::{
+    ecs::{
+        components::{
+            damage_history::{DamageCause, DamageHistory, DamageHistoryEntry, DamageType},
+            missile::MonsterProjectile,
+            Dead, WorldPosition,
+        },
+        system_data::time::GameTimeService,
+    },
+    math::Vector2,
+};
+
+use crate::{
+    ecs::{
+        resources::{LevelObstacles, RangedAttacksToExecute, SpatialGrids},
+        system_data::GameStateHelper,
+        systems::rollback::{RollbackFrame, RollbackStage},
+    },
+    utils::entities::is_dead,
+};
+
+pub const MONSTER_PROJECTILE_SPEED: f32 = 240.0;
+pub const MONSTER_PROJECTILE_RADIUS: f32 = 5.0;
+pub const MONSTER_PROJECTILE_LIFESPAN_SECS: f32 = 3.0;
+
+/// Shoots projectiles for ranged attacks of monsters and moves them until they hit a player
+/// or an obstacle.
+pub struct MonsterProjectileSubsystem;
+
+impl<'s> RollbackStage<'s> for MonsterProjectileSubsystem {
+    type SystemData = MonsterProjectileSystemData<'s>;
+
+    fn prepare_frame(&mut self, mut system_data: Self::SystemData, frame_number: u64) {
+        system_data.forget_projectiles(frame_number);
+    }
+
+    fn run_frame(&mut self, mut system_data: Self::SystemData, frame: &mut RollbackFrame) {
+        system_data.spawn_projectiles(frame.frame_number);
+        system_data.process_physics(frame.frame_number);
+    }
+}
+
+#[derive(SystemData)]
+pub struct MonsterProjectileSystemData<'s> {
+    game_time_service: GameTimeService<'s>,
+    game_state_helper: GameStateHelper<'s>,
+    level_obstacles: ReadExpect<'s, LevelObstacles>,
+    entities: Entities<'s>,
+    transforms: WriteStorage<'s, Transform>,
+    monster_projectiles: WriteStorage<'s, MonsterProjectile>,
+    dead: WriteStorage<'s, Dead>,
+    damage_histories: WriteStorage<'s, DamageHistory>,
+    world_positions: WriteStorage<'s, WorldPosition>,
+    spatial_grids: ReadExpect<'s, SpatialGrids>,
+    ranged_attacks_to_execute: WriteExpect<'s, RangedAttacksToExecute>,
+}
+
+impl<'s> MonsterProjectileSystemData<'s> {
+    /// A resimulated frame may have a different outcome, so the projectiles shot during
+    /// the last run of this frame or the later ones are killed, unless they are shot again.
+    /// We also remove `Dead` components which were inserted during the last run of the frame.
+    fn forget_projectiles(&mut self, frame_number: u64) {
+        let game_frame_number = self.game_time_service.game_frame_number();
+        let dead = &mut self.dead;
+        for (entity, projectile) in (&self.entities, &self.monster_projectiles).join() {
+            if projectile.frame_spawned >= frame_number {
+                if !is_dead(entity, &*dead, frame_number) {
+                    let frame_acknowledged = frame_number.max(game_frame_number);
+                    dead.insert(entity, Dead::new(frame_number, frame_acknowledged))
+                        .expect("Expected to insert a Dead component");
+                }
+                continue;
+            }
+
+            let will_be_killed = dead
+                .get(entity)
+                .map_or(false, |dead| frame_number + 1 == dead.dead_since_frame);
+            if will_be_killed {
+                dead.remove(entity)
+                    .expect("Expected to remove Dead component");
+            }
+        }
+    }
+
+    pub fn spawn_projectiles(&mut self, frame_number: u64) {
+        profile_scope!("MonsterProjectileSubsystem::spawn_projectiles");
+        let MonsterProjectileSystemData {
+            entities,
+            transforms,
+            monster_projectiles,
+            dead,
+            world_positions,
+            ranged_attacks_to_execute,
+            ..
+        } = self;
+
+        for attack in ranged_attacks_to_execute.attacks.drain(..) {
+            let direction = attack.target_position - attack.position;
+            let direction = if direction.norm_squared() > 0.0 {
+                direction.normalize()
+            } else {
+                Vector2::new(1.0, 0.0)
+            };
+            let projectile = MonsterProjectile {
+                shooter: attack.shooter,
+                radius: MONSTER_PROJECTILE_RADIUS,
+                velocity: direction * MONSTER_PROJECTILE_SPEED,
+                frame_spawned: frame_number,
+                damage: attack.damage,
+                status_effect: attack.status_effect,
+            };
+
+            // The frame may be resimulated, so we reuse the projectile shot during the last run.
+            let already_shot = (&*entities, &*monster_projectiles)
+                .join()
+                .find(|(_, projectile)| {
+                    projectile.shooter == attack.shooter && projectile.frame_spawned == frame_number
+                })
+                .map(|(entity, _)| entity);
+            if let Some(projectile_entity) = already_shot {
+                monster_projectiles
+                    .insert(projectile_entity, projectile)
+                    .expect("Expected to insert a MonsterProjectile");
+                world_positions
+                    .insert(projectile_entity, WorldPosition::new(attack.position))
+                    .expect("Expected to insert a WorldPosition");
+                dead.remove(projectile_entity);
+            } else {
+                let mut transform = Transform::default();
+                transform.set_translation_xyz(attack.position.x, attack.position.y, 50.0);
+                entities
+                    .build_entity()
+                    .with(transform, transforms)
+                    .with(WorldPosition::new(attack.position), world_positions)
+                    .with(projectile, monster_projectiles)
+                    .build();
+            }
+        }
+    }
+
+    pub fn process_physics(&mut self, frame_number: u64) {
+        profile_scope!("MonsterProjectileSubsystem::process_physics");
+        let MonsterProjectileSystemData {
+            game_time_service,
+            game_state_helper,
+            level_obstacles,
+            entities,
+            monster_projectiles,
+            dead,
+            damage_histories,
+            world_positions,
+            spatial_grids,
+            ..
+        } = self;
+        let fixed_seconds = game_time_service.engine_time().fixed_seconds();
+
+        for (projectile_entity, projectile) in (&*entities, &*monster_projectiles).join() {
+            if projectile.frame_spawned > frame_number
+                || is_dead(projectile_entity, &*dead, frame_number)
+            {
+                continue;
+            }
+
+            let lifespan =
+                game_time_service.seconds_between_frames(frame_number, projectile.frame_spawned);
+            if lifespan > MONSTER_PROJECTILE_LIFESPAN_SECS {
+                kill(game_time_service, dead, projectile_entity, frame_number);
+                continue;
+            }
+
+            let projectile_position = world_positions
+                .get_mut(projectile_entity)
+                .expect("Expected a WorldPosition for a MonsterProjectile");
+            **projectile_position += projectile.velocity * fixed_seconds;
+            let projectile_position = **projectile_position;
+
+            let hit_player =
+                spatial_grids
+                    .players
+                    .find(projectile_position, projectile.radius, |player| {
+                        let radius = projectile.radius + player.radius;
+                        (projectile_position - player.position).norm_squared() < radius * radius
+                            && !is_dead(player.entity, &*dead, frame_number)
+                    });
+            if let Some(hit_player) = hit_player {
+                if game_state_helper.is_authoritative() {
+                    damage_histories
+                        .get_mut(hit_player.entity)
+                        .expect("Expected player's DamageHistory")
+                        .add_entry(
+                            frame_number,
+                            DamageHistoryEntry::new(
+                                projectile.damage,
+                                DamageType::Physical,
+                                DamageCause::Projectile,
+                                Some(projectile.shooter),
+                            )
+                            .with_status_effect(projectile.status_effect),
+                        );
+                }
+                kill(game_time_service, dead, projectile_entity, frame_number);
+                continue;
+            }
+
+            // Projectiles can't fly through obstacles either.
+            if level_obstacles.intersects(projectile_position, projectile.radius) {
+                kill(game_time_service, dead, projectile_entity, frame_number);
+            }
+        }
+    }
+}
+
+fn kill(
+    game_time_service: &GameTimeService,
+    dead: &mut WriteStorage<Dead>,
+    projectile_entity: Entity,
+    frame_number: u64,
+) {
+    let dead_since_frame = frame_number + 1;
+    let frame_acknowledged = dead_since_frame.max(game_time_service.game_frame_number());
+    dead.insert(
+        projectile_entity,
+        Dead::new(dead_since_frame, frame_acknowledged),
+    )
+    .expect("Expected to insert a Dead component");
+}
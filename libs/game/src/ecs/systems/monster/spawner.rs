@@ -97,11 +97,12 @@ impl<'s> MonsterSpawnerSystemData<'s> {
         let spawn_actions = self.get_spawn_actions(&spawn_actions);
 
         for spawn_action in spawn_actions {
-            let ghoul = self
+            let monster_definition = self
                 .monster_definitions
-                .0
-                .get("Ghoul")
-                .expect("Failed to get Ghoul monster definition")
+                .get(&spawn_action.monster)
+                .unwrap_or_else(|| {
+                    panic!("Failed to get {} monster definition", spawn_action.monster)
+                })
                 .clone();
 
             match spawn_action.spawn_type {
@@ -116,7 +117,7 @@ impl<'s> MonsterSpawnerSystemData<'s> {
                             frame_number,
                             action: MobAction::Idle,
                         },
-                        &ghoul,
+                        &monster_definition,
                         entity_net_id,
                     );
                 }
@@ -139,7 +140,7 @@ impl<'s> MonsterSpawnerSystemData<'s> {
                             frame_number,
                             position,
                             action,
-                            &ghoul,
+                            &monster_definition,
                             entity_net_id_range.as_mut().map(|entity_net_id_range| {
                                 entity_net_id_range
                                     .next()
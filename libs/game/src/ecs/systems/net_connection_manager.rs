@@ -147,9 +147,7 @@ impl<'s> System<'s> for NetConnectionManagerSystem {
                 + Duration::from_millis(PING_INTERVAL_MILLIS)
                 < Instant::now()
             {
-                connection_model
-                    .ping_pong_data
-                    .add_ping(ping_id, game_time_service.engine_time().frame_number());
+                connection_model.ping_pong_data.add_ping(ping_id);
                 transport.send_with_requirements(
                     connection_model.addr,
                     &ping_message(connection_model.session_id, ping_id),
@@ -283,6 +281,7 @@ impl NetConnectionManagerSystem {
                                 peer_frame_number,
                                 game_time_service.engine_time().frame_number(),
                                 game_time_service.game_frame_number(),
+                                game_time_service.engine_time().fixed_seconds(),
                             );
                             (None, None)
                         }
@@ -0,0 +1,102 @@
+use amethyst::ecs::{Entities, Join, ReadExpect, ReadStorage, System, WriteExpect};
+
+use gv_core::{
+    ecs::{
+        components::{Dead, Monster, Player, WorldPosition},
+        resources::{GameEngineState, GameLevelState, GamePause, NewGameEngineState},
+        system_data::time::GameTimeService,
+    },
+    math::Vector2,
+};
+
+use crate::{
+    ecs::resources::{ActiveObjective, LevelObjective, WaveScript},
+    utils::entities::is_dead,
+};
+
+/// Completes a campaign level once its objective is met.
+///
+/// Doesn't fetch `GameStateHelper`, as the helper reads `NewGameEngineState`, which this system
+/// writes. Objectives are set only for single player games, so there's no need to check
+/// whether the game is authoritative.
+pub struct LevelObjectiveSystem;
+
+impl<'s> System<'s> for LevelObjectiveSystem {
+    type SystemData = (
+        GameTimeService<'s>,
+        Entities<'s>,
+        ReadStorage<'s, Monster>,
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, WorldPosition>,
+        ReadStorage<'s, Dead>,
+        ReadExpect<'s, ActiveObjective>,
+        ReadExpect<'s, WaveScript>,
+        ReadExpect<'s, GameLevelState>,
+        ReadExpect<'s, GamePause>,
+        ReadExpect<'s, GameEngineState>,
+        WriteExpect<'s, NewGameEngineState>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_time_service,
+            entities,
+            monsters,
+            players,
+            world_positions,
+            dead,
+            active_objective,
+            wave_script,
+            game_level_state,
+            game_pause,
+            game_engine_state,
+            mut new_game_engine_state,
+        ): Self::SystemData,
+    ) {
+        let is_running = *game_engine_state == GameEngineState::Playing
+            && new_game_engine_state.0 == GameEngineState::Playing
+            && !game_pause.is_paused;
+        if !is_running {
+            return;
+        }
+        let objective = match active_objective.0 {
+            Some(ref objective) => objective,
+            None => return,
+        };
+        let frame_number = game_time_service.game_frame_number();
+
+        let is_complete = match objective {
+            LevelObjective::SurviveWaves(waves) => {
+                let waves_started = game_level_state.wave.map_or(0, |wave| wave + 1);
+                waves_started > *waves
+                    || (waves_started == *waves
+                        && wave_script.is_running_wave_spawned(&game_level_state)
+                        && (&entities, &monsters)
+                            .join()
+                            .all(|(monster, _)| is_dead(monster, &dead, frame_number)))
+            }
+            LevelObjective::KillBoss(boss) => {
+                (&entities, &monsters)
+                    .join()
+                    .any(|(monster_entity, monster)| {
+                        monster.name == *boss && is_dead(monster_entity, &dead, frame_number)
+                    })
+            }
+            LevelObjective::ReachArea { center, radius } => {
+                let center = Vector2::new(center.0, center.1);
+                (&entities, &players, &world_positions).join().any(
+                    |(player_entity, _, player_position)| {
+                        !is_dead(player_entity, &dead, frame_number)
+                            && (**player_position - center).norm_squared() < radius * radius
+                    },
+                )
+            }
+        };
+
+        if is_complete {
+            log::info!("The level objective is met at frame {}", frame_number);
+            new_game_engine_state.0 = GameEngineState::LevelComplete;
+        }
+    }
+}
@@ -1,16 +1,26 @@
 use amethyst::ecs::{ReadExpect, System, WriteExpect};
 
-use gv_core::ecs::resources::{net::MultiplayerGameState, GameTime};
+use gv_core::ecs::resources::{net::MultiplayerGameState, GamePause, GameTime, SimulationTicks};
 
 pub struct PauseSystem;
 
 impl<'s> System<'s> for PauseSystem {
     type SystemData = (
         ReadExpect<'s, MultiplayerGameState>,
+        ReadExpect<'s, GamePause>,
+        ReadExpect<'s, SimulationTicks>,
         WriteExpect<'s, GameTime>,
     );
 
-    fn run(&mut self, (multiplayer_game_state, mut game_time): Self::SystemData) {
+    fn run(
+        &mut self,
+        (multiplayer_game_state, game_pause, simulation_ticks, mut game_time): Self::SystemData,
+    ) {
+        // Skipped frames are counted in ticks, like game frames are.
+        if !simulation_ticks.is_ticking {
+            return;
+        }
+
         if multiplayer_game_state.waiting_network {
             game_time.frames_skipped += 1;
             log::info!(
@@ -23,6 +33,8 @@ impl<'s> System<'s> for PauseSystem {
                 "Skipping a frame, reason: waiting for players (skipped: {})",
                 game_time.frames_skipped
             );
+        } else if !multiplayer_game_state.is_playing && game_pause.is_paused {
+            game_time.frames_skipped += 1;
         }
     }
 }
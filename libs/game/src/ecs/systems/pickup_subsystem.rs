@@ -0,0 +1,464 @@
+use amethyst::{
+    core::Transform,
+    ecs::{
+        shred::{ResourceId, SystemData},
+        Entities, Entity, Join, ReadExpect, ReadStorage, World, WriteExpect, WriteStorage,
+    },
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+#[cfg(not(feature = "client"))]
+use gv_core::net::NetUpdate;
+use gv_core::{
+    ecs::{
+        components::{
+            pickup::{
+                Pickup, PickupKind, PickupSpawn, DAMAGE_BOOST_SECS, HEALTH_ORB_HEALING,
+                PICKUP_LIFESPAN_SECS, PICKUP_RADIUS, SPEED_BUFF_SECS,
+            },
+            Dead, EntityNetMetadata, Monster, Player, WorldPosition,
+        },
+        resources::net::EntityNetMetadataStorage,
+        system_data::time::GameTimeService,
+    },
+    math::Vector2,
+    net::NetIdentifier,
+    profile_scope,
+};
+
+use crate::{
+    ecs::{
+        resources::{MonsterDefinitions, SpatialGrids},
+        system_data::GameStateHelper,
+        systems::{
+            rollback::{RollbackFrame, RollbackStage},
+            FrameUpdate, OutcomingNetUpdates,
+        },
+    },
+    utils::entities::is_dead,
+};
+
+/// Drops pickups from dying monsters and lets players collect them. Drops are rolled and
+/// collections are detected only by an authoritative side, clients apply them from
+/// server updates.
+pub struct PickupSubsystem;
+
+impl<'s> RollbackStage<'s> for PickupSubsystem {
+    type SystemData = PickupSystemData<'s>;
+
+    fn prepare_frame(&mut self, mut system_data: Self::SystemData, frame_number: u64) {
+        system_data.forget_pickups(frame_number);
+    }
+
+    fn run_frame(&mut self, mut system_data: Self::SystemData, frame: &mut RollbackFrame) {
+        let is_authoritative = system_data.game_state_helper.is_authoritative();
+        if is_authoritative {
+            system_data.drop_pickups(frame.frame_number, frame.outcoming_net_updates);
+        } else {
+            system_data.spawn_received_pickups(frame.frame_number, frame.updates);
+        }
+        system_data.expire_pickups(frame.frame_number);
+        if is_authoritative {
+            system_data.collect_pickups(frame.frame_number, frame.outcoming_net_updates);
+        } else {
+            system_data.collect_received_pickups(frame.frame_number, frame.updates);
+        }
+    }
+}
+
+#[derive(SystemData)]
+pub struct PickupSystemData<'s> {
+    game_time_service: GameTimeService<'s>,
+    game_state_helper: GameStateHelper<'s>,
+    monster_definitions: ReadExpect<'s, MonsterDefinitions>,
+    entities: Entities<'s>,
+    transforms: WriteStorage<'s, Transform>,
+    entity_net_metadata_storage: WriteExpect<'s, EntityNetMetadataStorage>,
+    entity_net_metadata: WriteStorage<'s, EntityNetMetadata>,
+    players: WriteStorage<'s, Player>,
+    monsters: ReadStorage<'s, Monster>,
+    pickups: WriteStorage<'s, Pickup>,
+    dead: WriteStorage<'s, Dead>,
+    world_positions: WriteStorage<'s, WorldPosition>,
+    spatial_grids: ReadExpect<'s, SpatialGrids>,
+}
+
+impl<'s> PickupSystemData<'s> {
+    /// A resimulated frame may have a different outcome, so the pickups dropped during
+    /// the last run of this frame or the later ones are killed, unless they are dropped again.
+    /// Pickups collected or expired during the last run of the frame are revived.
+    fn forget_pickups(&mut self, frame_number: u64) {
+        let game_frame_number = self.game_time_service.game_frame_number();
+        let dead = &mut self.dead;
+        for (entity, pickup) in (&self.entities, &self.pickups).join() {
+            if pickup.frame_spawned >= frame_number {
+                if !is_dead(entity, &*dead, frame_number) {
+                    let frame_acknowledged = frame_number.max(game_frame_number);
+                    dead.insert(entity, Dead::new(frame_number, frame_acknowledged))
+                        .expect("Expected to insert a Dead component");
+                }
+                continue;
+            }
+
+            let will_be_killed = dead
+                .get(entity)
+                .map_or(false, |dead| frame_number + 1 == dead.dead_since_frame);
+            if will_be_killed {
+                dead.remove(entity)
+                    .expect("Expected to remove Dead component");
+            }
+        }
+    }
+
+    pub fn drop_pickups(
+        &mut self,
+        frame_number: u64,
+        outcoming_net_updates: &mut OutcomingNetUpdates,
+    ) {
+        profile_scope!("PickupSubsystem::drop_pickups");
+        let monster_definitions = &self.monster_definitions;
+        let drops: Vec<(Entity, PickupKind)> = (&self.entities, &self.monsters, &self.dead)
+            .join()
+            // Monsters which have been killed during this frame.
+            .filter(|(_, _, dead)| dead.dead_since_frame == frame_number + 1)
+            .filter_map(|(monster_entity, monster, _)| {
+                let monster_definition =
+                    monster_definitions.get(&monster.name).unwrap_or_else(|| {
+                        panic!("Expected a monster definition for {}", monster.name)
+                    });
+                // The roll doesn't change if the frame is resimulated.
+                let mut rng =
+                    StdRng::seed_from_u64((u64::from(monster_entity.id()) << 32) ^ frame_number);
+                monster_definition
+                    .roll_drop(rng.gen())
+                    .map(|kind| (monster_entity, kind))
+            })
+            .collect();
+
+        for (monster_entity, kind) in drops {
+            let position = **self
+                .world_positions
+                .get(monster_entity)
+                .expect("Expected a WorldPosition for a Monster");
+
+            // The frame may be resimulated, so we reuse the pickup dropped during the last run.
+            let already_dropped = (&self.entities, &self.pickups)
+                .join()
+                .find(|(_, pickup)| {
+                    pickup.dropped_by == Some(monster_entity)
+                        && pickup.frame_spawned == frame_number
+                })
+                .map(|(entity, _)| entity);
+            let pickup_entity = self.spawn_pickup(
+                frame_number,
+                kind,
+                position,
+                Some(monster_entity),
+                already_dropped,
+            );
+
+            if self.game_state_helper.is_multiplayer() {
+                let registered_net_id = self
+                    .entity_net_metadata
+                    .get(pickup_entity)
+                    .map(|entity_net_metadata| entity_net_metadata.id);
+                let entity_net_id = registered_net_id
+                    .unwrap_or_else(|| self.register_net_id(pickup_entity, frame_number, None));
+                add_pickup_spawn_net_update(
+                    outcoming_net_updates,
+                    entity_net_id,
+                    PickupSpawn { kind, position },
+                );
+            }
+        }
+    }
+
+    pub fn expire_pickups(&mut self, frame_number: u64) {
+        profile_scope!("PickupSubsystem::expire_pickups");
+        let PickupSystemData {
+            game_time_service,
+            entities,
+            pickups,
+            dead,
+            ..
+        } = self;
+
+        for (pickup_entity, pickup) in (&*entities, &*pickups).join() {
+            if pickup.frame_spawned > frame_number || is_dead(pickup_entity, &*dead, frame_number) {
+                continue;
+            }
+            let lifespan =
+                game_time_service.seconds_between_frames(frame_number, pickup.frame_spawned);
+            if lifespan > PICKUP_LIFESPAN_SECS {
+                kill(game_time_service, dead, pickup_entity, frame_number);
+            }
+        }
+    }
+
+    pub fn collect_pickups(
+        &mut self,
+        frame_number: u64,
+        outcoming_net_updates: &mut OutcomingNetUpdates,
+    ) {
+        profile_scope!("PickupSubsystem::collect_pickups");
+        let PickupSystemData {
+            game_time_service,
+            game_state_helper,
+            entities,
+            entity_net_metadata,
+            players,
+            pickups,
+            dead,
+            world_positions,
+            spatial_grids,
+            ..
+        } = self;
+
+        for (pickup_entity, pickup, pickup_position) in
+            (&*entities, &*pickups, &*world_positions).join()
+        {
+            if pickup.frame_spawned > frame_number || is_dead(pickup_entity, &*dead, frame_number) {
+                continue;
+            }
+
+            let pickup_position = **pickup_position;
+            let collected_by =
+                spatial_grids
+                    .players
+                    .find(pickup_position, pickup.radius, |player| {
+                        let radius = pickup.radius + player.radius;
+                        (pickup_position - player.position).norm_squared() < radius * radius
+                            && !is_dead(player.entity, &*dead, frame_number)
+                    });
+            if let Some(collected_by) = collected_by {
+                apply_pickup(
+                    game_time_service,
+                    players
+                        .get_mut(collected_by.entity)
+                        .expect("Expected a Player in the spatial grid"),
+                    pickup.kind,
+                    frame_number,
+                );
+                kill(game_time_service, dead, pickup_entity, frame_number);
+
+                if game_state_helper.is_multiplayer() {
+                    let net_id = |entity| {
+                        entity_net_metadata
+                            .get(entity)
+                            .expect("Expected EntityNetMetadata in multiplayer")
+                            .id
+                    };
+                    add_pickup_collection_net_update(
+                        outcoming_net_updates,
+                        net_id(pickup_entity),
+                        net_id(collected_by.entity),
+                    );
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "client")]
+    fn spawn_received_pickups(&mut self, frame_number: u64, frame_updates: &FrameUpdate) {
+        for net_update in &frame_updates.pickup_spawns {
+            let existing_entity = self
+                .entity_net_metadata_storage
+                .get_entity(net_update.entity_net_id);
+            let pickup_entity = self.spawn_pickup(
+                frame_number,
+                net_update.data.kind,
+                net_update.data.position,
+                None,
+                existing_entity,
+            );
+            if existing_entity.is_none() {
+                self.register_net_id(pickup_entity, frame_number, Some(net_update.entity_net_id));
+            }
+        }
+    }
+
+    #[cfg(not(feature = "client"))]
+    fn spawn_received_pickups(&mut self, _frame_number: u64, _frame_updates: &FrameUpdate) {}
+
+    #[cfg(feature = "client")]
+    fn collect_received_pickups(&mut self, frame_number: u64, frame_updates: &FrameUpdate) {
+        let PickupSystemData {
+            game_time_service,
+            entity_net_metadata_storage,
+            players,
+            pickups,
+            dead,
+            ..
+        } = self;
+
+        for net_update in &frame_updates.pickup_collections {
+            let pickup_entity = entity_net_metadata_storage.get_entity(net_update.entity_net_id);
+            let player_entity = entity_net_metadata_storage.get_entity(net_update.data);
+            let (pickup_entity, player_entity) = match (pickup_entity, player_entity) {
+                (Some(pickup_entity), Some(player_entity)) => (pickup_entity, player_entity),
+                _ => {
+                    log::error!(
+                        "Couldn't find a pickup (net id: {}) or a player (net id: {}) to collect it",
+                        net_update.entity_net_id,
+                        net_update.data
+                    );
+                    continue;
+                }
+            };
+            let pickup = pickups
+                .get(pickup_entity)
+                .expect("Expected a Pickup component");
+            apply_pickup(
+                game_time_service,
+                players
+                    .get_mut(player_entity)
+                    .expect("Expected a Player component"),
+                pickup.kind,
+                frame_number,
+            );
+            kill(game_time_service, dead, pickup_entity, frame_number);
+        }
+    }
+
+    #[cfg(not(feature = "client"))]
+    fn collect_received_pickups(&mut self, _frame_number: u64, _frame_updates: &FrameUpdate) {}
+
+    fn spawn_pickup(
+        &mut self,
+        frame_number: u64,
+        kind: PickupKind,
+        position: Vector2,
+        dropped_by: Option<Entity>,
+        existing_entity: Option<Entity>,
+    ) -> Entity {
+        let pickup = Pickup {
+            kind,
+            dropped_by,
+            radius: PICKUP_RADIUS,
+            frame_spawned: frame_number,
+        };
+
+        if let Some(pickup_entity) = existing_entity {
+            self.pickups
+                .insert(pickup_entity, pickup)
+                .expect("Expected to insert a Pickup");
+            self.world_positions
+                .insert(pickup_entity, WorldPosition::new(position))
+                .expect("Expected to insert a WorldPosition");
+            self.dead.remove(pickup_entity);
+            pickup_entity
+        } else {
+            let mut transform = Transform::default();
+            transform.set_translation_xyz(position.x, position.y, 5.0);
+            self.entities
+                .build_entity()
+                .with(transform, &mut self.transforms)
+                .with(WorldPosition::new(position), &mut self.world_positions)
+                .with(pickup, &mut self.pickups)
+                .build()
+        }
+    }
+
+    /// Registers a new net id if `entity_net_id` is `None`.
+    fn register_net_id(
+        &mut self,
+        pickup_entity: Entity,
+        frame_number: u64,
+        entity_net_id: Option<NetIdentifier>,
+    ) -> NetIdentifier {
+        let entity_net_metadata_storage = &mut self.entity_net_metadata_storage;
+        let entity_net_id = match entity_net_id {
+            Some(entity_net_id) => {
+                entity_net_metadata_storage.set_net_id(pickup_entity, entity_net_id);
+                entity_net_id
+            }
+            None => entity_net_metadata_storage.register_new_entity(pickup_entity),
+        };
+        self.entity_net_metadata
+            .insert(
+                pickup_entity,
+                EntityNetMetadata {
+                    id: entity_net_id,
+                    spawned_frame_number: frame_number,
+                },
+            )
+            .expect("Expected to insert EntityNetMetadata");
+        entity_net_id
+    }
+}
+
+fn apply_pickup(
+    game_time_service: &GameTimeService,
+    player: &mut Player,
+    kind: PickupKind,
+    frame_number: u64,
+) {
+    match kind {
+        PickupKind::HealthOrb => {
+            player.health = (player.health + HEALTH_ORB_HEALING).min(player.max_health);
+        }
+        PickupKind::DamageBoost => {
+            player.damage_boost_until_frame =
+                frame_number + game_time_service.frames_in_seconds(DAMAGE_BOOST_SECS);
+        }
+        PickupKind::SpeedBuff => {
+            player.speed_buff_until_frame =
+                frame_number + game_time_service.frames_in_seconds(SPEED_BUFF_SECS);
+        }
+    }
+}
+
+fn kill(
+    game_time_service: &GameTimeService,
+    dead: &mut WriteStorage<Dead>,
+    pickup_entity: Entity,
+    frame_number: u64,
+) {
+    let dead_since_frame = frame_number + 1;
+    let frame_acknowledged = dead_since_frame.max(game_time_service.game_frame_number());
+    dead.insert(
+        pickup_entity,
+        Dead::new(dead_since_frame, frame_acknowledged),
+    )
+    .expect("Expected to insert a Dead component");
+}
+
+#[cfg(feature = "client")]
+fn add_pickup_spawn_net_update(
+    _outcoming_net_updates: &mut OutcomingNetUpdates,
+    _entity_net_id: NetIdentifier,
+    _pickup_spawn: PickupSpawn,
+) {
+}
+
+#[cfg(not(feature = "client"))]
+fn add_pickup_spawn_net_update(
+    outcoming_net_updates: &mut OutcomingNetUpdates,
+    entity_net_id: NetIdentifier,
+    pickup_spawn: PickupSpawn,
+) {
+    outcoming_net_updates.pickup_spawns.push(NetUpdate {
+        entity_net_id,
+        data: pickup_spawn,
+    });
+}
+
+#[cfg(feature = "client")]
+fn add_pickup_collection_net_update(
+    _outcoming_net_updates: &mut OutcomingNetUpdates,
+    _pickup_net_id: NetIdentifier,
+    _player_net_id: NetIdentifier,
+) {
+}
+
+#[cfg(not(feature = "client"))]
+fn add_pickup_collection_net_update(
+    outcoming_net_updates: &mut OutcomingNetUpdates,
+    pickup_net_id: NetIdentifier,
+    player_net_id: NetIdentifier,
+) {
+    outcoming_net_updates.pickup_collections.push(NetUpdate {
+        entity_net_id: pickup_net_id,
+        data: player_net_id,
+    });
+}
@@ -1,12 +1,13 @@
 use amethyst::{
     core::math::clamp,
-    ecs::{Entities, Entity, Join, ReadExpect, ReadStorage},
+    ecs::{
+        shred::{ResourceId, SystemData},
+        Entities, Entity, Join, ReadExpect, ReadStorage, World, WriteExpect, WriteStorage,
+    },
 };
 use rand::seq::SliceRandom;
 
-use std::time::Duration;
-
-use gv_animation_prefabs::{AnimationId, MAGE_TORSO};
+use gv_animation_prefabs::MAGE_TORSO;
 #[cfg(not(feature = "client"))]
 use gv_core::net::NetUpdateWithPosition;
 use gv_core::{
@@ -16,11 +17,14 @@ use gv_core::{
     },
     ecs::{
         components::{
-            missile::Missile, ClientPlayerActions, Player, PlayerActions, PlayerLastCastedSpells,
-            WorldPosition,
+            missile::Missile, status_effect::StatusEffects, ClientPlayerActions, Dead,
+            EntityNetMetadata, Player, PlayerActions, PlayerLastCastedSpells, WorldPosition,
         },
         resources::{
-            net::{ActionUpdateIdProvider, CastActionsToExecute, MultiplayerGameState},
+            net::{
+                ActionUpdateIdProvider, CastActionToExecute, CastActionsToExecute,
+                MultiplayerGameState,
+            },
             GameLevelState,
         },
         system_data::time::GameTimeService,
@@ -28,33 +32,43 @@ use gv_core::{
     math::{Vector2, ZeroVector},
     net::{NetIdentifier, NetUpdate},
     profile_scope,
+    progression::MANA_REGEN_PER_SEC,
 };
 
-use crate::ecs::{
-    system_data::GameStateHelper,
-    systems::{
-        AnimationsResourceBundle, ClientFrameUpdate, OutcomingNetUpdates, WriteExpectCell,
-        WriteStorageCell,
+use crate::{
+    ecs::{
+        resources::{LevelObstacles, SpellDefinition, SpellDefinitions},
+        system_data::GameStateHelper,
+        systems::{
+            rollback::{RollbackFrame, RollbackStage},
+            AnimationsSystemData, ClientFrameUpdate, FrameUpdate, OutcomingNetUpdates,
+        },
     },
+    utils::entities::is_dead,
 };
 
-const MISSILE_CAST_COOLDOWN: Duration = Duration::from_millis(500);
-
-pub struct PlayerActionSubsystem<'a, 's> {
-    pub game_time_service: &'s GameTimeService<'s>,
-    pub game_state_helper: &'s GameStateHelper<'s>,
-    pub entities: &'s Entities<'s>,
-    pub game_level_state: &'s ReadExpect<'s, GameLevelState>,
-    pub multiplayer_game_state: &'s ReadExpect<'s, MultiplayerGameState>,
-    pub client_player_actions: &'s ReadStorage<'s, ClientPlayerActions>,
-    pub action_update_id_provider: WriteExpectCell<'s, ActionUpdateIdProvider>,
-    pub cast_actions_to_execute: WriteExpectCell<'s, CastActionsToExecute>,
-    pub player_actions: WriteStorageCell<'s, PlayerActions>,
-    pub player_last_casted_spells: WriteStorageCell<'s, PlayerLastCastedSpells>,
-    pub missiles: WriteStorageCell<'s, Missile>,
-    pub world_positions: WriteStorageCell<'s, WorldPosition>,
-    #[cfg_attr(not(feature = "client"), allow(dead_code))]
-    pub animations_resource_bundle: &'a AnimationsResourceBundle<'s>,
+pub struct PlayerActionSubsystem;
+
+#[derive(SystemData)]
+pub struct PlayerActionSystemData<'s> {
+    game_time_service: GameTimeService<'s>,
+    game_state_helper: GameStateHelper<'s>,
+    entities: Entities<'s>,
+    game_level_state: ReadExpect<'s, GameLevelState>,
+    level_obstacles: ReadExpect<'s, LevelObstacles>,
+    spell_definitions: ReadExpect<'s, SpellDefinitions>,
+    multiplayer_game_state: ReadExpect<'s, MultiplayerGameState>,
+    client_player_actions: ReadStorage<'s, ClientPlayerActions>,
+    action_update_id_provider: WriteExpect<'s, ActionUpdateIdProvider>,
+    cast_actions_to_execute: WriteExpect<'s, CastActionsToExecute>,
+    entity_net_metadata: ReadStorage<'s, EntityNetMetadata>,
+    dead: ReadStorage<'s, Dead>,
+    player_actions: WriteStorage<'s, PlayerActions>,
+    player_last_casted_spells: WriteStorage<'s, PlayerLastCastedSpells>,
+    missiles: ReadStorage<'s, Missile>,
+    status_effects: ReadStorage<'s, StatusEffects>,
+    world_positions: WriteStorage<'s, WorldPosition>,
+    animations: AnimationsSystemData<'s>,
 }
 
 pub struct ApplyWalkActionNetArgs<'a> {
@@ -78,11 +92,98 @@ pub struct ApplyCastActionNetArgs<'a> {
     pub update: Option<IdentifiableAction<ClientActionUpdate<PlayerCastAction>>>,
 }
 
-const PLAYER_SPEED: f32 = 200.0;
+impl<'s> RollbackStage<'s> for PlayerActionSubsystem {
+    type SystemData = (WriteStorage<'s, Player>, PlayerActionSystemData<'s>);
 
-impl<'a, 's> PlayerActionSubsystem<'a, 's> {
+    fn run_frame(&mut self, system_data: Self::SystemData, frame: &mut RollbackFrame) {
+        let (mut players, mut system_data) = system_data;
+        let frame_number = frame.frame_number;
+        let dead = &system_data.dead;
+        let players_net_metadata = &system_data.entity_net_metadata;
+        for (entity, player, player_net_metadata) in (&system_data.entities, &mut players)
+            .join()
+            .filter(|(entity, player)| {
+                !is_dead(*entity, dead, frame_number) && !player.is_downed(frame_number)
+            })
+            .map(|(entity, player)| (entity, player, players_net_metadata.get(entity).cloned()))
+            .collect::<Vec<_>>()
+        {
+            player.mana = (player.mana
+                + MANA_REGEN_PER_SEC * system_data.game_time_service.engine_time().fixed_seconds())
+            .min(player.max_mana);
+
+            // Run walk action.
+            let net_args = if system_data.multiplayer_game_state.is_playing {
+                let player_net_metadata =
+                    player_net_metadata.expect("Expected EntityNetMetadata for a player");
+                let updates = walk_action_update_for_player(frame.updates, player_net_metadata);
+
+                Some(ApplyWalkActionNetArgs {
+                    entity_net_id: player_net_metadata.id,
+                    outcoming_net_updates: frame.outcoming_net_updates,
+                    updates,
+                })
+            } else {
+                None
+            };
+            system_data.apply_walk_action(
+                frame_number,
+                entity,
+                player,
+                net_args,
+                frame.client_side_actions,
+            );
+
+            // Run look action.
+            let net_args = if system_data.multiplayer_game_state.is_playing {
+                let player_net_metadata =
+                    player_net_metadata.expect("Expected EntityNetMetadata for a player");
+                let update = look_action_update_for_player(frame.updates, player_net_metadata);
+
+                Some(ApplyLookActionNetArgs {
+                    entity_net_id: player_net_metadata.id,
+                    outcoming_net_updates: frame.outcoming_net_updates,
+                    update,
+                })
+            } else {
+                None
+            };
+            system_data.apply_look_action(
+                frame_number,
+                entity,
+                player,
+                net_args,
+                frame.client_side_actions,
+            );
+
+            // Run cast action.
+            let net_args = if system_data.multiplayer_game_state.is_playing {
+                let player_net_metadata =
+                    player_net_metadata.expect("Expected EntityNetMetadata for a player");
+                let update = cast_action_update_for_player(frame.updates, player_net_metadata);
+
+                Some(ApplyCastActionNetArgs {
+                    entity_net_id: player_net_metadata.id,
+                    outcoming_net_updates: frame.outcoming_net_updates,
+                    update,
+                })
+            } else {
+                None
+            };
+            system_data.apply_cast_action(
+                frame_number,
+                entity,
+                player,
+                net_args,
+                frame.client_side_actions,
+            );
+        }
+    }
+}
+
+impl<'s> PlayerActionSystemData<'s> {
     pub fn apply_walk_action<'n>(
-        &self,
+        &mut self,
         frame_number: u64,
         entity: Entity,
         player: &mut Player,
@@ -90,24 +191,33 @@ impl<'a, 's> PlayerActionSubsystem<'a, 's> {
         client_side_actions: &mut ClientFrameUpdate,
     ) {
         profile_scope!("PlayerActionSubsystem::apply_walk_action");
-        let mut world_positions = self.world_positions.borrow_mut();
+        let PlayerActionSystemData {
+            game_time_service,
+            game_level_state,
+            level_obstacles,
+            multiplayer_game_state,
+            client_player_actions,
+            action_update_id_provider,
+            player_actions,
+            status_effects,
+            world_positions,
+            ..
+        } = self;
         let player_position = world_positions
             .get_mut(entity)
             .expect("Expected a WorldPosition");
-
-        let mut player_actions = self.player_actions.borrow_mut();
         let player_actions = player_actions
             .get_mut(entity)
             .expect("Expected player actions");
 
-        let client_player_actions = self.client_player_actions.get(entity);
+        let client_player_actions = client_player_actions.get(entity);
         let new_client_walk_action = client_player_actions
             .as_ref()
             .map(|actions| actions.walk_action.clone());
         let is_controllable = new_client_walk_action.is_some();
-        let is_latest_frame = self.game_time_service.game_frame_number() == frame_number;
+        let is_latest_frame = game_time_service.game_frame_number() == frame_number;
 
-        if self.multiplayer_game_state.is_playing {
+        if multiplayer_game_state.is_playing {
             let ApplyWalkActionNetArgs {
                 entity_net_id,
                 outcoming_net_updates,
@@ -121,7 +231,9 @@ impl<'a, 's> PlayerActionSubsystem<'a, 's> {
 
             // Decide which source has an actual update and retrieve it.
             let updated_walk_action = updates.map(|(_, updated_action)| updated_action);
-            let walk_action_update = self.actual_walk_action_update(
+            let walk_action_update = actual_walk_action_update(
+                game_time_service,
+                action_update_id_provider,
                 frame_number,
                 updated_walk_action,
                 &player_actions.walk_action,
@@ -141,7 +253,7 @@ impl<'a, 's> PlayerActionSubsystem<'a, 's> {
                 player_actions.walk_action = walk_action_update.action.clone();
 
                 // Add to network broadcasted updates.
-                self.add_walk_action_net_update(
+                add_walk_action_net_update(
                     outcoming_net_updates,
                     entity_net_id,
                     player_position.clone(),
@@ -159,15 +271,19 @@ impl<'a, 's> PlayerActionSubsystem<'a, 's> {
         if let PlayerWalkAction::Walk { direction } = &player_actions.walk_action {
             player.walking_direction = *direction;
             player.velocity = if *direction != Vector2::zero() {
-                direction.normalize() * PLAYER_SPEED
+                let speed_multiplier = status_effects.get(entity).map_or(1.0, |status_effects| {
+                    status_effects.speed_multiplier(frame_number)
+                });
+                direction.normalize() * player.current_speed(frame_number) * speed_multiplier
             } else {
                 Vector2::zero()
             };
-            **player_position +=
-                player.velocity * self.game_time_service.engine_time().fixed_seconds();
+            **player_position += player.velocity * game_time_service.engine_time().fixed_seconds();
 
-            let scene_half_size_x = self.game_level_state.dimensions.x / 2.0;
-            let scene_half_size_y = self.game_level_state.dimensions.y / 2.0;
+            // Obstacles may push a player out of the level, so its bounds are applied last.
+            **player_position = level_obstacles.resolve(**player_position, player.radius);
+            let scene_half_size_x = game_level_state.dimensions.x / 2.0;
+            let scene_half_size_y = game_level_state.dimensions.y / 2.0;
             player_position.x = clamp(player_position.x, -scene_half_size_x, scene_half_size_x);
             player_position.y = clamp(player_position.y, -scene_half_size_y, scene_half_size_y);
         } else {
@@ -176,7 +292,7 @@ impl<'a, 's> PlayerActionSubsystem<'a, 's> {
     }
 
     pub fn apply_look_action<'n>(
-        &self,
+        &mut self,
         frame_number: u64,
         entity: Entity,
         player: &mut Player,
@@ -184,26 +300,35 @@ impl<'a, 's> PlayerActionSubsystem<'a, 's> {
         client_side_actions: &mut ClientFrameUpdate,
     ) {
         profile_scope!("PlayerActionSubsystem::apply_look_action");
-        let mut player_actions = self.player_actions.borrow_mut();
+        let PlayerActionSystemData {
+            game_time_service,
+            multiplayer_game_state,
+            client_player_actions,
+            action_update_id_provider,
+            player_actions,
+            ..
+        } = self;
         let player_actions = player_actions
             .get_mut(entity)
             .expect("Expected player actions");
 
-        let client_player_actions = self.client_player_actions.get(entity);
+        let client_player_actions = client_player_actions.get(entity);
         let new_client_look_action = client_player_actions
             .as_ref()
             .map(|actions| actions.look_action.clone());
         let is_controllable = new_client_look_action.is_some();
-        let is_latest_frame = self.game_time_service.game_frame_number() == frame_number;
+        let is_latest_frame = game_time_service.game_frame_number() == frame_number;
 
-        if self.multiplayer_game_state.is_playing {
+        if multiplayer_game_state.is_playing {
             let ApplyLookActionNetArgs {
                 entity_net_id,
                 outcoming_net_updates,
                 update: updated_look_action,
             } = net_args.expect("Expected ApplyLookActionNetArgs in multiplayer");
             // Decide which source has an actual update and retrieve it.
-            let look_action_update = self.actual_look_action_update(
+            let look_action_update = actual_look_action_update(
+                game_time_service,
+                action_update_id_provider,
                 frame_number,
                 updated_look_action,
                 &player_actions.look_action,
@@ -223,7 +348,7 @@ impl<'a, 's> PlayerActionSubsystem<'a, 's> {
                 player_actions.look_action = look_action_update.action.clone();
 
                 // Add to network broadcasted updates.
-                self.add_look_action_net_update(
+                add_look_action_net_update(
                     outcoming_net_updates,
                     entity_net_id,
                     look_action_update,
@@ -242,42 +367,51 @@ impl<'a, 's> PlayerActionSubsystem<'a, 's> {
     }
 
     pub fn apply_cast_action<'n>(
-        &self,
+        &mut self,
         frame_number: u64,
         entity: Entity,
+        player: &mut Player,
         mut net_args: Option<ApplyCastActionNetArgs<'n>>,
         _client_side_actions: &mut ClientFrameUpdate,
     ) {
         profile_scope!("PlayerActionSubsystem::apply_cast_action");
-        let mut player_actions = self.player_actions.borrow_mut();
+        let PlayerActionSystemData {
+            game_time_service,
+            game_state_helper,
+            entities,
+            spell_definitions,
+            multiplayer_game_state,
+            client_player_actions,
+            action_update_id_provider,
+            cast_actions_to_execute,
+            player_actions,
+            player_last_casted_spells,
+            missiles,
+            status_effects,
+            world_positions,
+            animations,
+            ..
+        } = self;
         let player_actions = player_actions
             .get_mut(entity)
             .expect("Expected player actions");
 
-        let mut player_last_casted_spells = self.player_last_casted_spells.borrow_mut();
         let player_last_casted_spells = player_last_casted_spells
             .get_mut(entity)
             .expect("Expected PlayerLastCastedSpells component");
 
-        let mut world_positions = self.world_positions.borrow_mut();
         let player_position = world_positions
             .get_mut(entity)
             .expect("Expected a WorldPosition")
             .clone();
 
-        let mut cast_actions_to_execute = self.cast_actions_to_execute.borrow_mut();
-
-        let client_player_actions = self.client_player_actions.get(entity);
+        let client_player_actions = client_player_actions.get(entity);
 
-        let is_latest_frame = self.game_time_service.game_frame_number() == frame_number;
-        let is_cooling_down = self
-            .game_time_service
-            .seconds_between_frames(frame_number, player_last_casted_spells.missile)
-            < MISSILE_CAST_COOLDOWN.as_secs_f32();
+        let is_latest_frame = game_time_service.game_frame_number() == frame_number;
 
         player_actions.cast_action = None;
 
-        if self.multiplayer_game_state.is_playing {
+        if multiplayer_game_state.is_playing {
             let ApplyCastActionNetArgs {
                 entity_net_id,
                 outcoming_net_updates,
@@ -291,7 +425,28 @@ impl<'a, 's> PlayerActionSubsystem<'a, 's> {
                 action: mut cast_action,
             }) = cast_action_update.clone()
             {
-                if !is_cooling_down || !self.game_state_helper.is_authoritative() {
+                let spell = match spell_definitions.get(&cast_action.action.spell) {
+                    Some(spell) => spell,
+                    None => {
+                        log::warn!(
+                            "Ignoring a cast update ({}) for {} with an unknown spell {}",
+                            action_id,
+                            entity_net_id,
+                            cast_action.action.spell
+                        );
+                        return;
+                    }
+                };
+                let can_cast = can_cast(
+                    game_time_service,
+                    status_effects,
+                    frame_number,
+                    entity,
+                    player,
+                    spell,
+                    player_last_casted_spells,
+                );
+                if can_cast || !game_state_helper.is_authoritative() {
                     log::trace!(
                         "Applying a new cast update ({}) for {} (frame {}): {:?}",
                         action_id,
@@ -301,44 +456,42 @@ impl<'a, 's> PlayerActionSubsystem<'a, 's> {
                     );
                 }
 
-                if self.game_state_helper.is_authoritative() && !is_cooling_down {
+                if game_state_helper.is_authoritative() && can_cast {
                     // Update player actions.
-                    player_last_casted_spells.missile = frame_number;
+                    player_last_casted_spells.set_frame_number(&spell.id, frame_number);
+                    player.mana -= spell.cost;
                     cast_action.action.cast_position = *player_position;
                     player_actions.cast_action = Some(cast_action.action.clone());
 
                     // Add to network broadcasted updates.
-                    self.add_cast_action_net_update(
+                    add_cast_action_net_update(
                         outcoming_net_updates,
                         *entity_net_id,
                         Some(action_id),
                         cast_action,
                     );
-                } else if !self.game_state_helper.is_authoritative() {
-                    player_last_casted_spells.missile = frame_number;
+                } else if !game_state_helper.is_authoritative() {
+                    player_last_casted_spells.set_frame_number(&spell.id, frame_number);
+                    player.mana = (player.mana - spell.cost).max(0.0);
                     player_actions.cast_action = Some(cast_action.action);
                 }
 
                 if let Some(cast_action) = &player_actions.cast_action {
-                    if let Some(missile) = self.already_casted_missile(action_id) {
+                    if let Some(missile) = already_casted_missile(entities, missiles, action_id) {
                         let missile_position = world_positions
                             .get_mut(missile)
                             .expect("Expected a WorldPosition for a Missile");
                         **missile_position = cast_action.cast_position;
                     } else {
-                        cast_actions_to_execute.actions.push(IdentifiableAction {
-                            action_id,
-                            action: cast_action.clone(),
+                        cast_actions_to_execute.actions.push(CastActionToExecute {
+                            caster: entity,
+                            damage: spell.damage * player.current_damage_multiplier(frame_number),
+                            action: IdentifiableAction {
+                                action_id,
+                                action: cast_action.clone(),
+                            },
                         });
-
-                        let animation_id = [AnimationId::Spell1, AnimationId::Spell2]
-                            .choose(&mut rand::thread_rng())
-                            .unwrap();
-                        self.animations_resource_bundle.play_animation(
-                            entity,
-                            MAGE_TORSO,
-                            *animation_id,
-                        );
+                        play_cast_animation(animations, entity, spell);
                     }
 
                     return;
@@ -349,8 +502,21 @@ impl<'a, 's> PlayerActionSubsystem<'a, 's> {
         if let Some(client_player_actions) = client_player_actions.cloned() {
             if is_latest_frame {
                 if let Some(mut cast_action) = client_player_actions.cast_action {
-                    if !is_cooling_down {
-                        if self.multiplayer_game_state.is_playing {
+                    let spell = spell_definitions.get(&cast_action.spell);
+                    let can_cast = spell.map_or(false, |spell| {
+                        can_cast(
+                            game_time_service,
+                            status_effects,
+                            frame_number,
+                            entity,
+                            player,
+                            spell,
+                            player_last_casted_spells,
+                        )
+                    });
+                    if can_cast {
+                        let spell = spell.unwrap();
+                        if multiplayer_game_state.is_playing {
                             let ApplyCastActionNetArgs {
                                 entity_net_id,
                                 outcoming_net_updates,
@@ -363,11 +529,9 @@ impl<'a, 's> PlayerActionSubsystem<'a, 's> {
                                 &cast_action
                             );
 
-                            let mut action_update_id_provider =
-                                self.action_update_id_provider.borrow_mut();
                             cast_action.cast_position = *player_position;
 
-                            self.add_cast_action_net_update(
+                            add_cast_action_net_update(
                                 outcoming_net_updates,
                                 entity_net_id,
                                 None,
@@ -383,257 +547,376 @@ impl<'a, 's> PlayerActionSubsystem<'a, 's> {
                                 frame_number,
                                 &cast_action
                             );
-                            cast_actions_to_execute.actions.push(IdentifiableAction {
-                                action_id: 0,
-                                action: cast_action.clone(),
+                            player.mana -= spell.cost;
+                            cast_actions_to_execute.actions.push(CastActionToExecute {
+                                caster: entity,
+                                damage: spell.damage
+                                    * player.current_damage_multiplier(frame_number),
+                                action: IdentifiableAction {
+                                    action_id: 0,
+                                    action: cast_action.clone(),
+                                },
                             });
-
-                            let animation_id = [AnimationId::Spell1, AnimationId::Spell2]
-                                .choose(&mut rand::thread_rng())
-                                .unwrap();
-                            self.animations_resource_bundle.play_animation(
-                                entity,
-                                MAGE_TORSO,
-                                *animation_id,
-                            );
+                            play_cast_animation(animations, entity, spell);
                         }
 
                         player_actions.cast_action = Some(cast_action);
                     }
                 }
             }
-            if player_actions.cast_action.is_some() {
-                player_last_casted_spells.missile = frame_number;
-            }
-        }
-    }
-
-    #[cfg(feature = "client")]
-    fn actual_walk_action_update(
-        &self,
-        frame_number: u64,
-        updated_player_action: Option<ClientActionUpdate<PlayerWalkAction>>,
-        current_walk_action: &PlayerWalkAction,
-        new_client_walk_action: Option<PlayerWalkAction>,
-        client_side_actions: &mut ClientFrameUpdate,
-        entity_net_id: NetIdentifier,
-    ) -> Option<ClientActionUpdate<PlayerWalkAction>> {
-        if let Some(new_client_walk_action) = new_client_walk_action {
-            if self.game_time_service.game_frame_number() == frame_number {
-                let mut action_update_id_provider = self.action_update_id_provider.borrow_mut();
-                if *current_walk_action != new_client_walk_action {
-                    let client_action_id = action_update_id_provider.next_update_id();
-                    let client_action_update = ClientActionUpdate {
-                        client_action_id,
-                        action: new_client_walk_action,
-                    };
-                    client_side_actions.walk_action_updates.push(NetUpdate {
-                        entity_net_id,
-                        data: client_action_update.clone(),
-                    });
-                    return Some(client_action_update);
-                }
+            if let Some(cast_action) = &player_actions.cast_action {
+                player_last_casted_spells.set_frame_number(&cast_action.spell, frame_number);
             }
         }
-        updated_player_action.or_else(|| {
-            client_side_actions
-                .walk_action_updates
-                .iter()
-                .find(|action_update| action_update.entity_net_id == entity_net_id)
-                .map(|client_side_action| client_side_action.data.clone())
-        })
-    }
-
-    #[cfg(not(feature = "client"))]
-    fn actual_walk_action_update(
-        &self,
-        _frame_number: u64,
-        updated_player_action: Option<ClientActionUpdate<PlayerWalkAction>>,
-        _current_walk_action: &PlayerWalkAction,
-        _new_client_walk_action: Option<PlayerWalkAction>,
-        _client_side_actions: &mut ClientFrameUpdate,
-        _entity_net_id: NetIdentifier,
-    ) -> Option<ClientActionUpdate<PlayerWalkAction>> {
-        updated_player_action
     }
+}
 
-    #[cfg(feature = "client")]
-    fn add_walk_action_net_update(
-        &self,
-        outcoming_net_updates: &mut OutcomingNetUpdates,
-        entity_net_id: NetIdentifier,
-        _player_position: WorldPosition,
-        walk_action_update: ClientActionUpdate<PlayerWalkAction>,
-        is_controllable: bool,
-        is_latest_frame: bool,
-    ) {
-        if is_controllable && is_latest_frame {
-            outcoming_net_updates.walk_action_updates.push(NetUpdate {
-                entity_net_id,
-                data: walk_action_update,
+/// Spells can't be cast while they are cooling down, without enough mana or while stunned.
+fn can_cast(
+    game_time_service: &GameTimeService,
+    status_effects: &ReadStorage<StatusEffects>,
+    frame_number: u64,
+    entity: Entity,
+    player: &Player,
+    spell: &SpellDefinition,
+    player_last_casted_spells: &PlayerLastCastedSpells,
+) -> bool {
+    let is_stunned = status_effects.get(entity).map_or(false, |status_effects| {
+        status_effects.is_stunned(frame_number)
+    });
+    let is_cooling_down =
+        player_last_casted_spells
+            .frame_number(&spell.id)
+            .map_or(false, |last_casted_frame| {
+                game_time_service.seconds_between_frames(frame_number, last_casted_frame)
+                    < spell.cooldown_secs * player.cast_cooldown_multiplier
             });
-        }
-    }
+    !is_stunned && !is_cooling_down && player.mana >= spell.cost
+}
 
-    #[cfg(not(feature = "client"))]
-    fn add_walk_action_net_update(
-        &self,
-        outcoming_net_updates: &mut OutcomingNetUpdates,
-        entity_net_id: NetIdentifier,
-        player_position: WorldPosition,
-        walk_action_update: ClientActionUpdate<PlayerWalkAction>,
-        _is_controllable: bool,
-        _is_latest_frame: bool,
-    ) {
-        outcoming_net_updates
-            .player_walk_actions_updates
-            .push(NetUpdateWithPosition {
-                entity_net_id,
-                position: player_position,
-                data: walk_action_update,
-            });
+fn play_cast_animation(
+    animations: &mut AnimationsSystemData,
+    entity: Entity,
+    spell: &SpellDefinition,
+) {
+    if let Some(animation_id) = spell.animations.choose(&mut rand::thread_rng()) {
+        animations.play_animation(entity, MAGE_TORSO, *animation_id);
     }
+}
 
-    #[cfg(feature = "client")]
-    fn actual_look_action_update(
-        &self,
-        frame_number: u64,
-        updated_player_action: Option<ClientActionUpdate<PlayerLookAction>>,
-        current_look_action: &PlayerLookAction,
-        new_client_look_action: Option<PlayerLookAction>,
-        client_side_actions: &mut ClientFrameUpdate,
-        entity_net_id: NetIdentifier,
-    ) -> Option<ClientActionUpdate<PlayerLookAction>> {
-        if let Some(new_client_look_action) = new_client_look_action {
-            if self.game_time_service.game_frame_number() == frame_number {
-                let mut action_update_id_provider = self.action_update_id_provider.borrow_mut();
-                if *current_look_action != new_client_look_action {
-                    let client_action_id = action_update_id_provider.next_update_id();
-                    let client_action_update = ClientActionUpdate {
-                        client_action_id,
-                        action: new_client_look_action,
-                    };
-                    client_side_actions.look_action_updates.push(NetUpdate {
-                        entity_net_id,
-                        data: client_action_update.clone(),
-                    });
-                    return Some(client_action_update);
-                }
+#[cfg(feature = "client")]
+fn actual_walk_action_update(
+    game_time_service: &GameTimeService,
+    action_update_id_provider: &mut ActionUpdateIdProvider,
+    frame_number: u64,
+    updated_player_action: Option<ClientActionUpdate<PlayerWalkAction>>,
+    current_walk_action: &PlayerWalkAction,
+    new_client_walk_action: Option<PlayerWalkAction>,
+    client_side_actions: &mut ClientFrameUpdate,
+    entity_net_id: NetIdentifier,
+) -> Option<ClientActionUpdate<PlayerWalkAction>> {
+    if let Some(new_client_walk_action) = new_client_walk_action {
+        if game_time_service.game_frame_number() == frame_number {
+            if *current_walk_action != new_client_walk_action {
+                let client_action_id = action_update_id_provider.next_update_id();
+                let client_action_update = ClientActionUpdate {
+                    client_action_id,
+                    action: new_client_walk_action,
+                };
+                client_side_actions.walk_action_updates.push(NetUpdate {
+                    entity_net_id,
+                    data: client_action_update.clone(),
+                });
+                return Some(client_action_update);
             }
         }
-        updated_player_action.or_else(|| {
-            client_side_actions
-                .look_action_updates
-                .iter()
-                .find(|action_update| action_update.entity_net_id == entity_net_id)
-                .map(|client_side_action| client_side_action.data.clone())
-        })
     }
+    updated_player_action.or_else(|| {
+        client_side_actions
+            .walk_action_updates
+            .iter()
+            .find(|action_update| action_update.entity_net_id == entity_net_id)
+            .map(|client_side_action| client_side_action.data.clone())
+    })
+}
 
-    #[cfg(not(feature = "client"))]
-    fn actual_look_action_update(
-        &self,
-        _frame_number: u64,
-        updated_player_action: Option<ClientActionUpdate<PlayerLookAction>>,
-        _current_look_action: &PlayerLookAction,
-        _new_client_look_action: Option<PlayerLookAction>,
-        _client_side_actions: &mut ClientFrameUpdate,
-        _entity_net_id: NetIdentifier,
-    ) -> Option<ClientActionUpdate<PlayerLookAction>> {
-        updated_player_action
+#[cfg(not(feature = "client"))]
+fn actual_walk_action_update(
+    _game_time_service: &GameTimeService,
+    _action_update_id_provider: &mut ActionUpdateIdProvider,
+    _frame_number: u64,
+    updated_player_action: Option<ClientActionUpdate<PlayerWalkAction>>,
+    _current_walk_action: &PlayerWalkAction,
+    _new_client_walk_action: Option<PlayerWalkAction>,
+    _client_side_actions: &mut ClientFrameUpdate,
+    _entity_net_id: NetIdentifier,
+) -> Option<ClientActionUpdate<PlayerWalkAction>> {
+    updated_player_action
+}
+
+#[cfg(feature = "client")]
+fn add_walk_action_net_update(
+    outcoming_net_updates: &mut OutcomingNetUpdates,
+    entity_net_id: NetIdentifier,
+    _player_position: WorldPosition,
+    walk_action_update: ClientActionUpdate<PlayerWalkAction>,
+    is_controllable: bool,
+    is_latest_frame: bool,
+) {
+    if is_controllable && is_latest_frame {
+        outcoming_net_updates.walk_action_updates.push(NetUpdate {
+            entity_net_id,
+            data: walk_action_update,
+        });
     }
+}
 
-    #[cfg(feature = "client")]
-    fn add_look_action_net_update(
-        &self,
-        outcoming_net_updates: &mut OutcomingNetUpdates,
-        entity_net_id: NetIdentifier,
-        look_action_update: ClientActionUpdate<PlayerLookAction>,
-        frame_number: u64,
-        is_controllable: bool,
-        is_latest_frame: bool,
-    ) {
-        if is_controllable && is_latest_frame {
-            let has_last_frame = outcoming_net_updates
-                .look_actions_updates
-                .back()
-                .map_or(false, |(update_frame_number, _)| {
-                    *update_frame_number == frame_number
+#[cfg(not(feature = "client"))]
+fn add_walk_action_net_update(
+    outcoming_net_updates: &mut OutcomingNetUpdates,
+    entity_net_id: NetIdentifier,
+    player_position: WorldPosition,
+    walk_action_update: ClientActionUpdate<PlayerWalkAction>,
+    _is_controllable: bool,
+    _is_latest_frame: bool,
+) {
+    outcoming_net_updates
+        .player_walk_actions_updates
+        .push(NetUpdateWithPosition {
+            entity_net_id,
+            position: player_position,
+            data: walk_action_update,
+        });
+}
+
+#[cfg(feature = "client")]
+fn actual_look_action_update(
+    game_time_service: &GameTimeService,
+    action_update_id_provider: &mut ActionUpdateIdProvider,
+    frame_number: u64,
+    updated_player_action: Option<ClientActionUpdate<PlayerLookAction>>,
+    current_look_action: &PlayerLookAction,
+    new_client_look_action: Option<PlayerLookAction>,
+    client_side_actions: &mut ClientFrameUpdate,
+    entity_net_id: NetIdentifier,
+) -> Option<ClientActionUpdate<PlayerLookAction>> {
+    if let Some(new_client_look_action) = new_client_look_action {
+        if game_time_service.game_frame_number() == frame_number {
+            if *current_look_action != new_client_look_action {
+                let client_action_id = action_update_id_provider.next_update_id();
+                let client_action_update = ClientActionUpdate {
+                    client_action_id,
+                    action: new_client_look_action,
+                };
+                client_side_actions.look_action_updates.push(NetUpdate {
+                    entity_net_id,
+                    data: client_action_update.clone(),
                 });
-            if !has_last_frame {
-                outcoming_net_updates
-                    .look_actions_updates
-                    .push_back((frame_number, Vec::with_capacity(1)));
+                return Some(client_action_update);
             }
+        }
+    }
+    updated_player_action.or_else(|| {
+        client_side_actions
+            .look_action_updates
+            .iter()
+            .find(|action_update| action_update.entity_net_id == entity_net_id)
+            .map(|client_side_action| client_side_action.data.clone())
+    })
+}
+
+#[cfg(not(feature = "client"))]
+fn actual_look_action_update(
+    _game_time_service: &GameTimeService,
+    _action_update_id_provider: &mut ActionUpdateIdProvider,
+    _frame_number: u64,
+    updated_player_action: Option<ClientActionUpdate<PlayerLookAction>>,
+    _current_look_action: &PlayerLookAction,
+    _new_client_look_action: Option<PlayerLookAction>,
+    _client_side_actions: &mut ClientFrameUpdate,
+    _entity_net_id: NetIdentifier,
+) -> Option<ClientActionUpdate<PlayerLookAction>> {
+    updated_player_action
+}
 
+#[cfg(feature = "client")]
+fn add_look_action_net_update(
+    outcoming_net_updates: &mut OutcomingNetUpdates,
+    entity_net_id: NetIdentifier,
+    look_action_update: ClientActionUpdate<PlayerLookAction>,
+    frame_number: u64,
+    is_controllable: bool,
+    is_latest_frame: bool,
+) {
+    if is_controllable && is_latest_frame {
+        let has_last_frame = outcoming_net_updates
+            .look_actions_updates
+            .back()
+            .map_or(false, |(update_frame_number, _)| {
+                *update_frame_number == frame_number
+            });
+        if !has_last_frame {
             outcoming_net_updates
                 .look_actions_updates
-                .back_mut()
-                .unwrap()
-                .1
-                .push(NetUpdate {
-                    entity_net_id,
-                    data: look_action_update,
-                });
+                .push_back((frame_number, Vec::with_capacity(1)));
         }
-    }
 
-    #[cfg(not(feature = "client"))]
-    fn add_look_action_net_update(
-        &self,
-        outcoming_net_updates: &mut OutcomingNetUpdates,
-        entity_net_id: NetIdentifier,
-        look_action_update: ClientActionUpdate<PlayerLookAction>,
-        _frame_number: u64,
-        _is_controllable: bool,
-        _is_latest_frame: bool,
-    ) {
         outcoming_net_updates
-            .player_look_actions_updates
+            .look_actions_updates
+            .back_mut()
+            .unwrap()
+            .1
             .push(NetUpdate {
                 entity_net_id,
                 data: look_action_update,
             });
     }
+}
 
-    #[cfg(feature = "client")]
-    fn add_cast_action_net_update(
-        &self,
-        outcoming_net_updates: &mut OutcomingNetUpdates,
-        entity_net_id: NetIdentifier,
-        _action_id: Option<NetIdentifier>,
-        cast_action_update: ClientActionUpdate<PlayerCastAction>,
-    ) {
-        outcoming_net_updates.cast_action_updates.push(NetUpdate {
+#[cfg(not(feature = "client"))]
+fn add_look_action_net_update(
+    outcoming_net_updates: &mut OutcomingNetUpdates,
+    entity_net_id: NetIdentifier,
+    look_action_update: ClientActionUpdate<PlayerLookAction>,
+    _frame_number: u64,
+    _is_controllable: bool,
+    _is_latest_frame: bool,
+) {
+    outcoming_net_updates
+        .player_look_actions_updates
+        .push(NetUpdate {
             entity_net_id,
-            data: cast_action_update,
+            data: look_action_update,
         });
-    }
+}
 
-    #[cfg(not(feature = "client"))]
-    fn add_cast_action_net_update(
-        &self,
-        outcoming_net_updates: &mut OutcomingNetUpdates,
-        entity_net_id: NetIdentifier,
-        action_id: Option<NetIdentifier>,
-        cast_action_update: ClientActionUpdate<PlayerCastAction>,
-    ) {
-        outcoming_net_updates
-            .player_cast_actions_updates
-            .push(NetUpdate {
-                entity_net_id,
-                data: IdentifiableAction {
-                    action_id: action_id.expect("Expected an action id passed for server"),
-                    action: cast_action_update,
-                },
-            });
-    }
+#[cfg(feature = "client")]
+fn add_cast_action_net_update(
+    outcoming_net_updates: &mut OutcomingNetUpdates,
+    entity_net_id: NetIdentifier,
+    _action_id: Option<NetIdentifier>,
+    cast_action_update: ClientActionUpdate<PlayerCastAction>,
+) {
+    outcoming_net_updates.cast_action_updates.push(NetUpdate {
+        entity_net_id,
+        data: cast_action_update,
+    });
+}
 
-    fn already_casted_missile(&self, cast_action_id: NetIdentifier) -> Option<Entity> {
-        let missiles = self.missiles.borrow();
-        (&*missiles, self.entities)
-            .join()
-            .find(|(missile, _)| missile.action_id == cast_action_id)
-            .map(|(_, entity)| entity)
-    }
+#[cfg(not(feature = "client"))]
+fn add_cast_action_net_update(
+    outcoming_net_updates: &mut OutcomingNetUpdates,
+    entity_net_id: NetIdentifier,
+    action_id: Option<NetIdentifier>,
+    cast_action_update: ClientActionUpdate<PlayerCastAction>,
+) {
+    outcoming_net_updates
+        .player_cast_actions_updates
+        .push(NetUpdate {
+            entity_net_id,
+            data: IdentifiableAction {
+                action_id: action_id.expect("Expected an action id passed for server"),
+                action: cast_action_update,
+            },
+        });
+}
+
+fn already_casted_missile(
+    entities: &Entities,
+    missiles: &ReadStorage<Missile>,
+    cast_action_id: NetIdentifier,
+) -> Option<Entity> {
+    (missiles, entities)
+        .join()
+        .find(|(missile, _)| missile.action_id == cast_action_id)
+        .map(|(_, entity)| entity)
+}
+
+#[cfg(feature = "client")]
+fn walk_action_update_for_player(
+    frame_updates: &FrameUpdate,
+    entity_net_metadata: EntityNetMetadata,
+) -> Option<(Option<WorldPosition>, ClientActionUpdate<PlayerWalkAction>)> {
+    frame_updates
+        .player_updates
+        .player_walk_actions_updates
+        .iter()
+        .find(|actions_updates| actions_updates.entity_net_id == entity_net_metadata.id)
+        .or_else(|| {
+            frame_updates
+                .controlled_player_updates
+                .player_walk_actions_updates
+                .iter()
+                .find(|actions_updates| actions_updates.entity_net_id == entity_net_metadata.id)
+        })
+        .map(move |update| (Some(update.position.clone()), update.data.clone()))
+}
+
+#[cfg(not(feature = "client"))]
+fn walk_action_update_for_player(
+    frame_updates: &FrameUpdate,
+    entity_net_metadata: EntityNetMetadata,
+) -> Option<(Option<WorldPosition>, ClientActionUpdate<PlayerWalkAction>)> {
+    frame_updates
+        .walk_action_updates
+        .iter()
+        .find(|actions_updates| actions_updates.entity_net_id == entity_net_metadata.id)
+        .map(move |update| (None, update.data.clone()))
+}
+
+#[cfg(feature = "client")]
+fn look_action_update_for_player(
+    frame_updates: &FrameUpdate,
+    entity_net_metadata: EntityNetMetadata,
+) -> Option<ClientActionUpdate<PlayerLookAction>> {
+    frame_updates
+        .player_updates
+        .player_look_actions_updates
+        .iter()
+        .find(|actions_updates| actions_updates.entity_net_id == entity_net_metadata.id)
+        .map(move |update| update.data.clone())
+}
+
+#[cfg(not(feature = "client"))]
+fn look_action_update_for_player(
+    frame_updates: &FrameUpdate,
+    entity_net_metadata: EntityNetMetadata,
+) -> Option<ClientActionUpdate<PlayerLookAction>> {
+    frame_updates
+        .look_action_updates
+        .iter()
+        .find(|actions_updates| actions_updates.entity_net_id == entity_net_metadata.id)
+        .map(move |update| update.data.clone())
+}
+
+#[cfg(feature = "client")]
+fn cast_action_update_for_player(
+    frame_updates: &FrameUpdate,
+    entity_net_metadata: EntityNetMetadata,
+) -> Option<IdentifiableAction<ClientActionUpdate<PlayerCastAction>>> {
+    frame_updates
+        .player_updates
+        .player_cast_actions_updates
+        .iter()
+        .find(|actions_updates| actions_updates.entity_net_id == entity_net_metadata.id)
+        .or_else(|| {
+            frame_updates
+                .controlled_player_updates
+                .player_cast_actions_updates
+                .iter()
+                .find(|actions_updates| actions_updates.entity_net_id == entity_net_metadata.id)
+        })
+        .map(move |update| update.data.clone())
+}
+
+#[cfg(not(feature = "client"))]
+fn cast_action_update_for_player(
+    frame_updates: &FrameUpdate,
+    entity_net_metadata: EntityNetMetadata,
+) -> Option<IdentifiableAction<ClientActionUpdate<PlayerCastAction>>> {
+    frame_updates
+        .cast_action_updates
+        .iter()
+        .find(|actions_updates| actions_updates.entity_net_id == entity_net_metadata.id)
+        .map(move |update| update.data.clone())
 }
@@ -1,5 +1,12 @@
 mod action_subsystem;
+mod revive_subsystem;
 
-pub use self::action_subsystem::{
-    ApplyCastActionNetArgs, ApplyLookActionNetArgs, ApplyWalkActionNetArgs, PlayerActionSubsystem,
+pub use self::{
+    action_subsystem::{
+        ApplyCastActionNetArgs, ApplyLookActionNetArgs, ApplyWalkActionNetArgs,
+        PlayerActionSubsystem,
+    },
+    revive_subsystem::{
+        ReviveSubsystem, BLEED_OUT_SECS, REVIVE_HEALTH_FRACTION, REVIVE_RADIUS, REVIVE_SECS,
+    },
 };
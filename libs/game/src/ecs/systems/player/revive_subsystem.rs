@@ -0,0 +1,249 @@
+use amethyst::ecs::{
+    shred::{ResourceId, SystemData},
+    Entities, Entity, Join, ReadExpect, ReadStorage, World, WriteStorage,
+};
+
+#[cfg(not(feature = "client"))]
+use gv_core::net::NetUpdate;
+use gv_core::{
+    ecs::{
+        components::{Dead, EntityNetMetadata, Player, PlayerDownedUpdate, WorldPosition},
+        resources::net::EntityNetMetadataStorage,
+        system_data::time::GameTimeService,
+    },
+    net::NetIdentifier,
+    profile_scope,
+};
+
+use crate::{
+    ecs::{
+        system_data::GameStateHelper,
+        systems::{
+            rollback::{RollbackFrame, RollbackStage},
+            FrameUpdate, OutcomingNetUpdates,
+        },
+    },
+    utils::entities::is_dead,
+};
+
+pub const REVIVE_RADIUS: f32 = 60.0;
+pub const REVIVE_SECS: f32 = 3.0;
+pub const BLEED_OUT_SECS: f32 = 30.0;
+/// A revived player gets this part of their max health back.
+pub const REVIVE_HEALTH_FRACTION: f32 = 0.3;
+
+/// Revives downed players whose teammates stand close to them and kills the ones who
+/// bleed out. Only a server decides that, clients apply `PlayerDownedUpdate`s.
+pub struct ReviveSubsystem;
+
+impl<'s> RollbackStage<'s> for ReviveSubsystem {
+    type SystemData = ReviveSystemData<'s>;
+
+    fn run_frame(&mut self, mut system_data: Self::SystemData, frame: &mut RollbackFrame) {
+        // Players just die in single player.
+        if !system_data.game_state_helper.is_multiplayer() {
+            return;
+        }
+
+        system_data.forget_bleed_outs(frame.frame_number);
+        if system_data.game_state_helper.is_authoritative() {
+            system_data.process_downed_players(frame.frame_number, frame.outcoming_net_updates);
+        } else {
+            system_data.apply_incoming_net_updates(frame.frame_number, frame.updates);
+        }
+    }
+}
+
+#[derive(SystemData)]
+pub struct ReviveSystemData<'s> {
+    game_time_service: GameTimeService<'s>,
+    game_state_helper: GameStateHelper<'s>,
+    entities: Entities<'s>,
+    #[cfg_attr(not(feature = "client"), allow(dead_code))]
+    entity_net_metadata_storage: ReadExpect<'s, EntityNetMetadataStorage>,
+    entity_net_metadata: ReadStorage<'s, EntityNetMetadata>,
+    players: WriteStorage<'s, Player>,
+    dead: WriteStorage<'s, Dead>,
+    world_positions: ReadStorage<'s, WorldPosition>,
+}
+
+impl<'s> ReviveSystemData<'s> {
+    /// A resimulated frame may have a different outcome, so we remove `Dead` components
+    /// which were inserted during the last run of the frame.
+    fn forget_bleed_outs(&mut self, frame_number: u64) {
+        let dead = &mut self.dead;
+        for (entity, _) in (&self.entities, &self.players).join() {
+            let will_be_killed = dead
+                .get(entity)
+                .map_or(false, |dead| frame_number + 1 == dead.dead_since_frame);
+            if will_be_killed {
+                dead.remove(entity)
+                    .expect("Expected to remove Dead component");
+            }
+        }
+    }
+
+    pub fn process_downed_players(
+        &mut self,
+        frame_number: u64,
+        outcoming_net_updates: &mut OutcomingNetUpdates,
+    ) {
+        profile_scope!("ReviveSubsystem::process_downed_players");
+        let ReviveSystemData {
+            game_time_service,
+            entities,
+            entity_net_metadata,
+            players,
+            dead,
+            world_positions,
+            ..
+        } = self;
+
+        let revivers = (&*entities, &*players, &*world_positions)
+            .join()
+            .filter(|(entity, player, _)| {
+                !player.is_downed(frame_number) && !is_dead(*entity, &*dead, frame_number)
+            })
+            .map(|(entity, _, position)| (entity, **position))
+            .collect::<Vec<_>>();
+        let revive_frames = game_time_service.frames_in_seconds(REVIVE_SECS);
+        let bleed_out_frames = game_time_service.frames_in_seconds(BLEED_OUT_SECS);
+
+        for (entity, player, position) in (&*entities, &mut *players, &*world_positions).join() {
+            let downed_since_frame = match player.downed_since_frame {
+                Some(downed_since_frame) => downed_since_frame,
+                None => continue,
+            };
+            let entity_net_id = entity_net_metadata
+                .get(entity)
+                .expect("Expected EntityNetMetadata in multiplayer")
+                .id;
+            // Has been downed during this frame.
+            if downed_since_frame == frame_number + 1 {
+                add_downed_net_update(
+                    outcoming_net_updates,
+                    entity_net_id,
+                    PlayerDownedUpdate::Downed,
+                );
+                continue;
+            }
+            if is_dead(entity, &*dead, frame_number) {
+                continue;
+            }
+
+            let is_reviver_close = revivers.iter().any(|(reviver, reviver_position)| {
+                *reviver != entity
+                    && (**position - *reviver_position).norm_squared()
+                        < REVIVE_RADIUS * REVIVE_RADIUS
+            });
+            if is_reviver_close {
+                player.revive_frames += 1;
+            } else {
+                player.revive_frames = 0;
+            }
+
+            if player.revive_frames >= revive_frames {
+                log::debug!(
+                    "Player {} is revived at frame {}",
+                    entity_net_id,
+                    frame_number
+                );
+                revive(player);
+                add_downed_net_update(
+                    outcoming_net_updates,
+                    entity_net_id,
+                    PlayerDownedUpdate::Revived,
+                );
+            } else if frame_number - downed_since_frame >= bleed_out_frames {
+                log::debug!(
+                    "Player {} bleeds out at frame {}",
+                    entity_net_id,
+                    frame_number
+                );
+                kill(game_time_service, dead, entity, frame_number);
+                add_downed_net_update(
+                    outcoming_net_updates,
+                    entity_net_id,
+                    PlayerDownedUpdate::BledOut,
+                );
+            }
+        }
+    }
+
+    #[cfg(feature = "client")]
+    fn apply_incoming_net_updates(&mut self, frame_number: u64, frame_updates: &FrameUpdate) {
+        let ReviveSystemData {
+            game_time_service,
+            entity_net_metadata_storage,
+            players,
+            dead,
+            ..
+        } = self;
+
+        for net_update in &frame_updates.player_downed_updates {
+            let entity = entity_net_metadata_storage.get_entity(net_update.entity_net_id);
+            if entity.is_none() {
+                log::error!(
+                    "Couldn't find a player (net id: {}) to apply {:?}",
+                    net_update.entity_net_id,
+                    net_update.data
+                );
+                continue;
+            }
+            let entity = entity.unwrap();
+            let player = players
+                .get_mut(entity)
+                .expect("Expected a Player component");
+            match net_update.data {
+                PlayerDownedUpdate::Downed => {
+                    player.health = 0.0;
+                    player.downed_since_frame = Some(frame_number + 1);
+                    player.revive_frames = 0;
+                }
+                PlayerDownedUpdate::Revived => revive(player),
+                PlayerDownedUpdate::BledOut => kill(game_time_service, dead, entity, frame_number),
+            }
+        }
+    }
+
+    #[cfg(not(feature = "client"))]
+    fn apply_incoming_net_updates(&mut self, _frame_number: u64, _frame_updates: &FrameUpdate) {}
+}
+
+fn kill(
+    game_time_service: &GameTimeService,
+    dead: &mut WriteStorage<Dead>,
+    entity: Entity,
+    frame_number: u64,
+) {
+    let dead_since_frame = frame_number + 1;
+    let frame_acknowledged = dead_since_frame.max(game_time_service.game_frame_number());
+    dead.insert(entity, Dead::new(dead_since_frame, frame_acknowledged))
+        .expect("Expected to insert a Dead component");
+}
+
+fn revive(player: &mut Player) {
+    player.health = player.max_health * REVIVE_HEALTH_FRACTION;
+    player.downed_since_frame = None;
+    player.revive_frames = 0;
+}
+
+#[cfg(feature = "client")]
+fn add_downed_net_update(
+    _outcoming_net_updates: &mut OutcomingNetUpdates,
+    _entity_net_id: NetIdentifier,
+    _update: PlayerDownedUpdate,
+) {
+}
+
+#[cfg(not(feature = "client"))]
+fn add_downed_net_update(
+    outcoming_net_updates: &mut OutcomingNetUpdates,
+    entity_net_id: NetIdentifier,
+    update: PlayerDownedUpdate,
+) {
+    outcoming_net_updates.player_downed_updates.push(NetUpdate {
+        entity_net_id,
+        data: update,
+    });
+}
@@ -0,0 +1,307 @@
+use amethyst::ecs::{
+    shred::{ResourceId, SystemData},
+    Entity, World, WorldExt, WriteExpect, WriteStorage,
+};
+use gv_core::profile_scope;
+
+use gv_core::ecs::{
+    components::NetWorldPosition,
+    resources::world::{FramedUpdates, SavedWorldState, WorldStates},
+    system_data::time::GameTimeService,
+};
+
+use crate::{
+    ecs::{
+        system_data::GameStateHelper,
+        systems::{
+            world_state_subsystem::WorldStateSubsystem, AggregatedOutcomingUpdates,
+            ClientFrameUpdate, FrameUpdate, OutcomingNetUpdates,
+        },
+    },
+    utils::world::outcoming_net_updates_mut,
+};
+
+/// Everything a stage needs to know about a frame being (re)simulated.
+pub struct RollbackFrame<'f> {
+    pub frame_number: u64,
+    pub updates: &'f FrameUpdate,
+    pub client_side_actions: &'f mut ClientFrameUpdate,
+    pub outcoming_net_updates: &'f mut OutcomingNetUpdates,
+}
+
+/// A part of the simulation, which is run by `RollbackScheduler` for every (re)simulated frame.
+/// Stages fetch their `SystemData` for every call, so it mustn't include the resources
+/// the scheduler holds while running frames, see `RollbackSchedulerSystemData`.
+pub trait RollbackStage<'s> {
+    type SystemData: SystemData<'s>;
+
+    /// Is called for every stage before any of them runs the frame.
+    fn prepare_frame(&mut self, _system_data: Self::SystemData, _frame_number: u64) {}
+
+    fn run_frame(&mut self, system_data: Self::SystemData, frame: &mut RollbackFrame);
+}
+
+/// Lets `RollbackStages` keep stages with different `SystemData` types.
+trait DynRollbackStage<'s> {
+    fn setup(&mut self, world: &mut World);
+
+    fn reads(&self) -> Vec<ResourceId>;
+
+    fn writes(&self) -> Vec<ResourceId>;
+
+    fn prepare_frame(&mut self, world: &'s World, frame_number: u64);
+
+    fn run_frame(&mut self, world: &'s World, frame: &mut RollbackFrame);
+}
+
+impl<'s, T> DynRollbackStage<'s> for T
+where
+    T: RollbackStage<'s>,
+{
+    fn setup(&mut self, world: &mut World) {
+        <T::SystemData as SystemData<'s>>::setup(world);
+    }
+
+    fn reads(&self) -> Vec<ResourceId> {
+        <T::SystemData as SystemData<'s>>::reads()
+    }
+
+    fn writes(&self) -> Vec<ResourceId> {
+        <T::SystemData as SystemData<'s>>::writes()
+    }
+
+    fn prepare_frame(&mut self, world: &'s World, frame_number: u64) {
+        RollbackStage::prepare_frame(self, world.system_data(), frame_number);
+    }
+
+    fn run_frame(&mut self, world: &'s World, frame: &mut RollbackFrame) {
+        RollbackStage::run_frame(self, world.system_data(), frame);
+    }
+}
+
+/// The registry of rollback stages, which is taken from the world to build `ActionSystem`.
+/// The stages run in the order they were added.
+#[derive(Default)]
+pub struct RollbackStages {
+    stages: Vec<Box<dyn for<'s> DynRollbackStage<'s> + Send + Sync>>,
+}
+
+impl RollbackStages {
+    pub fn with_stage<S>(mut self, stage: S) -> Self
+    where
+        S: for<'s> RollbackStage<'s> + Send + Sync + 'static,
+    {
+        self.stages.push(Box::new(stage));
+        self
+    }
+}
+
+/// The resources `RollbackScheduler` holds while running frames.
+#[derive(SystemData)]
+struct RollbackSchedulerSystemData<'s> {
+    framed_updates: WriteExpect<'s, FramedUpdates<FrameUpdate>>,
+    framed_client_side_actions: WriteExpect<'s, FramedUpdates<ClientFrameUpdate>>,
+    world_states: WriteExpect<'s, WorldStates>,
+    aggregated_outcoming_updates: WriteExpect<'s, AggregatedOutcomingUpdates>,
+}
+
+/// The data the scheduler fetches only between running the stages.
+type SchedulerSystemData<'s> = (
+    GameTimeService<'s>,
+    GameStateHelper<'s>,
+    WorldStateSubsystem<'s>,
+    WriteStorage<'s, NetWorldPosition>,
+);
+
+/// Restores the world state of the oldest updated frame and resimulates the frames up to
+/// the current one, running the registered stages.
+pub struct RollbackScheduler {
+    stages: RollbackStages,
+}
+
+impl RollbackScheduler {
+    pub fn new(stages: RollbackStages) -> Self {
+        Self { stages }
+    }
+
+    pub fn setup(&mut self, world: &mut World) {
+        RollbackSchedulerSystemData::setup(world);
+        SchedulerSystemData::setup(world);
+        for stage in &mut self.stages.stages {
+            stage.setup(world);
+        }
+    }
+
+    pub fn reads(&self) -> Vec<ResourceId> {
+        let mut reads = RollbackSchedulerSystemData::reads();
+        reads.extend(SchedulerSystemData::reads());
+        for stage in &self.stages.stages {
+            reads.extend(stage.reads());
+        }
+        reads
+    }
+
+    pub fn writes(&self) -> Vec<ResourceId> {
+        let mut writes = RollbackSchedulerSystemData::writes();
+        writes.extend(SchedulerSystemData::writes());
+        for stage in &self.stages.stages {
+            writes.extend(stage.writes());
+        }
+        writes
+    }
+
+    /// Returns the number of frames that were resimulated (not counting the current one).
+    pub fn run(&mut self, world: &World) -> u64 {
+        profile_scope!("RollbackScheduler::run");
+        let (game_frame_number, interpolation_frame_delay, is_authoritative) = {
+            let (game_time_service, game_state_helper) =
+                world.system_data::<(GameTimeService, GameStateHelper)>();
+            (
+                game_time_service.game_frame_number(),
+                game_time_service.interpolation_frame_delay(),
+                game_state_helper.is_authoritative(),
+            )
+        };
+        let mut system_data = world.system_data::<RollbackSchedulerSystemData>();
+        let RollbackSchedulerSystemData {
+            framed_updates,
+            framed_client_side_actions,
+            world_states,
+            aggregated_outcoming_updates,
+        } = &mut system_data;
+
+        framed_updates.reserve_updates(game_frame_number);
+        framed_client_side_actions.reserve_updates(game_frame_number);
+
+        // We may update client actions when discarding updates in ClientNetworkSystem, but as
+        // we iterate though framed_updates, we should update its oldest_updated_frame as well.
+        framed_updates.oldest_updated_frame = framed_updates
+            .oldest_updated_frame
+            .min(framed_client_side_actions.oldest_updated_frame);
+
+        // Add a world state to save the components to, ensure the update is possible.
+        world_states.add_world_state();
+        world_states
+            .check_update_is_possible(framed_updates)
+            .unwrap_or_else(|err| {
+                panic!(
+                    "Expected an update to be possible (current frame {}): {:?}",
+                    game_frame_number, err
+                )
+            });
+
+        let oldest_updated_frame = framed_updates.oldest_updated_frame;
+
+        // Load NetWorldPositions from currently available saved world states.
+        let mut framed_net_positions: Vec<Vec<(Entity, NetWorldPosition)>> = if is_authoritative {
+            Vec::new()
+        } else {
+            let capacity = game_frame_number - oldest_updated_frame + 1;
+            let mut framed_net_positions = Vec::with_capacity(capacity as usize);
+            let mut world_states_iter = world_states
+                .states_iter(oldest_updated_frame.saturating_sub(interpolation_frame_delay));
+            // Filling with empty values as for the first interpolation_frame_delay frames
+            // we have zero data.
+            let zero_data_frames = interpolation_frame_delay
+                .saturating_sub(oldest_updated_frame)
+                .min(capacity);
+            for _ in 0..zero_data_frames {
+                framed_net_positions.push(Vec::new());
+            }
+            for _ in zero_data_frames..capacity {
+                let world_state = world_states_iter
+                    .next()
+                    .expect("Expected a world state while loading NetWorldPosition");
+                let net_positions = world_state
+                    .world_positions
+                    .iter()
+                    .cloned()
+                    .map(|(entity, world_position)| (entity, world_position.into()))
+                    .collect();
+                framed_net_positions.push(net_positions);
+            }
+            framed_net_positions
+        };
+
+        // Load the world state of the oldest updated frame.
+        let mut world_states_iter = world_states.states_iter_mut(oldest_updated_frame);
+        let mut world_state = world_states_iter.next().unwrap_or_else(|| {
+            panic!(
+                "Expected to store a world state for frame {}",
+                oldest_updated_frame,
+            )
+        });
+        world
+            .system_data::<WorldStateSubsystem>()
+            .load_from_world_state(world_state);
+
+        // Run each updated frame.
+        let mut client_side_actions_iter =
+            framed_client_side_actions.updates_iter_mut(oldest_updated_frame);
+        for frame_updated in framed_updates.iter_from_oldest_update() {
+            // Update no further than a current frame.
+            if game_frame_number < frame_updated.frame_number {
+                break;
+            }
+            let client_side_actions = client_side_actions_iter
+                .next()
+                .expect("Expected a framed client-side action");
+
+            if !is_authoritative {
+                SavedWorldState::load_storage_from(
+                    &mut world.write_storage::<NetWorldPosition>(),
+                    &framed_net_positions
+                        [(frame_updated.frame_number - oldest_updated_frame) as usize],
+                );
+            }
+
+            let mut frame = RollbackFrame {
+                frame_number: frame_updated.frame_number,
+                updates: frame_updated,
+                client_side_actions,
+                outcoming_net_updates: outcoming_net_updates_mut(
+                    aggregated_outcoming_updates,
+                    frame_updated.frame_number,
+                    game_frame_number,
+                ),
+            };
+            for stage in &mut self.stages.stages {
+                stage.prepare_frame(world, frame.frame_number);
+            }
+            for stage in &mut self.stages.stages {
+                stage.run_frame(world, &mut frame);
+            }
+
+            // Get the next world state and save the current world to it.
+            world_state = world_states_iter.next().unwrap_or_else(|| {
+                panic!(
+                    "Expected to store a world state for frame {}",
+                    frame_updated.frame_number,
+                )
+            });
+            world
+                .system_data::<WorldStateSubsystem>()
+                .save_world_state(world_state);
+
+            // Update net_positions if we're updating more than interpolation_frame_delay frames.
+            if frame_updated.frame_number - oldest_updated_frame >= interpolation_frame_delay
+                && !is_authoritative
+            {
+                let i =
+                    frame_updated.frame_number - oldest_updated_frame - interpolation_frame_delay;
+                framed_net_positions[i as usize] = world_state
+                    .world_positions
+                    .iter()
+                    .cloned()
+                    .map(|(entity, world_position)| (entity, world_position.into()))
+                    .collect();
+            }
+        }
+
+        drop(client_side_actions_iter);
+        framed_updates.oldest_updated_frame = game_frame_number + 1;
+        framed_client_side_actions.oldest_updated_frame = game_frame_number + 1;
+
+        game_frame_number.saturating_sub(oldest_updated_frame)
+    }
+}
@@ -0,0 +1,82 @@
+use amethyst::ecs::{
+    shred::{ResourceId, SystemData},
+    Entities, Join, ReadStorage, World, WriteExpect,
+};
+use gv_core::profile_scope;
+
+use gv_core::ecs::components::{Dead, EntityNetMetadata, Monster, Player, WorldPosition};
+
+use crate::{
+    ecs::{
+        resources::{SpatialGridEntry, SpatialGrids},
+        systems::rollback::{RollbackFrame, RollbackStage},
+    },
+    utils::entities::is_dead,
+};
+
+/// Rebuilds spatial grids from the current positions, so that the stages running after it
+/// can look for players and monsters nearby.
+pub struct SpatialGridsSubsystem;
+
+impl<'s> RollbackStage<'s> for SpatialGridsSubsystem {
+    type SystemData = SpatialGridsSystemData<'s>;
+
+    fn run_frame(&mut self, mut system_data: Self::SystemData, frame: &mut RollbackFrame) {
+        system_data.rebuild(frame.frame_number);
+    }
+}
+
+#[derive(SystemData)]
+pub struct SpatialGridsSystemData<'s> {
+    entities: Entities<'s>,
+    entity_net_metadata: ReadStorage<'s, EntityNetMetadata>,
+    players: ReadStorage<'s, Player>,
+    monsters: ReadStorage<'s, Monster>,
+    world_positions: ReadStorage<'s, WorldPosition>,
+    dead: ReadStorage<'s, Dead>,
+    spatial_grids: WriteExpect<'s, SpatialGrids>,
+}
+
+impl<'s> SpatialGridsSystemData<'s> {
+    pub fn rebuild(&mut self, frame_number: u64) {
+        profile_scope!("SpatialGridsSubsystem::rebuild");
+        self.rebuild_players(frame_number);
+        self.rebuild_monsters(frame_number);
+    }
+
+    /// Downed players are neither attacked by monsters nor collect pickups.
+    fn rebuild_players(&mut self, frame_number: u64) {
+        self.spatial_grids.players.rebuild(
+            (&self.entities, &self.players, &self.world_positions)
+                .join()
+                .filter(|(_, player, _)| !player.is_downed(frame_number))
+                .map(|(entity, player, player_position)| SpatialGridEntry {
+                    entity,
+                    position: **player_position,
+                    radius: player.radius,
+                }),
+        );
+    }
+
+    fn rebuild_monsters(&mut self, frame_number: u64) {
+        let entity_net_metadata = &self.entity_net_metadata;
+        let dead = &self.dead;
+        self.spatial_grids.monsters.rebuild(
+            (&self.entities, &self.monsters, &self.world_positions)
+                .join()
+                .filter(|(entity, _, _)| {
+                    let is_spawned = entity_net_metadata
+                        .get(*entity)
+                        .map_or(true, |net_metadata| {
+                            net_metadata.spawned_frame_number <= frame_number
+                        });
+                    is_spawned && !is_dead(*entity, dead, frame_number)
+                })
+                .map(|(entity, monster, monster_position)| SpatialGridEntry {
+                    entity,
+                    position: **monster_position,
+                    radius: monster.radius,
+                }),
+        );
+    }
+}
@@ -29,6 +29,7 @@ impl<'s> System<'s> for StateSwitcherSystem {
                 GameEngineState::Loading => unreachable!(),
                 GameEngineState::Menu => Trans::Switch(Box::new(MenuState)),
                 GameEngineState::Playing => Trans::Switch(Box::new(PlayingState)),
+                GameEngineState::LevelComplete => Trans::Switch(Box::new(LevelCompleteState)),
                 GameEngineState::ShuttingDown { shutdown_at } if Instant::now() > shutdown_at => {
                     Trans::Quit
                 }
@@ -0,0 +1,99 @@
+use amethyst::ecs::{
+    shred::{ResourceId, SystemData},
+    Entities, Join, ReadStorage, World, WriteStorage,
+};
+
+use gv_core::{
+    ecs::{
+        components::{
+            damage_history::{DamageCause, DamageHistory, DamageHistoryEntry},
+            status_effect::{StatusEffects, STATUS_EFFECT_TICK_SECS},
+            Dead, EntityNetMetadata,
+        },
+        system_data::time::GameTimeService,
+    },
+    profile_scope,
+};
+
+use crate::{
+    ecs::{
+        system_data::GameStateHelper,
+        systems::rollback::{RollbackFrame, RollbackStage},
+    },
+    utils::entities::is_dead,
+};
+
+/// Expires status effects and deals their damage over time. Effects themselves are applied
+/// by `DamageSubsystem` together with the damage entries which carry them.
+pub struct StatusEffectSubsystem;
+
+impl<'s> RollbackStage<'s> for StatusEffectSubsystem {
+    type SystemData = StatusEffectSystemData<'s>;
+
+    fn run_frame(&mut self, mut system_data: Self::SystemData, frame: &mut RollbackFrame) {
+        system_data.process_status_effects(frame.frame_number);
+    }
+}
+
+#[derive(SystemData)]
+pub struct StatusEffectSystemData<'s> {
+    game_state_helper: GameStateHelper<'s>,
+    game_time_service: GameTimeService<'s>,
+    entities: Entities<'s>,
+    entity_net_metadata: ReadStorage<'s, EntityNetMetadata>,
+    damage_histories: WriteStorage<'s, DamageHistory>,
+    status_effects: WriteStorage<'s, StatusEffects>,
+    dead: ReadStorage<'s, Dead>,
+}
+
+impl<'s> StatusEffectSystemData<'s> {
+    pub fn process_status_effects(&mut self, frame_number: u64) {
+        profile_scope!("StatusEffectSubsystem::process_status_effects");
+        let is_authoritative = self.game_state_helper.is_authoritative();
+        let tick_frames = self
+            .game_time_service
+            .frames_in_seconds(STATUS_EFFECT_TICK_SECS)
+            .max(1);
+
+        for (entity, status_effects, damage_history) in (
+            &self.entities,
+            &mut self.status_effects,
+            &mut self.damage_histories,
+        )
+            .join()
+        {
+            let is_spawned = self
+                .entity_net_metadata
+                .get(entity)
+                .map_or(true, |entity_net_metadata| {
+                    entity_net_metadata.spawned_frame_number <= frame_number
+                });
+            if !is_spawned || is_dead(entity, &self.dead, frame_number) {
+                continue;
+            }
+
+            status_effects.remove_expired(frame_number);
+
+            // Clients receive tick damage with the rest of damage entries.
+            if !is_authoritative {
+                continue;
+            }
+            for effect in status_effects.active(frame_number) {
+                let damage = effect.kind.tick_damage() * f32::from(effect.stacks);
+                let frames_active = frame_number - effect.started_frame;
+                if damage == 0.0 || frames_active == 0 || frames_active % tick_frames != 0 {
+                    continue;
+                }
+                damage_history.add_entry(
+                    frame_number,
+                    DamageHistoryEntry::new(
+                        damage,
+                        effect.kind.damage_type(),
+                        DamageCause::StatusEffect(effect.kind),
+                        effect.source,
+                    ),
+                );
+            }
+        }
+    }
+}
@@ -1,75 +1,109 @@
-use amethyst::ecs::Entities;
+use amethyst::ecs::{
+    shred::{ResourceId, SystemData},
+    Entities, World, WriteStorage,
+};
 
 use gv_core::ecs::{
     components::{
-        missile::Missile, Dead, Monster, Player, PlayerActions, PlayerLastCastedSpells,
-        WorldPosition,
+        missile::{Missile, MonsterProjectile},
+        pickup::Pickup,
+        status_effect::StatusEffects,
+        Dead, Monster, Player, PlayerActions, PlayerLastCastedSpells, WorldPosition,
     },
     resources::world::SavedWorldState,
 };
 
-use crate::ecs::systems::WriteStorageCell;
-
+/// The storages which are saved to world states, to be restored on rollbacks.
+#[derive(SystemData)]
 pub struct WorldStateSubsystem<'s> {
-    pub entities: &'s Entities<'s>,
-    pub players: WriteStorageCell<'s, Player>,
-    pub player_actions: WriteStorageCell<'s, PlayerActions>,
-    pub player_last_casted_spells: WriteStorageCell<'s, PlayerLastCastedSpells>,
-    pub monsters: WriteStorageCell<'s, Monster>,
-    pub missiles: WriteStorageCell<'s, Missile>,
-    pub world_positions: WriteStorageCell<'s, WorldPosition>,
-    pub dead: WriteStorageCell<'s, Dead>,
+    pub entities: Entities<'s>,
+    pub players: WriteStorage<'s, Player>,
+    pub player_actions: WriteStorage<'s, PlayerActions>,
+    pub player_last_casted_spells: WriteStorage<'s, PlayerLastCastedSpells>,
+    pub monsters: WriteStorage<'s, Monster>,
+    pub missiles: WriteStorage<'s, Missile>,
+    pub monster_projectiles: WriteStorage<'s, MonsterProjectile>,
+    pub pickups: WriteStorage<'s, Pickup>,
+    pub status_effects: WriteStorage<'s, StatusEffects>,
+    pub world_positions: WriteStorage<'s, WorldPosition>,
+    pub dead: WriteStorage<'s, Dead>,
 }
 
 impl<'s> WorldStateSubsystem<'s> {
     pub fn save_world_state(&self, saved_world_state: &mut SavedWorldState) {
-        saved_world_state.players =
-            SavedWorldState::copy_from_write_storage(&self.entities, &*self.players.borrow_mut());
-        saved_world_state.player_actions = SavedWorldState::copy_from_write_storage(
+        SavedWorldState::save_storage_to(
+            &mut saved_world_state.players,
+            &self.entities,
+            &self.players,
+        );
+        SavedWorldState::save_storage_to(
+            &mut saved_world_state.player_actions,
+            &self.entities,
+            &self.player_actions,
+        );
+        SavedWorldState::save_storage_to(
+            &mut saved_world_state.player_last_casted_spells,
+            &self.entities,
+            &self.player_last_casted_spells,
+        );
+        SavedWorldState::save_storage_to(
+            &mut saved_world_state.monsters,
             &self.entities,
-            &*self.player_actions.borrow_mut(),
+            &self.monsters,
         );
-        saved_world_state.player_last_casted_spells = SavedWorldState::copy_from_write_storage(
+        SavedWorldState::save_storage_to(
+            &mut saved_world_state.missiles,
             &self.entities,
-            &*self.player_last_casted_spells.borrow_mut(),
+            &self.missiles,
         );
-        saved_world_state.monsters =
-            SavedWorldState::copy_from_write_storage(&self.entities, &*self.monsters.borrow_mut());
-        saved_world_state.missiles =
-            SavedWorldState::copy_from_write_storage(&self.entities, &*self.missiles.borrow_mut());
-        saved_world_state.world_positions = SavedWorldState::copy_from_write_storage(
+        SavedWorldState::save_storage_to(
+            &mut saved_world_state.monster_projectiles,
             &self.entities,
-            &*self.world_positions.borrow_mut(),
+            &self.monster_projectiles,
         );
-        saved_world_state.dead =
-            SavedWorldState::copy_from_write_storage(&self.entities, &*self.dead.borrow_mut());
+        SavedWorldState::save_storage_to(
+            &mut saved_world_state.pickups,
+            &self.entities,
+            &self.pickups,
+        );
+        SavedWorldState::save_storage_to(
+            &mut saved_world_state.status_effects,
+            &self.entities,
+            &self.status_effects,
+        );
+        SavedWorldState::save_storage_to(
+            &mut saved_world_state.world_positions,
+            &self.entities,
+            &self.world_positions,
+        );
+        SavedWorldState::save_storage_to(&mut saved_world_state.dead, &self.entities, &self.dead);
     }
 
-    pub fn load_from_world_state(&self, saved_world_state: &SavedWorldState) {
-        SavedWorldState::load_storage_from(
-            &mut self.players.borrow_mut(),
-            &saved_world_state.players,
-        );
+    pub fn load_from_world_state(&mut self, saved_world_state: &SavedWorldState) {
+        SavedWorldState::load_storage_from(&mut self.players, &saved_world_state.players);
         SavedWorldState::load_storage_from(
-            &mut self.player_actions.borrow_mut(),
+            &mut self.player_actions,
             &saved_world_state.player_actions,
         );
         SavedWorldState::load_storage_from(
-            &mut self.player_last_casted_spells.borrow_mut(),
+            &mut self.player_last_casted_spells,
             &saved_world_state.player_last_casted_spells,
         );
+        SavedWorldState::load_storage_from(&mut self.monsters, &saved_world_state.monsters);
+        SavedWorldState::load_storage_from(&mut self.missiles, &saved_world_state.missiles);
         SavedWorldState::load_storage_from(
-            &mut self.monsters.borrow_mut(),
-            &saved_world_state.monsters,
+            &mut self.monster_projectiles,
+            &saved_world_state.monster_projectiles,
         );
+        SavedWorldState::load_storage_from(&mut self.pickups, &saved_world_state.pickups);
         SavedWorldState::load_storage_from(
-            &mut self.missiles.borrow_mut(),
-            &saved_world_state.missiles,
+            &mut self.status_effects,
+            &saved_world_state.status_effects,
         );
         SavedWorldState::load_storage_from(
-            &mut self.world_positions.borrow_mut(),
+            &mut self.world_positions,
             &saved_world_state.world_positions,
         );
-        SavedWorldState::load_storage_from(&mut self.dead.borrow_mut(), &saved_world_state.dead);
+        SavedWorldState::load_storage_from(&mut self.dead, &saved_world_state.dead);
     }
 }
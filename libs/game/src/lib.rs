@@ -7,6 +7,7 @@ pub mod states;
 pub mod utils;
 
 use amethyst::{
+    core::SystemDesc,
     error::Error,
     prelude::{GameDataBuilder, World},
 };
@@ -18,13 +19,26 @@ use gv_core::{
             ActionUpdateIdProvider, CastActionsToExecute, EntityNetMetadataStorage,
             MultiplayerGameState,
         },
+        stats::{ExperienceAwards, RunStats, RunSummary},
         world::{FramedUpdates, PlayerActionUpdates, WorldStates},
+        SimulationTicks,
     },
 };
 
-use crate::ecs::{
-    resources::ConnectionEvents,
-    systems::{missile::MissileDyingSystem, monster::*, *},
+use crate::{
+    ecs::{
+        resources::{
+            ActiveObjective, Campaign, ConnectionEvents, GameMode, LevelDefinition,
+            RangedAttacksToExecute, RollbackStats, SpatialGrids, SURVIVAL_LEVEL,
+        },
+        systems::{
+            missile::{MissileDyingSystem, MissilePhysicsSubsystem, MissileSpawnerSubsystem},
+            monster::*,
+            player::{PlayerActionSubsystem, ReviveSubsystem},
+            *,
+        },
+    },
+    utils::world::insert_level,
 };
 
 pub fn build_game_logic_systems<'a, 'b>(
@@ -35,20 +49,34 @@ pub fn build_game_logic_systems<'a, 'b>(
     world.insert(ConnectionEvents(Vec::new()));
     world.insert(MultiplayerGameState::new());
     world.insert(ActionUpdateIdProvider::default());
+    world.insert(SpatialGrids::default());
+    world.insert(RollbackStats::default());
+    world.insert(RunSummary::default());
+    world.insert(ExperienceAwards::default());
+    world.insert(SimulationTicks::default());
+
+    world.insert(Campaign::load()?);
+    world.insert(GameMode::Survival);
+    world.insert(ActiveObjective(None));
+    insert_level(world, LevelDefinition::load(SURVIVAL_LEVEL)?);
 
     // The resources which we need to remember to reset on starting a game.
     world.insert(FramedUpdates::<PlayerActionUpdates>::default());
     world.insert(FramedUpdates::<SpawnActions>::default());
     world.insert(WorldStates::default());
     world.insert(CastActionsToExecute::default());
+    world.insert(RangedAttacksToExecute::default());
+    world.insert(RunStats::default());
     world.insert(EntityNetMetadataStorage::new());
 
+    world.insert(rollback_stages());
+
     let game_data_builder = game_data_builder
         .with(PauseSystem, "pause_system", &["game_network_system"])
         .with(LevelSystem::default(), "level_system", &["pause_system"])
         .with(MonsterSpawnerSystem, "spawner_system", &["level_system"])
         .with(
-            ActionSystem,
+            ActionSystemDesc::default().build(world),
             "action_system",
             &dependencies_with_optional(&["spawner_system"], !is_server, &["input_system"]),
         )
@@ -62,11 +90,26 @@ pub fn build_game_logic_systems<'a, 'b>(
             "missile_dying_system",
             &["action_system"],
         )
+        .with(
+            LevelObjectiveSystem,
+            "level_objective_system",
+            &["action_system"],
+        )
+        .with(GameOverSystem, "game_over_system", &["action_system"])
+        .with(
+            DeadCleanupSystem,
+            "dead_cleanup_system",
+            &["monster_dying_system", "missile_dying_system"],
+        )
         .with(
             StateSwitcherSystem,
             "state_switcher_system",
             &dependencies_with_optional(
-                &["monster_dying_system", "missile_dying_system"],
+                &[
+                    "dead_cleanup_system",
+                    "level_objective_system",
+                    "game_over_system",
+                ],
                 !is_server,
                 &["menu_system"],
             ),
@@ -74,6 +117,24 @@ pub fn build_game_logic_systems<'a, 'b>(
     Ok(game_data_builder)
 }
 
+/// The stages `ActionSystem` runs for every (re)simulated frame. Monsters look for players
+/// with the spatial grids, so they are rebuilt after players move, and once again after
+/// monsters move, for the missiles to find them.
+fn rollback_stages() -> RollbackStages {
+    RollbackStages::default()
+        .with_stage(PlayerActionSubsystem)
+        .with_stage(SpatialGridsSubsystem)
+        .with_stage(MonsterActionSubsystem)
+        .with_stage(SpatialGridsSubsystem)
+        .with_stage(MissileSpawnerSubsystem)
+        .with_stage(MissilePhysicsSubsystem)
+        .with_stage(MonsterProjectileSubsystem)
+        .with_stage(StatusEffectSubsystem)
+        .with_stage(DamageSubsystem)
+        .with_stage(ReviveSubsystem)
+        .with_stage(PickupSubsystem)
+}
+
 fn optional_dependencies(dependencies: &[&'static str], condition: bool) -> Vec<&'static str> {
     if condition {
         dependencies.to_vec()
@@ -0,0 +1,44 @@
+use amethyst::{
+    ecs::World,
+    prelude::{GameData, SimpleState, StateData},
+};
+
+#[cfg(feature = "client")]
+use gv_client_shared::settings::Settings;
+use gv_core::ecs::resources::GameEngineState;
+
+use crate::{ecs::resources::GameMode, states::menu_state::reset_game};
+
+/// Moves a campaign to its next level once the objective of the current one is met.
+pub struct LevelCompleteState;
+
+impl SimpleState for LevelCompleteState {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        log::info!("LevelCompleteState started");
+        let world = data.world;
+        *world.fetch_mut::<GameEngineState>() = GameEngineState::LevelComplete;
+        reset_game(world);
+
+        let game_mode = *world.fetch::<GameMode>();
+        if let GameMode::Campaign { level } = game_mode {
+            let completed_levels = level + 1;
+            *world.fetch_mut::<GameMode>() = GameMode::Campaign {
+                level: completed_levels,
+            };
+            save_campaign_progress(world, completed_levels);
+        }
+    }
+}
+
+#[cfg(feature = "client")]
+fn save_campaign_progress(world: &World, completed_levels: usize) {
+    if let Err(err) = world
+        .fetch_mut::<Settings>()
+        .save_campaign_progress(completed_levels)
+    {
+        log::error!("Failed to save the campaign progress: {:?}", err);
+    }
+}
+
+#[cfg(not(feature = "client"))]
+fn save_campaign_progress(_world: &World, _completed_levels: usize) {}
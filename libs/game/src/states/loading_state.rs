@@ -2,7 +2,7 @@
 use amethyst::{
     assets::ProgressCounter,
     assets::{AssetStorage, Handle, Loader, PrefabLoader, RonFormat},
-    renderer::{ImageFormat, SpriteSheet, SpriteSheetFormat, Texture},
+    renderer::{sprite::Sprite, ImageFormat, SpriteSheet, Texture},
     ui::{FontAsset, TtfFormat, UiCreator},
 };
 use amethyst::{
@@ -17,9 +17,13 @@ use gv_client_shared::ecs::{
     components::{PlayerColor, SpellParticle},
     resources::{AssetHandles, DummyAssetHandles},
 };
-use gv_core::ecs::resources::{GameEngineState, GameLevelState, GameTime, NewGameEngineState};
+use gv_core::ecs::resources::{
+    GameEngineState, GameLevelState, GamePause, GameTime, NewGameEngineState,
+};
 
-use crate::ecs::resources::MonsterDefinitions;
+#[cfg(feature = "client")]
+use crate::ecs::resources::{LandscapeDefinition, LevelDefinition, SURVIVAL_LEVEL};
+use crate::ecs::resources::{MonsterDefinitions, SpellDefinitions};
 
 #[cfg(feature = "client")]
 #[derive(Default)]
@@ -40,8 +44,10 @@ impl SimpleState for LoadingState {
         let world = data.world;
 
         self.register_client_dependencies(world);
-        MonsterDefinitions::register(world);
+        world.insert(MonsterDefinitions::load().expect("Failed to load monster definitions"));
+        world.insert(SpellDefinitions::load().expect("Failed to load spell definitions"));
         world.insert(GameLevelState::default());
+        world.insert(GamePause::default());
         world.insert(GameTime::default());
         world.insert(GameEngineState::Loading);
         world.insert(NewGameEngineState(GameEngineState::Loading));
@@ -96,10 +102,14 @@ impl LoadingState {
                     )
                 };
 
-                let landscape_handle = load_sprite_sheet(
+                let landscape = LevelDefinition::load(SURVIVAL_LEVEL)
+                    .expect("Failed to load the survival level")
+                    .landscape
+                    .expect("Expected the survival level to define a landscape");
+                let landscape_handle = load_landscape_sprite_sheet(
                     world,
                     "resources/assets/desert_level.png",
-                    "resources/levels/desert.ron",
+                    &landscape,
                     &mut self.progress_counter,
                 );
 
@@ -127,6 +137,9 @@ impl LoadingState {
                         creator.create("resources/ui/lobby_menu.ron", ()),
                         creator.create("resources/ui/multiplayer_menu.ron", ()),
                         creator.create("resources/ui/restart_menu.ron", ()),
+                        creator.create("resources/ui/level_complete_menu.ron", ()),
+                        creator.create("resources/ui/upgrades_menu.ron", ()),
+                        creator.create("resources/ui/pause_menu.ron", ()),
                         creator.create("resources/ui/modal.ron", ()),
                         creator.create("resources/ui/game_overlays.ron", ()),
                     )
@@ -153,10 +166,10 @@ impl LoadingState {
 }
 
 #[cfg(feature = "client")]
-fn load_sprite_sheet(
+fn load_landscape_sprite_sheet(
     world: &mut World,
     png_path: &str,
-    ron_path: &str,
+    landscape: &LandscapeDefinition,
     progress: &mut ProgressCounter,
 ) -> Handle<SpriteSheet> {
     let texture_handle = {
@@ -169,11 +182,25 @@ fn load_sprite_sheet(
             &texture_storage,
         )
     };
+    // The whole texture is a single sprite.
+    let sprite = Sprite::from_pixel_values(
+        landscape.texture_width,
+        landscape.texture_height,
+        landscape.texture_width,
+        landscape.texture_height,
+        0,
+        0,
+        [0.0, 0.0],
+        false,
+        false,
+    );
     let loader = world.read_resource::<Loader>();
     let sprite_sheet_store = world.read_resource::<AssetStorage<SpriteSheet>>();
-    loader.load(
-        ron_path,
-        SpriteSheetFormat(texture_handle),
+    loader.load_from_data(
+        SpriteSheet {
+            texture: texture_handle,
+            sprites: vec![sprite],
+        },
         progress,
         &sprite_sheet_store,
     )
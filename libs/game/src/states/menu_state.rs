@@ -1,22 +1,45 @@
+#[cfg(feature = "client")]
+use amethyst::ecs::ReadExpect;
 use amethyst::{
-    ecs::{Entities, Join, ReadStorage},
+    ecs::{Entities, Join, ReadStorage, World},
     prelude::{GameData, SimpleState, StateData},
     renderer::Camera,
 };
 
+#[cfg(feature = "client")]
+use gv_client_shared::{ecs::resources::MultiplayerRoomState, settings::Settings};
 use gv_core::{
     actions::monster_spawn::SpawnActions,
     ecs::{
-        components::{missile::Missile, EntityNetMetadata, Monster, Player},
+        components::{
+            missile::{Missile, MonsterProjectile},
+            pickup::Pickup,
+            EntityNetMetadata, Monster, Player,
+        },
         resources::{
             net::{CastActionsToExecute, EntityNetMetadataStorage},
+            stats::RunStats,
             world::{FramedUpdates, WorldStates},
             GameEngineState, GameLevelState,
         },
     },
 };
+#[cfg(feature = "client")]
+use gv_core::{
+    ecs::{
+        components::ClientPlayerActions,
+        resources::{
+            net::MultiplayerGameState,
+            stats::{ExperienceAwards, RunSummary},
+        },
+    },
+    progression::EXPERIENCE_PER_KILL,
+};
 
-use crate::ecs::systems::{ClientFrameUpdate, FrameUpdate};
+use crate::ecs::{
+    resources::RangedAttacksToExecute,
+    systems::{ClientFrameUpdate, FrameUpdate},
+};
 
 pub struct MenuState;
 
@@ -31,49 +54,118 @@ impl SimpleState for MenuState {
             game_level_state.is_over = false;
             drop(game_level_state);
 
-            world.insert(FramedUpdates::<ClientFrameUpdate>::default());
-            world.insert(FramedUpdates::<FrameUpdate>::default());
-            world.insert(FramedUpdates::<SpawnActions>::default());
-            world.insert(WorldStates::default());
-            world.insert(CastActionsToExecute::default());
-            world.insert(EntityNetMetadataStorage::new());
-
-            world.exec(
-                |(entities, entity_net_metadata, players, monsters, missiles, cameras): (
-                    Entities,
-                    ReadStorage<EntityNetMetadata>,
-                    ReadStorage<Player>,
-                    ReadStorage<Monster>,
-                    ReadStorage<Missile>,
-                    ReadStorage<Camera>,
-                )| {
-                    for (entity_net_metadata, _) in (&entities, &entity_net_metadata).join() {
-                        entities
-                            .delete(entity_net_metadata)
-                            .expect("Expected to clean up an entity");
-                    }
-                    for (player_entity, _) in (&entities, &players).join() {
-                        entities
-                            .delete(player_entity)
-                            .expect("Expected to clean up an entity");
-                    }
-                    for (monster_entity, _) in (&entities, &monsters).join() {
-                        entities
-                            .delete(monster_entity)
-                            .expect("Expected to clean up an entity");
-                    }
-                    for (missile_entity, _) in (&entities, &missiles).join() {
-                        entities
-                            .delete(missile_entity)
-                            .expect("Expected to clean up an entity");
-                    }
-                    for (camera_entity, _) in (&entities, &cameras).join() {
-                        entities
-                            .delete(camera_entity)
-                            .expect("Expected to clean up an entity");
-                    }
-                },
-            );
+            reset_game(world);
         }
     }
 }
+
+/// Cleans up the entities and the resources of the finished game.
+pub(super) fn reset_game(world: &mut World) {
+    save_experience(world);
+
+    world.insert(FramedUpdates::<ClientFrameUpdate>::default());
+    world.insert(FramedUpdates::<FrameUpdate>::default());
+    world.insert(FramedUpdates::<SpawnActions>::default());
+    world.insert(WorldStates::default());
+    world.insert(CastActionsToExecute::default());
+    world.insert(RangedAttacksToExecute::default());
+    world.insert(RunStats::default());
+    world.insert(EntityNetMetadataStorage::new());
+
+    world.exec(
+        |(
+            entities,
+            entity_net_metadata,
+            players,
+            monsters,
+            missiles,
+            monster_projectiles,
+            pickups,
+            cameras,
+        ): (
+            Entities,
+            ReadStorage<EntityNetMetadata>,
+            ReadStorage<Player>,
+            ReadStorage<Monster>,
+            ReadStorage<Missile>,
+            ReadStorage<MonsterProjectile>,
+            ReadStorage<Pickup>,
+            ReadStorage<Camera>,
+        )| {
+            for (entity_net_metadata, _) in (&entities, &entity_net_metadata).join() {
+                entities
+                    .delete(entity_net_metadata)
+                    .expect("Expected to clean up an entity");
+            }
+            for (player_entity, _) in (&entities, &players).join() {
+                entities
+                    .delete(player_entity)
+                    .expect("Expected to clean up an entity");
+            }
+            for (monster_entity, _) in (&entities, &monsters).join() {
+                entities
+                    .delete(monster_entity)
+                    .expect("Expected to clean up an entity");
+            }
+            for (missile_entity, _) in (&entities, &missiles).join() {
+                entities
+                    .delete(missile_entity)
+                    .expect("Expected to clean up an entity");
+            }
+            for (projectile_entity, _) in (&entities, &monster_projectiles).join() {
+                entities
+                    .delete(projectile_entity)
+                    .expect("Expected to clean up an entity");
+            }
+            for (pickup_entity, _) in (&entities, &pickups).join() {
+                entities
+                    .delete(pickup_entity)
+                    .expect("Expected to clean up an entity");
+            }
+            for (camera_entity, _) in (&entities, &cameras).join() {
+                entities
+                    .delete(camera_entity)
+                    .expect("Expected to clean up an entity");
+            }
+        },
+    );
+}
+
+/// Awards the local player with experience for the kills which
+/// hadn't been confirmed before the game ended.
+#[cfg(feature = "client")]
+fn save_experience(world: &mut World) {
+    let kills = if world.fetch::<MultiplayerGameState>().is_playing {
+        let player_net_id = world.fetch::<MultiplayerRoomState>().player_net_id;
+        world
+            .fetch::<RunSummary>()
+            .players
+            .iter()
+            .find(|player| player.entity_net_id == Some(player_net_id))
+            .map_or(0, |player| player.stats.kills)
+    } else {
+        world.exec(
+            |(entities, client_player_actions, run_stats): (
+                Entities,
+                ReadStorage<ClientPlayerActions>,
+                ReadExpect<RunStats>,
+            )| {
+                (&entities, &client_player_actions)
+                    .join()
+                    .next()
+                    .map_or(0, |(player, _)| run_stats.player(player).kills)
+            },
+        )
+    };
+
+    let new_kills = world.fetch_mut::<ExperienceAwards>().award(kills);
+    if let Err(err) = world
+        .fetch_mut::<Settings>()
+        .add_experience(new_kills * EXPERIENCE_PER_KILL)
+    {
+        log::error!("Failed to save the player profile: {:?}", err);
+    }
+}
+
+#[cfg(not(feature = "client"))]
+fn save_experience(_world: &mut World) {}
@@ -1,5 +1,9 @@
+mod level_complete_state;
 mod loading_state;
 mod menu_state;
 mod playing_state;
 
-pub use self::{loading_state::LoadingState, menu_state::MenuState, playing_state::PlayingState};
+pub use self::{
+    level_complete_state::LevelCompleteState, loading_state::LoadingState, menu_state::MenuState,
+    playing_state::PlayingState,
+};
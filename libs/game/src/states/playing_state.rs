@@ -1,41 +1,62 @@
+use amethyst::{
+    core::{
+        frame_limiter::{FrameLimiter, FrameRateLimitStrategy},
+        Time,
+    },
+    ecs::{SystemData, World, WriteExpect, WriteStorage},
+    prelude::{GameData, SimpleState, SimpleTrans, StateData, Trans},
+};
 #[cfg(feature = "client")]
 use amethyst::{
     ecs::ReadExpect,
-    prelude::{SimpleTrans, StateEvent, Trans},
+    prelude::StateEvent,
+    winit::{self, ElementState},
 };
 #[cfg(not(feature = "client"))]
 use amethyst::{
     ecs::{Join, ReadStorage, Write},
     network::simulation::TransportResource,
 };
-use amethyst::{
-    ecs::{SystemData, World, WriteExpect, WriteStorage},
-    prelude::{GameData, SimpleState, StateData},
-};
 
 #[cfg(feature = "client")]
 use gv_client_shared::ecs::factories::PlayerClientFactory;
 #[cfg(feature = "client")]
 use gv_client_shared::{
     ecs::{factories::CameraFactory, resources::MultiplayerRoomState},
+    settings::Settings,
     utils,
 };
-use gv_core::ecs::{
-    components::EntityNetMetadata,
-    resources::{
-        net::{EntityNetMetadataStorage, MultiplayerGameState},
-        GameEngineState, GameLevelState,
-    },
-    system_data::time::GameTimeService,
-};
 #[cfg(feature = "client")]
 use gv_core::PLAYER_COLORS;
+use gv_core::{
+    actions::monster_spawn::SpawnActions,
+    ecs::{
+        components::EntityNetMetadata,
+        resources::{
+            net::{EntityNetMetadataStorage, MultiplayerGameState},
+            stats::{ExperienceAwards, RunSummary},
+            world::{
+                DummyFramedUpdate, FramedUpdates, PlayerActionUpdates, ReceivedClientActionUpdates,
+                ReceivedServerWorldUpdate, WorldStates,
+            },
+            GameEngineState, GameLevelState, GamePause, SimulationTicks,
+        },
+        system_data::time::GameTimeService,
+    },
+    net::TickRate,
+};
 #[cfg(not(feature = "client"))]
 use gv_core::{ecs::components::NetConnectionModel, net::server_message::ServerMessagePayload};
 
-use crate::ecs::factories::{LandscapeFactory, PlayerFactory};
 #[cfg(not(feature = "client"))]
 use crate::utils::net::broadcast_message_reliable;
+use crate::{
+    ecs::{
+        factories::{LandscapeFactory, PlayerFactory},
+        resources::{ActiveObjective, Campaign, GameMode, LevelDefinition, SURVIVAL_LEVEL},
+    },
+    utils::world::insert_level,
+};
 
 #[derive(Default)]
 pub struct PlayingState;
@@ -47,14 +68,43 @@ impl SimpleState for PlayingState {
         *world.fetch_mut::<GameEngineState>() = GameEngineState::Playing;
 
         world.insert(GameLevelState::default());
+        world.insert(GamePause::default());
+        world.insert(SimulationTicks::default());
+        world.insert(RunSummary::default());
+        world.insert(ExperienceAwards::default());
+        insert_game_mode_level(world);
+
+        let tick_rate = agree_tick_rate(world);
+        log::info!(
+            "Simulating at {} frames per second",
+            tick_rate.frames_per_second()
+        );
+        world
+            .fetch_mut::<Time>()
+            .set_fixed_seconds(tick_rate.fixed_seconds());
+        world
+            .fetch_mut::<FrameLimiter>()
+            .set_rate(FrameRateLimitStrategy::Yield, frame_rate_limit(tick_rate));
 
         GameTimeService::fetch(&world).set_game_start_time();
+        set_rollback_window(world);
 
         initialize_players(world);
 
         world.exec(|mut landscape_factory: LandscapeFactory| landscape_factory.create());
     }
 
+    /// Fixed updates happen at the tick rate, systems simulate them on the next frame.
+    fn fixed_update(&mut self, data: StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
+        data.world.fetch_mut::<SimulationTicks>().add_fixed_update();
+        Trans::None
+    }
+
+    fn update(&mut self, data: &mut StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
+        data.world.fetch_mut::<SimulationTicks>().start_frame();
+        Trans::None
+    }
+
     #[cfg(feature = "client")]
     fn handle_event(
         &mut self,
@@ -63,10 +113,92 @@ impl SimpleState for PlayingState {
     ) -> SimpleTrans {
         let world = data.world;
         utils::handle_window_event(&world, &event);
+        handle_pause_event(&world, &event);
         Trans::None
     }
 }
 
+/// Clients render frames independently of the tick rate.
+#[cfg(feature = "client")]
+fn frame_rate_limit(tick_rate: TickRate) -> u32 {
+    tick_rate.client_frame_rate_limit()
+}
+
+/// A server doesn't render anything, so it runs a frame per tick.
+#[cfg(not(feature = "client"))]
+fn frame_rate_limit(tick_rate: TickRate) -> u32 {
+    tick_rate.frames_per_second()
+}
+
+/// The saved frames cover the same amount of time whatever the agreed tick rate is.
+fn set_rollback_window(world: &mut World) {
+    let frames_limit = GameTimeService::fetch(&world).saved_world_states_limit() as usize;
+    world
+        .fetch_mut::<WorldStates>()
+        .set_frames_limit(frames_limit);
+    set_framed_updates_limit::<PlayerActionUpdates>(world, frames_limit);
+    set_framed_updates_limit::<SpawnActions>(world, frames_limit);
+    set_framed_updates_limit::<ReceivedClientActionUpdates>(world, frames_limit);
+    set_framed_updates_limit::<ReceivedServerWorldUpdate>(world, frames_limit);
+    set_framed_updates_limit::<DummyFramedUpdate>(world, frames_limit);
+}
+
+fn set_framed_updates_limit<T: Send + Sync + 'static>(world: &mut World, frames_limit: usize) {
+    if let Some(mut framed_updates) = world.try_fetch_mut::<FramedUpdates<T>>() {
+        framed_updates.set_frames_limit(frames_limit);
+    }
+}
+
+/// Multiplayer games are always survival ones.
+fn insert_game_mode_level(world: &mut World) {
+    let is_multiplayer = world.fetch::<MultiplayerGameState>().is_playing;
+    let game_mode = *world.fetch::<GameMode>();
+    let (level, objective) = match game_mode {
+        GameMode::Campaign { level } if !is_multiplayer => {
+            let campaign_level = world.fetch::<Campaign>().levels[level].clone();
+            log::info!("Starting the campaign level \"{}\"", campaign_level.name);
+            (campaign_level.level, Some(campaign_level.objective))
+        }
+        _ => (SURVIVAL_LEVEL.to_owned(), None),
+    };
+    insert_level(
+        world,
+        LevelDefinition::load(&level).expect("Expected to load a level"),
+    );
+    world.insert(ActiveObjective(objective));
+}
+
+/// Toggles the pause of a single player game, the game also gets paused if the window loses focus.
+#[cfg(feature = "client")]
+fn handle_pause_event(world: &World, event: &StateEvent) {
+    let toggle_pause = world.fetch::<Settings>().get_action_keycode("toggle_pause");
+    let is_multiplayer = world.fetch::<MultiplayerGameState>().is_playing;
+    let mut game_pause = world.fetch_mut::<GamePause>();
+    if is_multiplayer || world.fetch::<GameLevelState>().is_over {
+        return;
+    }
+
+    if let StateEvent::Window(event) = event {
+        match event {
+            winit::Event::WindowEvent {
+                event: winit::WindowEvent::KeyboardInput { input, .. },
+                ..
+            } if input.state == ElementState::Released && input.virtual_keycode == toggle_pause => {
+                game_pause.is_paused = !game_pause.is_paused;
+                log::info!("Toggling pause (is paused: {})", game_pause.is_paused);
+            }
+            winit::Event::WindowEvent {
+                event: winit::WindowEvent::Focused(false),
+                ..
+            } if !game_pause.is_paused => {
+                game_pause.is_paused = true;
+                log::info!("Pausing the game, the window has lost focus");
+            }
+            _ => {}
+        }
+    }
+}
+
 #[cfg(feature = "client")]
 fn initialize_players(world: &mut World) {
     let mut main_player = None;
@@ -79,6 +211,7 @@ fn initialize_players(world: &mut World) {
             mut entity_net_metadata_service,
             multiplayer_room_state,
             multiplayer_game_state,
+            settings,
         ): (
             PlayerFactory,
             PlayerClientFactory,
@@ -86,15 +219,16 @@ fn initialize_players(world: &mut World) {
             WriteExpect<EntityNetMetadataStorage>,
             ReadExpect<MultiplayerRoomState>,
             ReadExpect<MultiplayerGameState>,
+            ReadExpect<Settings>,
         )| {
             if !multiplayer_game_state.is_playing {
-                let player_entity = player_factory.create();
+                let player_entity = player_factory.create(settings.profile().upgrades);
                 player_client_factory.create(player_entity, PLAYER_COLORS[4], true);
                 main_player = Some(player_entity);
             }
 
             for player in &multiplayer_game_state.players {
-                let player_entity = player_factory.create();
+                let player_entity = player_factory.create(player.upgrades);
                 entity_net_metadata_service.set_net_id(player_entity, player.entity_net_id);
                 entity_net_metadata
                     .insert(
@@ -144,7 +278,7 @@ fn initialize_players(world: &mut World) {
                 .players
                 .iter_mut()
                 .map(|player| {
-                    let player_entity = player_factory.create();
+                    let player_entity = player_factory.create(player.upgrades);
                     let entity_net_id =
                         entity_net_metadata_service.register_new_entity(player_entity);
                     player.entity_net_id = entity_net_id;
@@ -163,8 +297,29 @@ fn initialize_players(world: &mut World) {
             broadcast_message_reliable(
                 &mut transport,
                 (&net_connections).join(),
-                ServerMessagePayload::StartGame(player_net_identifiers),
+                ServerMessagePayload::StartGame {
+                    tick_rate: multiplayer_game_state.tick_rate,
+                    players: player_net_identifiers,
+                },
             );
         },
     );
 }
+
+/// In multiplayer clients use the tick rate sent by a server.
+#[cfg(feature = "client")]
+fn agree_tick_rate(world: &mut World) -> TickRate {
+    let multiplayer_game_state = world.fetch::<MultiplayerGameState>();
+    if multiplayer_game_state.is_playing {
+        multiplayer_game_state.tick_rate
+    } else {
+        *world.fetch::<TickRate>()
+    }
+}
+
+#[cfg(not(feature = "client"))]
+fn agree_tick_rate(world: &mut World) -> TickRate {
+    let tick_rate = *world.fetch::<TickRate>();
+    world.fetch_mut::<MultiplayerGameState>().tick_rate = tick_rate;
+    tick_rate
+}
@@ -4,7 +4,7 @@ use gv_core::ecs::components::NetConnectionModel;
 #[cfg(feature = "client")]
 use gv_core::net::client_message::{ClientMessage, ClientMessagePayload};
 #[cfg(not(feature = "client"))]
-use gv_core::net::server_message::{ServerMessage, ServerMessagePayload};
+use gv_core::net::server_message::{DisconnectReason, ServerMessage, ServerMessagePayload};
 
 #[cfg(not(feature = "client"))]
 pub fn broadcast_message_reliable<'a>(
@@ -144,3 +144,33 @@ pub fn send_message_unreliable(
         UrgencyRequirement::Immediate,
     );
 }
+
+/// Disconnects a client whose definitions of the given kind differ from the server ones.
+/// Returns `true` if the client is rejected.
+#[cfg(not(feature = "client"))]
+pub fn reject_on_checksum_mismatch(
+    transport: &mut TransportResource,
+    net_connection: &mut NetConnectionModel,
+    kind: &str,
+    expected: u64,
+    got: u64,
+) -> bool {
+    if expected == got {
+        return false;
+    }
+    log::warn!(
+        "A client ({}) {} has different {} definitions (checksum {}, expected {})",
+        net_connection.id,
+        net_connection.addr,
+        kind,
+        got,
+        expected
+    );
+    send_message_reliable(
+        transport,
+        net_connection,
+        ServerMessagePayload::Disconnect(DisconnectReason::DefinitionsMismatch),
+    );
+    net_connection.disconnected = true;
+    true
+}
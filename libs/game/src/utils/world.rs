@@ -1,78 +1,51 @@
-use amethyst::ecs::{
-    storage::{GenericReadStorage, MaskedStorage, Storage},
-    Entities, Entity, Join,
-};
+use amethyst::ecs::{Entity, World};
 use rand::{self, Rng};
 
-use std::ops::Deref;
+use gv_core::{actions::monster_spawn::Side, ecs::resources::GameLevelState, math::Vector2};
 
-use gv_core::{
-    actions::monster_spawn::Side,
-    ecs::{
-        components::{Dead, Monster, WorldPosition},
-        resources::GameLevelState,
-    },
-    math::Vector2,
+use crate::ecs::{
+    resources::{LevelDefinition, LevelObstacles, NavigationGrid, SpatialGrid, WaveScript},
+    systems::{AggregatedOutcomingUpdates, OutcomingNetUpdates},
 };
 
-use crate::{
-    ecs::systems::{AggregatedOutcomingUpdates, OutcomingNetUpdates},
-    utils::entities::is_dead,
-};
+const RANDOM_WALKABLE_POSITION_ATTEMPTS: usize = 10;
 
-pub fn closest_monster<
-    DW: Deref<Target = MaskedStorage<WorldPosition>>,
-    DM: Deref<Target = MaskedStorage<Monster>>,
-    G: GenericReadStorage<Component = Dead>,
->(
+/// Replaces the obstacles, the navigation grid and the wave script with the ones of a level.
+pub fn insert_level(world: &mut World, level_definition: LevelDefinition) {
+    let level_obstacles = LevelObstacles::new(level_definition.obstacles);
+    world.insert(NavigationGrid::new(
+        GameLevelState::default().dimensions,
+        &level_obstacles,
+        &LevelObstacles::new(level_definition.blocking_areas),
+    ));
+    world.insert(level_obstacles);
+    world.insert(WaveScript(level_definition.waves));
+}
+
+/// Expects the grid to contain only monsters that are alive at the current frame.
+pub fn closest_monster(
     missile_position: Vector2,
-    world_positions: &Storage<'_, WorldPosition, DW>,
-    entities: &Entities<'_>,
-    monsters: &Storage<'_, Monster, DM>,
-    dead: &G,
-    frame_number: u64,
+    monsters_grid: &SpatialGrid,
 ) -> Option<(Entity, Vector2)> {
-    (world_positions, entities, monsters)
-        .join()
-        .filter(|(_, entity, _)| !is_dead(*entity, dead, frame_number))
-        .fold(None, |res, (monster_position, monster, _)| {
-            if let Some((closest_monster, closest_monster_position)) = res {
-                if (closest_monster_position - missile_position).norm_squared()
-                    > (**monster_position - missile_position).norm_squared()
-                {
-                    Some((monster, **monster_position))
-                } else {
-                    Some((closest_monster, closest_monster_position))
-                }
-            } else {
-                Some((monster, **monster_position))
-            }
-        })
+    monsters_grid
+        .closest(missile_position)
+        .map(|entry| (entry.entity, entry.position))
 }
 
-pub fn find_first_hit_monster<
-    DT: Deref<Target = MaskedStorage<Monster>>,
-    DP: Deref<Target = MaskedStorage<WorldPosition>>,
-    G: GenericReadStorage<Component = Dead>,
->(
+/// Expects the grid to contain only monsters that are alive at the current frame.
+pub fn find_first_hit_monster(
     object_position: Vector2,
     object_radius: f32,
-    targets: &Storage<'_, Monster, DT>,
-    target_positions: &Storage<'_, WorldPosition, DP>,
-    entities: &Entities<'_>,
-    dead: &G,
-    frame_number: u64,
+    monsters_grid: &SpatialGrid,
 ) -> Option<Entity> {
-    (target_positions, entities, targets)
-        .join()
-        .filter(|(_, entity, _)| !is_dead(*entity, dead, frame_number))
-        .find(|(target_position, _, target)| {
-            let distance_squared = (object_position - ***target_position).norm_squared();
+    monsters_grid
+        .find(object_position, object_radius, |target| {
+            let distance_squared = (object_position - target.position).norm_squared();
             let impact_distance = object_radius + target.radius;
             let impact_distance_squared = impact_distance * impact_distance;
             distance_squared <= impact_distance_squared
         })
-        .map(|result| result.1)
+        .map(|target| target.entity)
 }
 
 pub fn random_scene_position(game_scene: &GameLevelState) -> Vector2 {
@@ -89,6 +62,21 @@ pub fn random_scene_position(game_scene: &GameLevelState) -> Vector2 {
     )
 }
 
+/// Gives up and returns a blocked position if it fails to find a walkable one several times.
+pub fn random_walkable_position(
+    game_scene: &GameLevelState,
+    navigation_grid: &NavigationGrid,
+) -> Vector2 {
+    let mut position = random_scene_position(game_scene);
+    for _ in 0..RANDOM_WALKABLE_POSITION_ATTEMPTS {
+        if navigation_grid.is_walkable(position) {
+            break;
+        }
+        position = random_scene_position(game_scene);
+    }
+    position
+}
+
 pub fn random_spawn_position(game_level_state: &GameLevelState) -> Vector2 {
     let mut rng = rand::thread_rng();
 
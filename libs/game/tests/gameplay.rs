@@ -0,0 +1,1112 @@
+#![cfg(not(feature = "client"))]
+
+mod harness;
+
+use amethyst::ecs::{Entity, Join, WorldExt};
+
+use gv_core::{
+    actions::{mob::MobAction, player::PlayerWalkAction},
+    ecs::{
+        components::{
+            damage_history::{DamageCause, DamageType},
+            missile::MonsterProjectile,
+            pickup::PickupKind,
+            status_effect::StatusEffectKind,
+            Monster, Player, PlayerDownedUpdate, WorldPosition,
+        },
+        resources::world::SavedWorldState,
+    },
+    math::Vector2,
+    net::TickRate,
+    progression::PlayerUpgrades,
+};
+use gv_game::ecs::resources::{
+    Campaign, LevelObjective, MonsterDrop, RollbackStats, WaveCondition, WaveDefinition, WaveSpawn,
+    WaveSpawnPoint,
+};
+
+use harness::{HeadlessGame, Scenario, ScenarioBuilder, SPAWN_FRAME};
+
+/// LevelSystem spawns a single monster at (0, 300) at this frame.
+const FIRST_MONSTER_FRAME: u64 = 10;
+
+/// The player walks towards the first monster, so that it starts chasing the player
+/// instead of wandering around randomly, and casts two missiles (the monster has 100 health,
+/// a missile deals 50 damage). Returns the scenario, the monster and the frame it dies at.
+fn missile_kill_game(scenario: ScenarioBuilder) -> (Scenario, Entity, u64) {
+    let mut scenario = scenario.build();
+    let (game, player, player_net_id) =
+        (&mut scenario.game, scenario.player, scenario.player_net_id);
+
+    game.add_walk_action(
+        0,
+        player_net_id,
+        PlayerWalkAction::Walk {
+            direction: Vector2::new(0.0, 1.0),
+        },
+    );
+    game.run_until(FIRST_MONSTER_FRAME + 1);
+    let monsters = game.monsters();
+    assert_eq!(monsters.len(), 1);
+    let monster = monsters[0];
+    assert!((game.position(monster) - Vector2::new(0.0, 300.0)).norm() < 5.0);
+
+    game.add_walk_action(30, player_net_id, PlayerWalkAction::Stop);
+    // Casting a missile is cooling down for the first 30 frames.
+    game.run_until(31);
+    game.add_cast_action(31, player_net_id, game.position(monster));
+    game.run_until(62);
+    game.add_cast_action(62, player_net_id, game.position(monster));
+
+    let death_frame = game.run_until_condition(240, |game| game.is_dead(monster));
+    assert_eq!(game.monster(monster).health, 0.0);
+    assert!(!game.is_dead(player));
+    (scenario, monster, death_frame)
+}
+
+fn missile_kill_scenario() -> u64 {
+    missile_kill_game(ScenarioBuilder::default()).2
+}
+
+#[test]
+fn missiles_kill_monster() {
+    let death_frame = missile_kill_scenario();
+    assert!(death_frame > 62);
+}
+
+#[test]
+fn missile_kill_is_deterministic() {
+    assert_eq!(missile_kill_scenario(), missile_kill_scenario());
+}
+
+#[test]
+fn dead_monster_is_deleted_after_rollback_window() {
+    let (Scenario { mut game, .. }, monster, death_frame) =
+        missile_kill_game(ScenarioBuilder::default());
+    let monster_net_id = game.net_id(monster);
+
+    game.run_until(death_frame + game.saved_world_states_limit());
+    assert!(game.is_alive(monster));
+    assert_eq!(game.entity(monster_net_id), Some(monster));
+
+    game.run_until(death_frame + game.saved_world_states_limit() + 2);
+    assert!(!game.is_alive(monster));
+    assert_eq!(game.entity(monster_net_id), None);
+}
+
+#[test]
+fn missile_kill_is_credited_to_caster() {
+    let (
+        Scenario {
+            mut game, player, ..
+        },
+        monster,
+        death_frame,
+    ) = missile_kill_game(ScenarioBuilder::default());
+    let stats = game.stats(player);
+    assert_eq!(stats.kills, 1);
+    assert_eq!(stats.missiles_cast, 2);
+    assert_eq!(stats.missiles_hit, 2);
+    assert_eq!(stats.damage_dealt, 100.0);
+
+    // Confirming the frames outside the rollback window doesn't change the totals.
+    game.run_until(death_frame + game.saved_world_states_limit() + 2);
+    assert!(!game.is_alive(monster));
+    assert_eq!(game.stats(player), stats);
+}
+
+#[test]
+fn kills_are_confirmed_outside_of_rollback_window() {
+    let (
+        Scenario {
+            mut game, player, ..
+        },
+        _monster,
+        death_frame,
+    ) = missile_kill_game(ScenarioBuilder::default());
+    assert_eq!(game.confirmed_kills(player), 0);
+
+    game.run_until(death_frame + game.saved_world_states_limit() + 2);
+    assert_eq!(game.confirmed_kills(player), 1);
+}
+
+#[test]
+fn missile_damage_is_attributed_to_caster() {
+    let (
+        Scenario {
+            game,
+            player,
+            player_net_id,
+            ..
+        },
+        monster,
+        death_frame,
+    ) = missile_kill_game(ScenarioBuilder::default());
+
+    let entries = game.damage_entries(monster, FIRST_MONSTER_FRAME..=death_frame);
+    assert_eq!(entries.len(), 2);
+    for entry in entries {
+        assert_eq!(entry.source, Some(player));
+        assert_eq!(entry.damage_type, DamageType::Arcane);
+        assert_eq!(entry.cause, DamageCause::Missile);
+    }
+
+    // Clients get sources by their net ids.
+    let entries = game.sent_damage_entries(game.net_id(monster));
+    assert_eq!(entries.len(), 2);
+    assert!(entries
+        .iter()
+        .all(|entry| entry.source_net_id == Some(player_net_id)));
+}
+
+#[test]
+fn weakness_increases_damage_taken_by_monster() {
+    let (Scenario { game, player, .. }, monster, death_frame) = missile_kill_game(
+        ScenarioBuilder::default().with_resistances("Ghoul", vec![(DamageType::Arcane, -1.0)]),
+    );
+
+    // A single missile is enough, entries keep the damage before resistances.
+    let entries = game.damage_entries(monster, FIRST_MONSTER_FRAME..=death_frame);
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].damage, 50.0);
+    assert_eq!(game.stats(player).damage_dealt, 100.0);
+}
+
+#[test]
+fn overkill_damage_is_not_counted_as_dealt() {
+    let (Scenario { game, player, .. }, _monster, _death_frame) = missile_kill_game(
+        ScenarioBuilder::default().with_resistances("Ghoul", vec![(DamageType::Arcane, -2.0)]),
+    );
+
+    // A missile deals 150 damage to a monster with 100 health.
+    assert_eq!(game.stats(player).damage_dealt, 100.0);
+}
+
+#[test]
+fn resistance_reduces_damage_taken_by_monster() {
+    let Scenario {
+        mut game,
+        player,
+        player_net_id,
+        ..
+    } = ScenarioBuilder::default()
+        .with_resistances("Ghoul", vec![(DamageType::Arcane, 0.5)])
+        .build();
+    game.run_until(FIRST_MONSTER_FRAME + 1);
+    let monster = game.monsters()[0];
+
+    game.run_until(31);
+    game.add_cast_action(31, player_net_id, game.position(monster));
+    game.run_until_condition(240, |game| game.monster(monster).health < 100.0);
+    assert_eq!(game.monster(monster).health, 75.0);
+    assert_eq!(game.stats(player).damage_dealt, 25.0);
+}
+
+/// Ghouls always drop a speed buff.
+fn speed_buff_drop_scenario() -> ScenarioBuilder {
+    ScenarioBuilder::default().with_drops(
+        "Ghoul",
+        vec![MonsterDrop {
+            pickup: PickupKind::SpeedBuff,
+            chance: 1.0,
+        }],
+    )
+}
+
+#[test]
+fn killed_monster_drops_pickup_which_player_collects() {
+    let (
+        Scenario {
+            mut game,
+            player,
+            player_net_id,
+            ..
+        },
+        monster,
+        death_frame,
+    ) = missile_kill_game(speed_buff_drop_scenario());
+
+    let pickups = game.pickups();
+    assert_eq!(pickups.len(), 1);
+    let pickup = pickups[0];
+    assert_eq!(game.pickup(pickup).kind, PickupKind::SpeedBuff);
+    assert_eq!(game.pickup(pickup).dropped_by, Some(monster));
+    assert!((game.position(pickup) - game.position(monster)).norm() < 0.01);
+    let pickup_net_id = game.net_id(pickup);
+
+    let direction = (game.position(pickup) - game.position(player)).normalize();
+    game.add_walk_action(
+        death_frame,
+        player_net_id,
+        PlayerWalkAction::Walk { direction },
+    );
+    let collected_frame = game.run_until_condition(death_frame + 240, |game| game.is_dead(pickup));
+
+    assert!(game.player(player).speed_buff_until_frame > collected_frame);
+    assert_eq!(
+        game.sent_pickup_collections(),
+        vec![(pickup_net_id, player_net_id)]
+    );
+}
+
+/// The player walks to the east for a second of game time, which is `tick_rate` frames,
+/// and stops. Returns the game after another half of a second.
+fn one_second_walk_game(scenario: ScenarioBuilder, tick_rate: TickRate) -> Scenario {
+    let mut scenario = scenario.with_tick_rate(tick_rate).build();
+    let game = &mut scenario.game;
+    let player_net_id = scenario.player_net_id;
+    let frames_per_second = u64::from(tick_rate.frames_per_second());
+
+    game.add_walk_action(
+        0,
+        player_net_id,
+        PlayerWalkAction::Walk {
+            direction: Vector2::new(1.0, 0.0),
+        },
+    );
+    game.add_walk_action(frames_per_second, player_net_id, PlayerWalkAction::Stop);
+    game.run_until(frames_per_second * 3 / 2);
+    scenario
+}
+
+#[test]
+fn player_walks_with_constant_speed() {
+    let Scenario { game, player, .. } =
+        one_second_walk_game(ScenarioBuilder::default(), TickRate::Hz60);
+
+    // A second of walking with 200 units per second.
+    let position = game.position(player);
+    assert!((position - Vector2::new(200.0, 0.0)).norm() < 0.01);
+}
+
+#[test]
+fn player_walks_with_the_same_speed_at_any_tick_rate() {
+    for tick_rate in &[TickRate::Hz30, TickRate::Hz120] {
+        let Scenario { game, player, .. } =
+            one_second_walk_game(ScenarioBuilder::default(), *tick_rate);
+        let position = game.position(player);
+        assert!(
+            (position - Vector2::new(200.0, 0.0)).norm() < 0.01,
+            "Unexpected position at {:?}: {}",
+            tick_rate,
+            position
+        );
+    }
+}
+
+#[test]
+fn frames_between_ticks_are_not_simulated() {
+    let mut scenario = ScenarioBuilder::default()
+        .with_tick_rate(TickRate::Hz30)
+        .build();
+    let game = &mut scenario.game;
+    game.add_walk_action(
+        0,
+        scenario.player_net_id,
+        PlayerWalkAction::Walk {
+            direction: Vector2::new(1.0, 0.0),
+        },
+    );
+
+    // A client renders 60 frames per second at the tick rate of 30.
+    for _ in 0..30 {
+        game.step();
+        let position = game.position(scenario.player);
+        game.step_between_ticks();
+        assert_eq!(game.position(scenario.player), position);
+    }
+    assert_eq!(game.frame_number(), 30);
+    // Walking for 30 ticks is a second of game time.
+    let position = game.position(scenario.player);
+    assert!((position - Vector2::new(200.0, 0.0)).norm() < 0.01);
+}
+
+#[test]
+fn paused_single_player_game_stops_simulating() {
+    let Scenario { mut game, .. } = ScenarioBuilder::default().single_player().build();
+    game.run_until(5);
+
+    game.set_paused(true);
+    for _ in 0..30 {
+        game.step();
+    }
+    assert_eq!(game.frame_number(), 5);
+    assert!(game.monsters().is_empty());
+
+    game.set_paused(false);
+    game.run_until(FIRST_MONSTER_FRAME + 1);
+    assert_eq!(game.monsters().len(), 1);
+}
+
+#[test]
+fn multiplayer_game_cannot_be_paused() {
+    let Scenario { mut game, .. } = ScenarioBuilder::default().build();
+
+    game.set_paused(true);
+    game.run_until(FIRST_MONSTER_FRAME + 1);
+    assert_eq!(game.monsters().len(), 1);
+}
+
+#[test]
+fn upgrades_apply_to_player() {
+    let upgrades = PlayerUpgrades {
+        move_speed: 2,
+        max_health: 1,
+        ..PlayerUpgrades::default()
+    };
+    let Scenario { game, player, .. } = one_second_walk_game(
+        ScenarioBuilder::default().with_upgrades(upgrades),
+        TickRate::Hz60,
+    );
+    assert_eq!(game.player(player).health, 120.0);
+
+    // A second of walking with 10% faster than 200 units per second.
+    let position = game.position(player);
+    assert!((position - Vector2::new(220.0, 0.0)).norm() < 0.01);
+}
+
+#[test]
+fn upgraded_missile_deals_more_damage() {
+    let Scenario {
+        mut game,
+        player_net_id,
+        ..
+    } = ScenarioBuilder::default()
+        .with_upgrades(PlayerUpgrades {
+            spell_damage: 5,
+            ..PlayerUpgrades::default()
+        })
+        .build();
+    game.run_until(FIRST_MONSTER_FRAME + 1);
+    let monster = game.monsters()[0];
+
+    game.run_until(31);
+    game.add_cast_action(31, player_net_id, game.position(monster));
+    let hit_frame = game.run_until_condition(240, |game| {
+        !game
+            .damage_entries(monster, 31..=game.frame_number() - 1)
+            .is_empty()
+    });
+
+    let entries = game.damage_entries(monster, 31..=hit_frame);
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].damage, 75.0);
+    assert_eq!(game.monster(monster).health, 25.0);
+}
+
+/// Starts a game with a ghoul chasing the player from the north, so that straight missiles
+/// aimed at it can't miss. Returns the game at frame 31, when casting isn't cooling down anymore.
+fn approaching_ghoul_game() -> (Scenario, Entity) {
+    // Inside the ghoul aggro radius.
+    let mut scenario = ScenarioBuilder::default()
+        .with_waves(Vec::new())
+        .with_monster("Ghoul", Vector2::new(0.0, 180.0))
+        .build();
+    scenario.game.run_until(31);
+    let monster = scenario
+        .game
+        .entity(scenario.monster_net_ids[0])
+        .expect("Expected a ghoul");
+    (scenario, monster)
+}
+
+#[test]
+fn spells_have_separate_cooldowns() {
+    let (
+        Scenario {
+            mut game,
+            player,
+            player_net_id,
+            ..
+        },
+        monster,
+    ) = approaching_ghoul_game();
+
+    game.add_spell_cast_action(31, player_net_id, "Missile", game.position(monster));
+    game.add_spell_cast_action(32, player_net_id, "Fireball", game.position(monster));
+    game.run_until(34);
+    // Fireball costs mana, a missile is free.
+    assert!(game.player(player).mana < 75.0);
+
+    game.run_until_condition(240, |game| game.is_dead(monster));
+    let mut damage_types = game
+        .damage_entries(monster, 31..=game.frame_number())
+        .into_iter()
+        .filter(|entry| entry.cause != DamageCause::StatusEffect(StatusEffectKind::Burn))
+        .map(|entry| entry.damage_type)
+        .collect::<Vec<_>>();
+    damage_types.sort_by_key(|damage_type| *damage_type as u8);
+    assert_eq!(damage_types, vec![DamageType::Fire, DamageType::Arcane]);
+}
+
+#[test]
+fn fireball_burns_monster() {
+    let (
+        Scenario {
+            mut game,
+            player_net_id,
+            ..
+        },
+        monster,
+    ) = approaching_ghoul_game();
+
+    game.add_spell_cast_action(31, player_net_id, "Fireball", game.position(monster));
+    let hit_frame = game.run_until_condition(240, |game| game.monster(monster).health < 100.0);
+    assert_eq!(game.monster(monster).health, 30.0);
+    assert!(game
+        .status_effects(monster)
+        .active(game.frame_number())
+        .any(|effect| effect.kind == StatusEffectKind::Burn));
+
+    // Burn keeps dealing fire damage after the hit.
+    game.run_until(hit_frame + 60);
+    assert!(game.monster(monster).health < 30.0);
+}
+
+#[test]
+fn nova_hits_every_monster_within_radius() {
+    let Scenario {
+        mut game,
+        player,
+        player_net_id,
+        monster_net_ids,
+        ..
+    } = ScenarioBuilder::default()
+        .with_monster("Ghoul", Vector2::new(-100.0, 0.0))
+        .with_monster("Ghoul", Vector2::new(100.0, 0.0))
+        .build();
+
+    // The cast is received late, so the server resimulates the frames after it.
+    let cast_frame = SPAWN_FRAME + 5;
+    game.run_until(cast_frame + 5);
+    game.add_spell_cast_action(cast_frame, player_net_id, "Nova", Vector2::new(0.0, 0.0));
+    game.run_until(cast_frame + 20);
+
+    let far_monster = game.monsters()[0];
+    assert!((game.position(far_monster) - game.position(player)).norm() > 160.0);
+    assert!(game
+        .damage_entries(far_monster, cast_frame..=game.frame_number())
+        .is_empty());
+
+    for net_id in &monster_net_ids {
+        let monster = game.entity(*net_id).expect("Expected a spawned monster");
+        let entries = game.damage_entries(monster, cast_frame..=game.frame_number());
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].damage, 40.0);
+        assert_eq!(entries[0].damage_type, DamageType::Frost);
+        assert_eq!(entries[0].cause, DamageCause::Nova);
+        assert_eq!(entries[0].source, Some(player));
+        assert_eq!(game.monster(monster).health, 60.0);
+    }
+    assert_eq!(game.stats(player).missiles_hit, 1);
+}
+
+/// The player walks for 20 frames starting with `walk_frame`, the walk action is received
+/// either in time or `late_frames` late. Returns the position of the player.
+/// There are no monsters, as idle ones wander to random positions, which differ
+/// when frames get resimulated.
+fn walk_scenario(walk_frame: u64, late_frames: u64) -> Vector2 {
+    let Scenario {
+        mut game,
+        player,
+        player_net_id,
+        ..
+    } = ScenarioBuilder::default().with_waves(Vec::new()).build();
+
+    game.add_late_walk_action(
+        walk_frame,
+        late_frames,
+        player_net_id,
+        PlayerWalkAction::Walk {
+            direction: Vector2::new(-1.0, 0.0),
+        },
+    );
+    game.run_until(walk_frame + 20);
+    game.add_walk_action(walk_frame + 20, player_net_id, PlayerWalkAction::Stop);
+    game.run_until(walk_frame + 55);
+    game.position(player)
+}
+
+#[test]
+fn rollback_after_late_walk_action_converges() {
+    let in_time_position = walk_scenario(5, 0);
+    let late_position = walk_scenario(5, 10);
+
+    // 20 frames of walking with 200 units per second.
+    assert!((in_time_position - Vector2::new(-200.0 / 3.0, 0.0)).norm() < 0.01);
+    assert!((late_position - in_time_position).norm() < 0.001);
+}
+
+#[test]
+fn rollback_to_reused_world_states_converges() {
+    // The oldest world states are reused after 10 seconds.
+    let walk_frame = 700;
+    let in_time_position = walk_scenario(walk_frame, 0);
+    let late_position = walk_scenario(walk_frame, 10);
+
+    assert!((late_position - in_time_position).norm() < 0.001);
+}
+
+/// Entities of every saved column, in the order they are restored.
+fn saved_entities(world_state: &SavedWorldState) -> Vec<Vec<Entity>> {
+    fn entities<T>(column: &[(Entity, T)]) -> Vec<Entity> {
+        column.iter().map(|(entity, _)| *entity).collect()
+    }
+
+    vec![
+        entities(&world_state.players),
+        entities(&world_state.player_actions),
+        entities(&world_state.player_last_casted_spells),
+        entities(&world_state.monsters),
+        entities(&world_state.missiles),
+        entities(&world_state.monster_projectiles),
+        entities(&world_state.pickups),
+        entities(&world_state.status_effects),
+        entities(&world_state.world_positions),
+        entities(&world_state.dead),
+    ]
+}
+
+/// Positions, health and deaths of the entities, which are restored from world states.
+fn world_snapshot(game: &HeadlessGame) -> Vec<(Entity, Vector2, Option<f32>, bool)> {
+    let world = game.world();
+    let players = world.read_storage::<Player>();
+    let monsters = world.read_storage::<Monster>();
+    (&world.entities(), &world.read_storage::<WorldPosition>())
+        .join()
+        .map(|(entity, position)| {
+            let health = players
+                .get(entity)
+                .map(|player| player.health)
+                .or_else(|| monsters.get(entity).map(|monster| monster.health));
+            (entity, **position, health, game.is_dead(entity))
+        })
+        .collect()
+}
+
+#[test]
+fn reused_world_state_restores_the_same_storages() {
+    let Scenario { mut game, .. } = spitter_scenario().build();
+    // The oldest world state gets reused with the components of an older frame.
+    game.run_until(SPAWN_FRAME + 5);
+    let mut reused_world_state = SavedWorldState::default();
+    game.save_world_state_to(&mut reused_world_state);
+
+    game.run_until(SPAWN_FRAME + 60);
+    let mut new_world_state = SavedWorldState::default();
+    game.save_world_state_to(&mut new_world_state);
+    game.save_world_state_to(&mut reused_world_state);
+    assert_eq!(
+        saved_entities(&reused_world_state),
+        saved_entities(&new_world_state)
+    );
+    let saved_snapshot = world_snapshot(&game);
+
+    game.run_until(SPAWN_FRAME + 90);
+    assert_ne!(world_snapshot(&game), saved_snapshot);
+    // Entities created after saving aren't touched by restoring.
+    let restored_snapshot = |game: &HeadlessGame| {
+        world_snapshot(game)
+            .into_iter()
+            .filter(|(entity, ..)| {
+                saved_snapshot
+                    .iter()
+                    .any(|(saved_entity, ..)| saved_entity == entity)
+            })
+            .collect::<Vec<_>>()
+    };
+    game.load_world_state(&reused_world_state);
+    assert_eq!(restored_snapshot(&game), saved_snapshot);
+    game.load_world_state(&new_world_state);
+    assert_eq!(restored_snapshot(&game), saved_snapshot);
+}
+
+#[test]
+fn late_action_resimulates_frames() {
+    let Scenario {
+        mut game,
+        player_net_id,
+        ..
+    } = ScenarioBuilder::default().build();
+
+    game.run_until(15);
+    game.step();
+    assert_eq!(game.world().fetch::<RollbackStats>().resimulated_frames, 0);
+
+    game.add_walk_action(
+        5,
+        player_net_id,
+        PlayerWalkAction::Walk {
+            direction: Vector2::new(-1.0, 0.0),
+        },
+    );
+    game.step();
+    assert_eq!(game.world().fetch::<RollbackStats>().resimulated_frames, 11);
+}
+
+#[test]
+fn overlapping_monsters_push_each_other_away() {
+    let Scenario {
+        mut game,
+        monster_net_ids,
+        ..
+    } = ScenarioBuilder::default()
+        .with_monster("Ghoul", Vector2::new(500.0, 500.0))
+        .with_monster("Ghoul", Vector2::new(505.0, 500.0))
+        .build();
+    game.run_until(SPAWN_FRAME + 10);
+
+    let first = game
+        .entity(monster_net_ids[0])
+        .expect("Expected a spawned monster");
+    let second = game
+        .entity(monster_net_ids[1])
+        .expect("Expected a spawned monster");
+    let min_distance = game.monster(first).radius + game.monster(second).radius;
+    assert!((game.position(first) - game.position(second)).norm() >= min_distance);
+}
+
+#[test]
+fn player_is_blocked_by_obstacle() {
+    let Scenario {
+        mut game,
+        player,
+        player_net_id,
+        ..
+    } = ScenarioBuilder::default().build();
+
+    // A rock with the radius of 140 from resources/levels/desert.ron.
+    let obstacle_center = Vector2::new(900.0, -300.0);
+    game.add_walk_action(
+        0,
+        player_net_id,
+        PlayerWalkAction::Walk {
+            direction: obstacle_center.normalize(),
+        },
+    );
+    // It's enough time to reach the center of the rock if it wasn't there.
+    game.run_until(300);
+
+    let player_radius = game.player(player).radius;
+    let distance = (game.position(player) - obstacle_center).norm();
+    assert!((distance - (140.0 + player_radius)).abs() < 0.01);
+}
+
+#[test]
+fn beetles_notice_players_from_further_away_than_ghouls() {
+    // Both monsters are outside the ghoul aggro radius (200), but inside the beetle one (320).
+    let Scenario {
+        mut game,
+        monster_net_ids,
+        ..
+    } = ScenarioBuilder::default()
+        .with_monster("Ghoul", Vector2::new(-260.0, 0.0))
+        .with_monster("Beetle", Vector2::new(260.0, 0.0))
+        .build();
+    game.run_until(SPAWN_FRAME + 5);
+
+    let ghoul = game.monster(game.entity(monster_net_ids[0]).expect("Expected a ghoul"));
+    let beetle = game.monster(game.entity(monster_net_ids[1]).expect("Expected a beetle"));
+    assert_eq!(beetle.name, "Beetle");
+    assert!(beetle.health < ghoul.health);
+    assert!(matches!(beetle.action.action, MobAction::Chase(_)));
+    assert!(!matches!(ghoul.action.action, MobAction::Chase(_)));
+}
+
+/// The spitter shoots from 220 units away, so it doesn't need to come closer.
+fn spitter_scenario() -> ScenarioBuilder {
+    ScenarioBuilder::default().with_monster("Spitter", Vector2::new(-150.0, 0.0))
+}
+
+#[test]
+fn spitter_shoots_player_from_distance() {
+    let Scenario {
+        mut game,
+        player,
+        monster_net_ids,
+        ..
+    } = spitter_scenario().build();
+    let initial_health = game.player(player).health;
+
+    game.run_until(SPAWN_FRAME + 1);
+    let spitter = game.entity(monster_net_ids[0]).expect("Expected a spitter");
+
+    // Projectiles get cleaned up after hitting, so we check for one while it's flying.
+    game.run_until_condition(SPAWN_FRAME + 30, |game| {
+        game.world()
+            .read_storage::<MonsterProjectile>()
+            .join()
+            .any(|projectile| projectile.shooter == spitter)
+    });
+    game.run_until(SPAWN_FRAME + 60);
+    assert!(game.player(player).health < initial_health);
+    let distance = (game.position(spitter) - game.position(player)).norm();
+    assert!(distance > game.monster(spitter).radius + game.player(player).radius);
+}
+
+#[test]
+fn spitter_projectiles_poison_player() {
+    let Scenario {
+        mut game,
+        player,
+        monster_net_ids,
+        ..
+    } = spitter_scenario().build();
+    game.run_until(SPAWN_FRAME + 120);
+
+    let spitter = game.entity(monster_net_ids[0]).expect("Expected a spitter");
+    let poison = game
+        .status_effects(player)
+        .effects
+        .into_iter()
+        .find(|effect| effect.kind == StatusEffectKind::Poison)
+        .expect("Expected the player to be poisoned");
+    assert_eq!(poison.source, Some(spitter));
+
+    // Poison ticks are credited to the spitter.
+    let ticks = game
+        .damage_entries(player, SPAWN_FRAME..=SPAWN_FRAME + 119)
+        .into_iter()
+        .filter(|entry| entry.cause == DamageCause::StatusEffect(StatusEffectKind::Poison))
+        .collect::<Vec<_>>();
+    assert!(!ticks.is_empty());
+    assert!(ticks.iter().all(|entry| entry.source == Some(spitter)));
+}
+
+/// The player walks away from a spitter, the walk action is received `late_frames` late.
+/// Returns the frames the flying projectiles have been shot at.
+fn spitter_projectiles_scenario(late_frames: u64) -> Vec<u64> {
+    let Scenario {
+        mut game,
+        player_net_id,
+        ..
+    } = spitter_scenario().build();
+
+    game.add_late_walk_action(
+        SPAWN_FRAME + 10,
+        late_frames,
+        player_net_id,
+        PlayerWalkAction::Walk {
+            direction: Vector2::new(0.0, 1.0),
+        },
+    );
+    game.run_until(SPAWN_FRAME + 90);
+
+    let world = game.world();
+    let mut frames_spawned = (
+        &world.entities(),
+        &world.read_storage::<MonsterProjectile>(),
+    )
+        .join()
+        .filter(|(entity, _)| !game.is_dead(*entity))
+        .map(|(_, projectile)| projectile.frame_spawned)
+        .collect::<Vec<_>>();
+    frames_spawned.sort();
+    frames_spawned
+}
+
+#[test]
+fn rollback_forgets_projectiles_of_resimulated_frames() {
+    assert_eq!(
+        spitter_projectiles_scenario(0),
+        spitter_projectiles_scenario(20)
+    );
+}
+
+/// The player walks towards a dropped pickup, but stops on the next frame. The stop action
+/// is received either in time or after the player has already collected the pickup.
+/// Returns whether the pickup is collected and the end of the player's speed buff.
+fn pickup_collection_scenario(is_stop_late: bool) -> (bool, u64) {
+    let (
+        Scenario {
+            mut game,
+            player,
+            player_net_id,
+            ..
+        },
+        _monster,
+        death_frame,
+    ) = missile_kill_game(speed_buff_drop_scenario());
+    let pickup = game.pickups()[0];
+
+    let direction = (game.position(pickup) - game.position(player)).normalize();
+    game.add_walk_action(
+        death_frame,
+        player_net_id,
+        PlayerWalkAction::Walk { direction },
+    );
+    if is_stop_late {
+        game.run_until_condition(death_frame + 240, |game| game.is_dead(pickup));
+    }
+    game.add_walk_action(death_frame + 1, player_net_id, PlayerWalkAction::Stop);
+    game.run_until(death_frame + 250);
+    (
+        game.is_dead(pickup),
+        game.player(player).speed_buff_until_frame,
+    )
+}
+
+#[test]
+fn rollback_revives_pickups_collected_in_resimulated_frames() {
+    let in_time = pickup_collection_scenario(false);
+    assert!(!in_time.0);
+    assert_eq!(pickup_collection_scenario(true), in_time);
+}
+
+#[test]
+fn missile_slows_monster_down() {
+    let (Scenario { game, .. }, monster, _death_frame) =
+        missile_kill_game(ScenarioBuilder::default());
+    assert!(game
+        .status_effects(monster)
+        .effects
+        .iter()
+        .any(|effect| effect.kind == StatusEffectKind::Slow));
+}
+
+/// The ghoul attack animation hits at its 18th keyframe (0.3 seconds) and lasts 0.5 seconds.
+const GHOUL_HIT_FRAMES: u64 = 18;
+
+/// Spawns a ghoul right next to the player and runs the game until it starts attacking.
+/// Returns the game, the player, their net id and the frame the attack starts at.
+fn ghoul_attack_game() -> (Scenario, u64) {
+    let mut scenario = ScenarioBuilder::default()
+        .with_monster("Ghoul", Vector2::new(20.0, 0.0))
+        .build();
+    let ghoul_net_id = scenario.monster_net_ids[0];
+
+    let game = &mut scenario.game;
+    game.run_until_condition(SPAWN_FRAME + 30, |game| {
+        game.entity(ghoul_net_id).map_or(false, |ghoul| {
+            matches!(game.monster(ghoul).action.action, MobAction::Attack(_))
+        })
+    });
+    let ghoul = game.entity(ghoul_net_id).expect("Expected a ghoul");
+    let attack_frame = game.monster(ghoul).action.frame_number;
+    (scenario, attack_frame)
+}
+
+#[test]
+fn melee_attack_hits_after_wind_up() {
+    let (
+        Scenario {
+            mut game, player, ..
+        },
+        attack_frame,
+    ) = ghoul_attack_game();
+    let initial_health = game.player(player).health;
+
+    game.run_until(attack_frame + GHOUL_HIT_FRAMES - 1);
+    assert_eq!(game.player(player).health, initial_health);
+    game.run_until(attack_frame + GHOUL_HIT_FRAMES + 5);
+    assert!(game.player(player).health < initial_health);
+}
+
+#[test]
+fn stunned_melee_attack_hits_after_stun() {
+    let (
+        Scenario {
+            mut game, player, ..
+        },
+        attack_frame,
+    ) = ghoul_attack_game();
+    let initial_health = game.player(player).health;
+
+    // The ghoul is stunned at the frame it would hit the player.
+    game.run_until(attack_frame + GHOUL_HIT_FRAMES - 2);
+    let ghoul = game
+        .monsters()
+        .into_iter()
+        .find(|monster| matches!(game.monster(*monster).action.action, MobAction::Attack(_)))
+        .expect("Expected an attacking ghoul");
+    game.apply_status_effect(
+        ghoul,
+        StatusEffectKind::Stun,
+        attack_frame + GHOUL_HIT_FRAMES + 3,
+    );
+    game.run_until(attack_frame + GHOUL_HIT_FRAMES + 2);
+    assert_eq!(game.player(player).health, initial_health);
+    // Stuns don't change the frame the attack has started at.
+    assert_eq!(game.monster(ghoul).action.frame_number, attack_frame);
+    assert!(game.monster(ghoul).stunned_frames > 0);
+    game.run_until(attack_frame + GHOUL_HIT_FRAMES + 10);
+    assert!(game.player(player).health < initial_health);
+}
+
+#[test]
+fn player_dodges_melee_attack_by_walking_away() {
+    let (
+        Scenario {
+            mut game,
+            player,
+            player_net_id,
+            ..
+        },
+        attack_frame,
+    ) = ghoul_attack_game();
+    let initial_health = game.player(player).health;
+
+    game.add_walk_action(
+        attack_frame + 1,
+        player_net_id,
+        PlayerWalkAction::Walk {
+            direction: Vector2::new(-1.0, 0.0),
+        },
+    );
+    game.run_until(attack_frame + GHOUL_HIT_FRAMES + 5);
+    assert_eq!(game.player(player).health, initial_health);
+}
+
+#[test]
+fn game_is_over_when_all_players_are_down() {
+    let (
+        Scenario {
+            mut game, player, ..
+        },
+        attack_frame,
+    ) = ghoul_attack_game();
+    assert!(!game.is_over());
+
+    game.run_until_condition(attack_frame + 1200, |game| game.is_over());
+    // Players get downed instead of dying in multiplayer.
+    assert!(game.is_downed(player));
+    assert!(!game.is_dead(player));
+    let run_summary = game.run_summary();
+    assert_eq!(run_summary.players.len(), 1);
+    assert!(run_summary.waves_reached >= 1);
+    let player_summary = &run_summary.players[0];
+    assert!(player_summary.stats.damage_taken >= 100.0);
+    assert!(player_summary.time_survived_secs > 0.0);
+}
+
+/// Starts a game with two players and no monsters, the first player is downed.
+/// Returns the game, both players and the frame the first one is downed at.
+fn downed_player_game(teammate_position: Vector2) -> (HeadlessGame, Entity, Entity, u64) {
+    let Scenario {
+        mut game,
+        player,
+        teammates,
+        ..
+    } = ScenarioBuilder::default()
+        .with_waves(Vec::new())
+        .with_teammate(teammate_position)
+        .build();
+    let teammate = teammates[0];
+
+    game.run_until(5);
+    game.set_health(player, 0.0);
+    let downed_frame = game.run_until_condition(10, |game| game.is_downed(player));
+    assert!(!game.is_dead(player));
+    assert!(!game.is_over());
+    (game, player, teammate, downed_frame)
+}
+
+#[test]
+fn teammate_revives_downed_player() {
+    let (mut game, player, teammate, downed_frame) = downed_player_game(Vector2::new(30.0, 0.0));
+
+    let revived_frame =
+        game.run_until_condition(downed_frame + 300, |game| !game.is_downed(player));
+    // Reviving takes 3 seconds.
+    assert!((178..=182).contains(&(revived_frame - downed_frame)));
+    assert!((game.player(player).health - 30.0).abs() < 0.001);
+    assert!(!game.is_downed(teammate));
+    assert_eq!(
+        game.sent_downed_updates(game.net_id(player)),
+        vec![PlayerDownedUpdate::Downed, PlayerDownedUpdate::Revived]
+    );
+}
+
+#[test]
+fn downed_player_bleeds_out_without_teammates_nearby() {
+    let (mut game, player, _, downed_frame) = downed_player_game(Vector2::new(500.0, 0.0));
+
+    let death_frame = game.run_until_condition(downed_frame + 1900, |game| game.is_dead(player));
+    // Bleeding out takes 30 seconds.
+    assert!((1798..=1802).contains(&(death_frame - downed_frame)));
+    // The teammate is still standing.
+    assert!(!game.is_over());
+    assert_eq!(
+        game.sent_downed_updates(game.net_id(player)),
+        vec![PlayerDownedUpdate::Downed, PlayerDownedUpdate::BledOut]
+    );
+}
+
+#[test]
+fn wave_repeats_spawns_until_next_wave_starts() {
+    let Scenario { mut game, .. } = ScenarioBuilder::default()
+        .with_waves(vec![
+            WaveDefinition {
+                start: WaveCondition::AfterSecs(0.0),
+                spawns: vec![WaveSpawn {
+                    monsters: vec![("Ghoul".to_owned(), 1.0)],
+                    at: WaveSpawnPoint::RandomSides { count: 2 },
+                    delay_secs: 0.0,
+                    every_secs: Some(1.0),
+                }],
+            },
+            WaveDefinition {
+                start: WaveCondition::AfterSecs(2.0),
+                spawns: vec![WaveSpawn {
+                    monsters: vec![("Beetle".to_owned(), 1.0)],
+                    at: WaveSpawnPoint::Point {
+                        position: (500.0, 500.0),
+                        count: 1,
+                    },
+                    delay_secs: 0.0,
+                    every_secs: None,
+                }],
+            },
+        ])
+        .build();
+    game.run_until(300);
+
+    // The first wave spawns ghouls at 0 and 1 seconds, the second one starts at 2 seconds.
+    let names: Vec<String> = game
+        .monsters()
+        .into_iter()
+        .map(|monster| game.monster(monster).name)
+        .collect();
+    assert_eq!(names.iter().filter(|name| *name == "Ghoul").count(), 4);
+    assert_eq!(names.iter().filter(|name| *name == "Beetle").count(), 1);
+}
+
+#[test]
+fn level_is_complete_when_player_reaches_area() {
+    let Scenario {
+        mut game,
+        player_net_id,
+        ..
+    } = ScenarioBuilder::default().build();
+    game.set_objective(LevelObjective::ReachArea {
+        center: (-300.0, 0.0),
+        radius: 50.0,
+    });
+
+    game.add_walk_action(
+        0,
+        player_net_id,
+        PlayerWalkAction::Walk {
+            direction: Vector2::new(-1.0, 0.0),
+        },
+    );
+    // It takes 1.25 seconds to walk 250 units.
+    let completed_at = game.run_until_condition(120, |game| game.is_level_complete());
+    assert!((74..=76).contains(&completed_at));
+}
+
+#[test]
+fn level_is_complete_when_waves_are_survived() {
+    let (Scenario { mut game, .. }, _monster, _dies_at) =
+        missile_kill_game(ScenarioBuilder::default());
+    // The only wave spawns a single monster, which is dead already.
+    game.set_objective(LevelObjective::SurviveWaves(1));
+    game.step();
+    assert!(game.is_level_complete());
+}
+
+#[test]
+fn campaign_levels_have_valid_waves() {
+    // Loading a level fails on unknown wave monsters or invalid weights.
+    Campaign::load().expect("Expected to load every campaign level");
+}
@@ -0,0 +1,759 @@
+//! A headless (server-side) simulation of the game logic, which is used by gameplay tests.
+//!
+//! The harness builds a `World` with `build_game_logic_systems` and no renderer, and runs it
+//! frame by frame. It plays the role of the server: the multiplayer game is started, and the
+//! actions injected by tests are stored in `FramedUpdates<ReceivedClientActionUpdates>` in the
+//! same way `ServerNetworkSystem` does, so late actions trigger a rollback.
+//!
+//! Tests set up games with `ScenarioBuilder`.
+//!
+//! Needs `gv_game` to be built without the `client` feature (`cargo test -p gv_game`).
+
+use amethyst::{
+    core::{ArcThreadPool, Time},
+    ecs::{Entity, Join, ReadExpect, System, World, WorldExt, WriteExpect, WriteStorage},
+    prelude::{GameData, GameDataBuilder},
+    DataInit,
+};
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use gv_core::{
+    actions::{
+        monster_spawn::{SpawnAction, SpawnActions, SpawnType},
+        player::{PlayerCastAction, PlayerWalkAction},
+        ClientActionUpdate, IdentifiableAction,
+    },
+    ecs::{
+        components::{
+            damage_history::{DamageHistory, DamageHistoryEntry, DamageType},
+            pickup::Pickup,
+            status_effect::{StatusEffectKind, StatusEffects},
+            ClientPlayerActions, Dead, EntityNetMetadata, Monster, Player, PlayerDownedUpdate,
+            WorldPosition,
+        },
+        resources::{
+            net::{
+                ActionUpdateIdProvider, EntityNetMetadataStorage, MultiplayerGameState,
+                MultiplayerRoomPlayer,
+            },
+            stats::{PlayerStats, RunStats, RunSummary},
+            world::{
+                DummyFramedUpdate, FramedUpdates, ReceivedClientActionUpdates, SavedWorldState,
+                ServerWorldUpdates, WorldStates,
+            },
+            GameEngineState, GameLevelState, GamePause, GameTime, NewGameEngineState,
+            SimulationTicks,
+        },
+        system_data::time::GameTimeService,
+    },
+    math::Vector2,
+    net::{NetIdentifier, NetUpdate, TickRate},
+    progression::PlayerUpgrades,
+};
+use gv_game::{
+    build_game_logic_systems,
+    ecs::{
+        factories::PlayerFactory,
+        resources::{
+            ActiveObjective, LevelDefinition, LevelObjective, MonsterDefinition,
+            MonsterDefinitions, MonsterDrop, SpellDefinitions, WaveDefinition, WaveScript,
+            SURVIVAL_LEVEL,
+        },
+        systems::WorldStateSubsystem,
+    },
+    utils::entities::is_dead,
+};
+
+pub struct HeadlessGame {
+    world: World,
+    game_data: GameData<'static, 'static>,
+    client_action_id: NetIdentifier,
+}
+
+impl HeadlessGame {
+    fn new() -> Self {
+        let mut world = World::new();
+        world.insert::<ArcThreadPool>(Arc::new(
+            rayon::ThreadPoolBuilder::new()
+                .build()
+                .expect("Expected to build a thread pool"),
+        ));
+        world.insert(Time::default());
+        world.insert(FramedUpdates::<DummyFramedUpdate>::default());
+        world.insert(FramedUpdates::<ReceivedClientActionUpdates>::default());
+        world.insert(ServerWorldUpdates::default());
+
+        let game_data_builder =
+            GameDataBuilder::default().with(HeadlessNetworkSystem, "game_network_system", &[]);
+        let game_data = build_game_logic_systems(game_data_builder, &mut world, true)
+            .expect("Expected to build game logic systems")
+            .build(&mut world);
+
+        world.insert(MonsterDefinitions::load().expect("Expected to load monster definitions"));
+        world.insert(SpellDefinitions::load().expect("Expected to load spell definitions"));
+        // The first wave of the desert level spawns a single monster at frame 10,
+        // the next ones would spawn monsters at random positions.
+        let mut waves = LevelDefinition::load(SURVIVAL_LEVEL)
+            .expect("Expected to load the desert level")
+            .waves;
+        waves.truncate(1);
+        world.insert(WaveScript(waves));
+        world.insert(GameLevelState::default());
+        world.insert(GamePause::default());
+        world.insert(GameTime::default());
+        world.insert(GameEngineState::Playing);
+        world.insert(NewGameEngineState(GameEngineState::Playing));
+        world.fetch_mut::<MultiplayerGameState>().is_playing = true;
+
+        let mut game = Self {
+            world,
+            game_data,
+            client_action_id: 0,
+        };
+        game.start_tick();
+        game
+    }
+
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    /// Makes the game a campaign level with this objective.
+    pub fn set_objective(&mut self, objective: LevelObjective) {
+        self.world.insert(ActiveObjective(Some(objective)));
+    }
+
+    /// Pauses a single-player game the same way the pause menu does.
+    pub fn set_paused(&mut self, is_paused: bool) {
+        self.world.fetch_mut::<GamePause>().is_paused = is_paused;
+    }
+
+    pub fn is_level_complete(&self) -> bool {
+        self.world.fetch::<NewGameEngineState>().0 == GameEngineState::LevelComplete
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.world.fetch::<GameLevelState>().is_over
+    }
+
+    pub fn run_summary(&self) -> RunSummary {
+        (*self.world.fetch::<RunSummary>()).clone()
+    }
+
+    pub fn stats(&self, player: Entity) -> PlayerStats {
+        self.world.fetch::<RunStats>().player(player)
+    }
+
+    pub fn confirmed_kills(&self, player: Entity) -> u32 {
+        self.world.fetch::<RunStats>().confirmed_kills(player)
+    }
+
+    /// Creates a player the same way `PlayingState` does on server.
+    /// Players are expected to be added before the first frame is run.
+    fn add_player(&mut self, upgrades: PlayerUpgrades) -> (Entity, NetIdentifier) {
+        self.world.exec(
+            move |(
+                mut player_factory,
+                mut entity_net_metadata,
+                mut entity_net_metadata_storage,
+                mut multiplayer_game_state,
+            ): (
+                PlayerFactory,
+                WriteStorage<EntityNetMetadata>,
+                WriteExpect<EntityNetMetadataStorage>,
+                WriteExpect<MultiplayerGameState>,
+            )| {
+                let player_entity = player_factory.create(upgrades);
+                let entity_net_id = entity_net_metadata_storage.register_new_entity(player_entity);
+                entity_net_metadata
+                    .insert(
+                        player_entity,
+                        EntityNetMetadata {
+                            id: entity_net_id,
+                            spawned_frame_number: 0,
+                        },
+                    )
+                    .expect("Expected to insert EntityNetMetadata component");
+                multiplayer_game_state.players.push(MultiplayerRoomPlayer {
+                    connection_id: entity_net_id,
+                    entity_net_id,
+                    nickname: format!("Player {}", entity_net_id),
+                    is_host: entity_net_id == 0,
+                    color: [1.0, 1.0, 1.0],
+                    upgrades,
+                });
+                (player_entity, entity_net_id)
+            },
+        )
+    }
+
+    /// Returns the number of the frame that will be simulated by the next `step` call.
+    pub fn frame_number(&self) -> u64 {
+        self.world
+            .system_data::<GameTimeService>()
+            .game_frame_number()
+    }
+
+    /// Returns the number of frames that can still be rolled back.
+    pub fn saved_world_states_limit(&self) -> u64 {
+        self.world
+            .system_data::<GameTimeService>()
+            .saved_world_states_limit()
+    }
+
+    /// Runs a single frame.
+    pub fn step(&mut self) {
+        self.game_data.update(&self.world);
+        self.world.maintain();
+        self.world.fetch_mut::<Time>().increment_frame_number();
+        self.start_tick();
+    }
+
+    /// Runs a frame which is rendered before the next tick is due, as clients do
+    /// if their frame rate limit is higher than the tick rate.
+    pub fn step_between_ticks(&mut self) {
+        {
+            // Puts off the tick the next frame was going to simulate.
+            let mut simulation_ticks = self.world.fetch_mut::<SimulationTicks>();
+            simulation_ticks.is_ticking = false;
+            simulation_ticks.pending += 1;
+        }
+        self.game_data.update(&self.world);
+        self.world.maintain();
+        self.world.fetch_mut::<Time>().increment_frame_number();
+        self.world.fetch_mut::<SimulationTicks>().start_frame();
+    }
+
+    /// Every frame of the harness simulates a tick, as if it was rendered at the tick rate.
+    fn start_tick(&mut self) {
+        let mut simulation_ticks = self.world.fetch_mut::<SimulationTicks>();
+        simulation_ticks.add_fixed_update();
+        simulation_ticks.start_frame();
+    }
+
+    /// Runs frames until `frame_number` becomes the next frame to be simulated.
+    pub fn run_until(&mut self, frame_number: u64) {
+        while self.frame_number() < frame_number {
+            self.step();
+        }
+    }
+
+    /// Runs frames until the condition is met, returns the number of the last simulated frame.
+    /// Panics if the condition isn't met until `frame_limit`.
+    pub fn run_until_condition(
+        &mut self,
+        frame_limit: u64,
+        mut condition: impl FnMut(&HeadlessGame) -> bool,
+    ) -> u64 {
+        while self.frame_number() < frame_limit {
+            self.step();
+            if condition(self) {
+                return self.frame_number() - 1;
+            }
+        }
+        panic!("The condition wasn't met until frame {}", frame_limit);
+    }
+
+    /// Adds a walk action as if it was received from a client. If `frame_number` is older
+    /// than the current frame, the world gets resimulated starting with it.
+    pub fn add_walk_action(
+        &mut self,
+        frame_number: u64,
+        entity_net_id: NetIdentifier,
+        action: PlayerWalkAction,
+    ) {
+        let client_action_id = self.next_client_action_id();
+        self.client_updates(frame_number)
+            .walk_action_updates
+            .push(NetUpdate {
+                entity_net_id,
+                data: ClientActionUpdate {
+                    client_action_id,
+                    action,
+                },
+            });
+    }
+
+    /// Runs frames until `late_frames` after `frame_number` and adds a walk action
+    /// for `frame_number`, as if the server has received it late.
+    pub fn add_late_walk_action(
+        &mut self,
+        frame_number: u64,
+        late_frames: u64,
+        entity_net_id: NetIdentifier,
+        action: PlayerWalkAction,
+    ) {
+        self.run_until(frame_number + late_frames);
+        self.add_walk_action(frame_number, entity_net_id, action);
+    }
+
+    /// Adds a cast action of the missile spell as if it was received from a client.
+    pub fn add_cast_action(
+        &mut self,
+        frame_number: u64,
+        entity_net_id: NetIdentifier,
+        target_position: Vector2,
+    ) {
+        self.add_spell_cast_action(frame_number, entity_net_id, "Missile", target_position);
+    }
+
+    pub fn add_spell_cast_action(
+        &mut self,
+        frame_number: u64,
+        entity_net_id: NetIdentifier,
+        spell: &str,
+        target_position: Vector2,
+    ) {
+        let client_action_id = self.next_client_action_id();
+        let action_id = self
+            .world
+            .fetch_mut::<ActionUpdateIdProvider>()
+            .next_update_id();
+        self.client_updates(frame_number)
+            .cast_action_updates
+            .push(NetUpdate {
+                entity_net_id,
+                data: IdentifiableAction {
+                    action_id,
+                    action: ClientActionUpdate {
+                        client_action_id,
+                        action: PlayerCastAction {
+                            spell: spell.to_owned(),
+                            // Server overwrites it with the actual player position.
+                            cast_position: Vector2::new(0.0, 0.0),
+                            target_position,
+                        },
+                    },
+                },
+            });
+    }
+
+    /// Schedules spawning of a single idle monster of the `monster` definition.
+    /// Returns its net id, the monster entity exists after `frame_number` is simulated.
+    pub fn add_spawn_action(
+        &mut self,
+        frame_number: u64,
+        monster: &str,
+        position: Vector2,
+    ) -> NetIdentifier {
+        let entity_net_id = self
+            .world
+            .fetch_mut::<EntityNetMetadataStorage>()
+            .reserve_ids(1)
+            .start;
+        self.world
+            .fetch_mut::<FramedUpdates<SpawnActions>>()
+            .update_frame(frame_number)
+            .unwrap_or_else(|| panic!("Expected SpawnActions for frame {}", frame_number))
+            .spawn_actions
+            .push(SpawnAction {
+                monster: monster.to_owned(),
+                spawn_type: SpawnType::Single {
+                    entity_net_id: Some(entity_net_id),
+                    position,
+                },
+            });
+        entity_net_id
+    }
+
+    pub fn entity(&self, entity_net_id: NetIdentifier) -> Option<Entity> {
+        self.world
+            .fetch::<EntityNetMetadataStorage>()
+            .get_entity(entity_net_id)
+    }
+
+    pub fn net_id(&self, entity: Entity) -> NetIdentifier {
+        self.world
+            .read_storage::<EntityNetMetadata>()
+            .get(entity)
+            .expect("Expected EntityNetMetadata")
+            .id
+    }
+
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.world.entities().is_alive(entity)
+    }
+
+    pub fn monsters(&self) -> Vec<Entity> {
+        (
+            &self.world.entities(),
+            &self.world.read_storage::<Monster>(),
+        )
+            .join()
+            .map(|(entity, _)| entity)
+            .collect()
+    }
+
+    pub fn pickups(&self) -> Vec<Entity> {
+        (&self.world.entities(), &self.world.read_storage::<Pickup>())
+            .join()
+            .map(|(entity, _)| entity)
+            .collect()
+    }
+
+    pub fn pickup(&self, entity: Entity) -> Pickup {
+        self.world
+            .read_storage::<Pickup>()
+            .get(entity)
+            .cloned()
+            .expect("Expected a Pickup")
+    }
+
+    pub fn status_effects(&self, entity: Entity) -> StatusEffects {
+        self.world
+            .read_storage::<StatusEffects>()
+            .get(entity)
+            .cloned()
+            .expect("Expected StatusEffects")
+    }
+
+    /// Applies a status effect starting with the next frame, as if a spell has hit the entity.
+    pub fn apply_status_effect(
+        &mut self,
+        entity: Entity,
+        kind: StatusEffectKind,
+        until_frame: u64,
+    ) {
+        let frame_number = self.frame_number();
+        self.world
+            .write_storage::<StatusEffects>()
+            .get_mut(entity)
+            .expect("Expected StatusEffects")
+            .apply(kind, frame_number, until_frame, None);
+        self.save_world_state();
+    }
+
+    pub fn position(&self, entity: Entity) -> Vector2 {
+        **self
+            .world
+            .read_storage::<WorldPosition>()
+            .get(entity)
+            .expect("Expected a WorldPosition")
+    }
+
+    pub fn set_position(&mut self, entity: Entity, position: Vector2) {
+        self.world
+            .write_storage::<WorldPosition>()
+            .insert(entity, WorldPosition::new(position))
+            .expect("Expected to insert a WorldPosition");
+        self.save_world_state();
+    }
+
+    /// Sets the health of a player directly, bypassing their damage history.
+    pub fn set_health(&mut self, entity: Entity, health: f32) {
+        self.world
+            .write_storage::<Player>()
+            .get_mut(entity)
+            .expect("Expected a Player")
+            .health = health;
+        self.save_world_state();
+    }
+
+    pub fn player(&self, entity: Entity) -> Player {
+        self.world
+            .read_storage::<Player>()
+            .get(entity)
+            .cloned()
+            .expect("Expected a Player")
+    }
+
+    pub fn monster(&self, entity: Entity) -> Monster {
+        self.world
+            .read_storage::<Monster>()
+            .get(entity)
+            .cloned()
+            .expect("Expected a Monster")
+    }
+
+    /// Returns the damage entries an entity has received within the frames (inclusive).
+    pub fn damage_entries(
+        &self,
+        entity: Entity,
+        frames: std::ops::RangeInclusive<u64>,
+    ) -> Vec<DamageHistoryEntry> {
+        let damage_histories = self.world.read_storage::<DamageHistory>();
+        let damage_history = damage_histories
+            .get(entity)
+            .expect("Expected a DamageHistory");
+        frames
+            .filter_map(|frame_number| damage_history.get_entries(frame_number))
+            .flat_map(|damage_entries| damage_entries.entries.clone())
+            .collect()
+    }
+
+    /// Returns the damage entries of an entity that would be sent to clients. Only the latest
+    /// update of a resimulated frame is taken into account.
+    pub fn sent_damage_entries(&self, entity_net_id: NetIdentifier) -> Vec<DamageHistoryEntry> {
+        let server_world_updates = self.world.fetch::<ServerWorldUpdates>();
+        let mut entries = BTreeMap::new();
+        for (_, update) in &server_world_updates.updates {
+            entries.insert(
+                update.frame_number,
+                update
+                    .damage_histories_updates
+                    .iter()
+                    .filter(|net_update| net_update.entity_net_id == entity_net_id)
+                    .flat_map(|net_update| net_update.data.entries.clone())
+                    .collect::<Vec<_>>(),
+            );
+        }
+        entries
+            .into_iter()
+            .flat_map(|(_, entries)| entries)
+            .collect()
+    }
+
+    /// Returns net ids of collected pickups with net ids of players who've collected them,
+    /// as they would be sent to clients.
+    pub fn sent_pickup_collections(&self) -> Vec<(NetIdentifier, NetIdentifier)> {
+        let server_world_updates = self.world.fetch::<ServerWorldUpdates>();
+        let mut collections = BTreeMap::new();
+        for (_, update) in &server_world_updates.updates {
+            collections.insert(
+                update.frame_number,
+                update
+                    .pickup_collections
+                    .iter()
+                    .map(|net_update| (net_update.entity_net_id, net_update.data))
+                    .collect::<Vec<_>>(),
+            );
+        }
+        collections
+            .into_iter()
+            .flat_map(|(_, collections)| collections)
+            .collect()
+    }
+
+    /// Returns the downed state updates of a player that would be sent to clients.
+    pub fn sent_downed_updates(&self, entity_net_id: NetIdentifier) -> Vec<PlayerDownedUpdate> {
+        let server_world_updates = self.world.fetch::<ServerWorldUpdates>();
+        let mut updates = BTreeMap::new();
+        for (_, update) in &server_world_updates.updates {
+            updates.insert(
+                update.frame_number,
+                update
+                    .player_downed_updates
+                    .iter()
+                    .filter(|net_update| net_update.entity_net_id == entity_net_id)
+                    .map(|net_update| net_update.data)
+                    .collect::<Vec<_>>(),
+            );
+        }
+        updates
+            .into_iter()
+            .flat_map(|(_, updates)| updates)
+            .collect()
+    }
+
+    /// Checks whether a player is downed as of the last simulated frame.
+    pub fn is_downed(&self, entity: Entity) -> bool {
+        let last_frame = self.frame_number().saturating_sub(1);
+        self.player(entity).is_downed(last_frame)
+    }
+
+    /// Checks whether an entity is dead as of the last simulated frame.
+    pub fn is_dead(&self, entity: Entity) -> bool {
+        let last_frame = self.frame_number().saturating_sub(1);
+        is_dead(entity, &self.world.read_storage::<Dead>(), last_frame)
+    }
+
+    /// Saves the storages the same way the rollback system does after simulating a frame.
+    pub fn save_world_state_to(&self, world_state: &mut SavedWorldState) {
+        self.world
+            .system_data::<WorldStateSubsystem>()
+            .save_world_state(world_state);
+    }
+
+    /// Restores the storages the same way the rollback system does before simulating a frame.
+    pub fn load_world_state(&mut self, world_state: &SavedWorldState) {
+        self.world
+            .system_data::<WorldStateSubsystem>()
+            .load_from_world_state(world_state);
+    }
+
+    /// Every frame starts with loading its saved world state, so the components changed
+    /// by tests have to be saved to the state of the next frame as well.
+    fn save_world_state(&mut self) {
+        let frame_number = self.frame_number();
+        let mut world_states = self.world.fetch_mut::<WorldStates>();
+        let world_state = world_states
+            .states_iter_mut(frame_number)
+            .next()
+            .filter(|world_state| world_state.frame_number == frame_number);
+        if let Some(world_state) = world_state {
+            self.world
+                .system_data::<WorldStateSubsystem>()
+                .save_world_state(world_state);
+        }
+    }
+
+    fn monster_definition(&mut self, monster: &str) -> &mut MonsterDefinition {
+        self.world
+            .get_mut::<MonsterDefinitions>()
+            .expect("Expected MonsterDefinitions")
+            .definitions
+            .get_mut(monster)
+            .unwrap_or_else(|| panic!("Expected a monster definition for {}", monster))
+    }
+
+    fn next_client_action_id(&mut self) -> NetIdentifier {
+        let id = self.client_action_id;
+        self.client_action_id += 1;
+        id
+    }
+
+    fn client_updates(&mut self, frame_number: u64) -> &mut ReceivedClientActionUpdates {
+        self.world
+            .get_mut::<FramedUpdates<ReceivedClientActionUpdates>>()
+            .expect("Expected FramedUpdates<ReceivedClientActionUpdates>")
+            .update_frame(frame_number)
+            .unwrap_or_else(|| panic!("Expected client updates for frame {}", frame_number))
+    }
+}
+
+/// The frame monsters added with `ScenarioBuilder::with_monster` are spawned at.
+pub const SPAWN_FRAME: u64 = 20;
+
+/// A game started by `ScenarioBuilder`, no frames are simulated yet.
+pub struct Scenario {
+    pub game: HeadlessGame,
+    pub player: Entity,
+    pub player_net_id: NetIdentifier,
+    pub teammates: Vec<Entity>,
+    /// Net ids of the added monsters in the same order, the monsters exist
+    /// after `SPAWN_FRAME` is simulated.
+    pub monster_net_ids: Vec<NetIdentifier>,
+}
+
+/// Sets up a multiplayer game with a single player standing at (0, 0). Unless the waves
+/// are replaced, the first wave of the survival level spawns a ghoul at (0, 300) at frame 10.
+pub struct ScenarioBuilder {
+    game: HeadlessGame,
+    upgrades: PlayerUpgrades,
+    teammate_positions: Vec<Vector2>,
+    monsters: Vec<(String, Vector2)>,
+}
+
+impl Default for ScenarioBuilder {
+    fn default() -> Self {
+        Self {
+            game: HeadlessGame::new(),
+            upgrades: PlayerUpgrades::default(),
+            teammate_positions: Vec::new(),
+            monsters: Vec::new(),
+        }
+    }
+}
+
+impl ScenarioBuilder {
+    /// Replaces the level waves, the script runs from its first wave.
+    pub fn with_waves(mut self, waves: Vec<WaveDefinition>) -> Self {
+        self.game.world.insert(WaveScript(waves));
+        self
+    }
+
+    /// Replaces the drop table of a monster definition.
+    pub fn with_drops(mut self, monster: &str, drops: Vec<MonsterDrop>) -> Self {
+        self.game.monster_definition(monster).drops = drops;
+        self
+    }
+
+    /// Replaces the resistances of a monster definition.
+    pub fn with_resistances(mut self, monster: &str, resistances: Vec<(DamageType, f32)>) -> Self {
+        self.game.monster_definition(monster).resistances = resistances.into_iter().collect();
+        self
+    }
+
+    pub fn with_upgrades(mut self, upgrades: PlayerUpgrades) -> Self {
+        self.upgrades = upgrades;
+        self
+    }
+
+    pub fn with_tick_rate(self, tick_rate: TickRate) -> Self {
+        self.game
+            .world
+            .fetch_mut::<Time>()
+            .set_fixed_seconds(tick_rate.fixed_seconds());
+        self
+    }
+
+    /// Makes it a single-player game, which can be paused.
+    pub fn single_player(self) -> Self {
+        self.game
+            .world
+            .fetch_mut::<MultiplayerGameState>()
+            .is_playing = false;
+        self
+    }
+
+    /// Adds another player standing at `position`.
+    pub fn with_teammate(mut self, position: Vector2) -> Self {
+        self.teammate_positions.push(position);
+        self
+    }
+
+    /// Adds an idle monster spawned at `SPAWN_FRAME`.
+    pub fn with_monster(mut self, monster: &str, position: Vector2) -> Self {
+        self.monsters.push((monster.to_owned(), position));
+        self
+    }
+
+    pub fn build(self) -> Scenario {
+        let mut game = self.game;
+        let (player, player_net_id) = game.add_player(self.upgrades);
+        if !game.world.fetch::<MultiplayerGameState>().is_playing {
+            // A single player gets actions from input instead of a server.
+            game.world
+                .write_storage::<ClientPlayerActions>()
+                .insert(player, ClientPlayerActions::default())
+                .expect("Expected to insert ClientPlayerActions");
+        }
+        let teammates = self
+            .teammate_positions
+            .into_iter()
+            .map(|position| {
+                let (teammate, _) = game.add_player(PlayerUpgrades::default());
+                game.set_position(teammate, position);
+                teammate
+            })
+            .collect();
+        let monster_net_ids = self
+            .monsters
+            .iter()
+            .map(|(monster, position)| game.add_spawn_action(SPAWN_FRAME, monster, *position))
+            .collect();
+        Scenario {
+            game,
+            player,
+            player_net_id,
+            teammates,
+            monster_net_ids,
+        }
+    }
+}
+
+/// Takes the place of `ServerNetworkSystem`: the game logic systems depend on it, and it has to
+/// reserve `ServerWorldUpdates` for the frames that are going to be (re)simulated.
+struct HeadlessNetworkSystem;
+
+impl<'s> System<'s> for HeadlessNetworkSystem {
+    type SystemData = (
+        GameTimeService<'s>,
+        ReadExpect<'s, FramedUpdates<ReceivedClientActionUpdates>>,
+        WriteExpect<'s, ServerWorldUpdates>,
+    );
+
+    fn run(
+        &mut self,
+        (game_time_service, framed_updates, mut server_world_updates): Self::SystemData,
+    ) {
+        let current_frame_number = game_time_service.game_frame_number();
+        server_world_updates.reserve_new_updates(
+            framed_updates
+                .oldest_updated_frame
+                .min(current_frame_number),
+            current_frame_number,
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use amethyst::ecs::{Builder, Entity, World, WorldExt};
+
+use gv_core::math::Vector2;
+use gv_game::ecs::resources::{LevelObstacles, NavigationGrid, ObstacleDefinition};
+
+const STEP: f32 = 3.0;
+const RADIUS: f32 = 12.0;
+
+fn wall_grid() -> (NavigationGrid, LevelObstacles) {
+    let obstacles = LevelObstacles::new(vec![ObstacleDefinition::Polygon {
+        points: vec![
+            (-300.0, -20.0),
+            (300.0, -20.0),
+            (300.0, 20.0),
+            (-300.0, 20.0),
+        ],
+    }]);
+    let grid = NavigationGrid::new(
+        Vector2::new(1024.0, 1024.0),
+        &obstacles,
+        &LevelObstacles::new(Vec::new()),
+    );
+    (grid, obstacles)
+}
+
+/// Walks the same way as monsters do: towards the next waypoint, resolving collisions.
+fn walk(
+    grid: &mut NavigationGrid,
+    obstacles: &LevelObstacles,
+    destination: Vector2,
+    chased_player: Option<Entity>,
+) -> Vec<Vector2> {
+    let mut position = Vector2::new(0.0, -200.0);
+    let mut path = vec![position];
+    for _ in 0..1000 {
+        let waypoint = grid.next_waypoint(position, destination, chased_player);
+        let displacement = waypoint - position;
+        position = if displacement.norm() <= STEP {
+            waypoint
+        } else {
+            position + displacement.normalize() * STEP
+        };
+        position = obstacles.resolve(position, RADIUS);
+        path.push(position);
+        if position == destination {
+            break;
+        }
+    }
+    path
+}
+
+#[test]
+fn monsters_walk_around_obstacles() {
+    let (mut grid, obstacles) = wall_grid();
+    let destination = Vector2::new(0.0, 200.0);
+    let path = walk(&mut grid, &obstacles, destination, None);
+
+    assert_eq!(*path.last().unwrap(), destination);
+    // Walking around the wall takes longer than the straight line would.
+    assert!(path.len() as f32 * STEP > 700.0);
+}
+
+#[test]
+fn cached_flow_fields_give_the_same_path() {
+    let (mut grid, obstacles) = wall_grid();
+    let destination = Vector2::new(0.0, 200.0);
+    let first_path = walk(&mut grid, &obstacles, destination, None);
+    let cached_path = walk(&mut grid, &obstacles, destination, None);
+
+    let (mut fresh_grid, _) = wall_grid();
+    let fresh_path = walk(&mut fresh_grid, &obstacles, destination, None);
+
+    assert_eq!(first_path, cached_path);
+    assert_eq!(first_path, fresh_path);
+}
+
+#[test]
+fn chased_player_flow_field_follows_the_player() {
+    let (mut grid, obstacles) = wall_grid();
+    let player = World::new().create_entity().build();
+    let first_destination = Vector2::new(0.0, 200.0);
+    let second_destination = Vector2::new(150.0, 250.0);
+    walk(&mut grid, &obstacles, first_destination, Some(player));
+    let chasing_path = walk(&mut grid, &obstacles, second_destination, Some(player));
+
+    let (mut fresh_grid, _) = wall_grid();
+    let fresh_path = walk(&mut fresh_grid, &obstacles, second_destination, None);
+
+    assert_eq!(chasing_path, fresh_path);
+}
+
+#[test]
+fn evicted_flow_fields_give_the_same_path() {
+    let (mut grid, obstacles) = wall_grid();
+    let destination = Vector2::new(0.0, 200.0);
+    let first_path = walk(&mut grid, &obstacles, destination, None);
+    // Every destination is behind the wall, so each of them needs its own flow field.
+    for i in 0..80 {
+        let other_destination = Vector2::new(-400.0 + i as f32 * 10.0, 100.0);
+        grid.next_waypoint(Vector2::new(0.0, -200.0), other_destination, None);
+    }
+
+    assert_eq!(walk(&mut grid, &obstacles, destination, None), first_path);
+}
@@ -0,0 +1,60 @@
+//! Tests of the server-side message helpers.
+//!
+//! Needs `gv_game` to be built without the `client` feature (`cargo test -p gv_game`).
+
+use amethyst::network::simulation::{DeliveryRequirement, TransportResource};
+
+use std::net::SocketAddr;
+
+use gv_core::{
+    ecs::components::NetConnectionModel,
+    net::server_message::{DisconnectReason, ServerMessage, ServerMessagePayload},
+};
+use gv_game::utils::net::reject_on_checksum_mismatch;
+
+fn client_connection() -> NetConnectionModel {
+    let addr: SocketAddr = "127.0.0.1:3455".parse().unwrap();
+    NetConnectionModel::new(1, 2, addr)
+}
+
+#[test]
+fn join_with_different_definitions_is_rejected() {
+    let mut transport = TransportResource::new();
+    let mut connection = client_connection();
+
+    assert!(reject_on_checksum_mismatch(
+        &mut transport,
+        &mut connection,
+        "monster",
+        1,
+        2
+    ));
+    assert!(connection.disconnected);
+
+    let messages = transport.get_messages();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].destination, connection.addr);
+    assert_eq!(messages[0].delivery, DeliveryRequirement::Reliable);
+    let message: ServerMessage = bincode::deserialize(&messages[0].payload).unwrap();
+    assert_eq!(message.session_id, connection.session_id);
+    assert!(matches!(
+        message.payload,
+        ServerMessagePayload::Disconnect(DisconnectReason::DefinitionsMismatch)
+    ));
+}
+
+#[test]
+fn join_with_same_definitions_is_accepted() {
+    let mut transport = TransportResource::new();
+    let mut connection = client_connection();
+
+    assert!(!reject_on_checksum_mismatch(
+        &mut transport,
+        &mut connection,
+        "spell",
+        1,
+        1
+    ));
+    assert!(!connection.disconnected);
+    assert!(transport.get_messages().is_empty());
+}
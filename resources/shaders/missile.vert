@@ -11,11 +11,13 @@ layout(location = 0) in vec2 pos;
 layout(location = 1) in float seconds_since_spawn;
 layout(location = 2) in float opacity;
 layout(location = 3) in float ttl;
+layout(location = 4) in vec3 light_color;
 
 layout(location = 0) out VertexData {
     vec2 uv;
     float opacity;
     float seconds_since_spawn;
+    vec3 light_color;
 } vertex;
 
 const vec2 positions[4] = vec2[](
@@ -47,6 +49,7 @@ void main() {
     vertex.uv = vec2(u, v) + vec2(0.5);
     vertex.opacity = opacity;
     vertex.seconds_since_spawn = seconds_since_spawn;
+    vertex.light_color = light_color;
     vec2 final_pos = pos + vec2(u * size.x, v * size.y);
     vec4 vertex = vec4(final_pos, z, 1.0);
     gl_Position = proj_view * vertex;
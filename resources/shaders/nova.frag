@@ -0,0 +1,26 @@
+#version 450
+
+layout(location = 0) in VertexData {
+    vec2 uv;
+    float progress;
+    vec3 light_color;
+} vertex;
+layout(location = 0) out vec4 out_color;
+
+const vec3 ring_color = vec3(0.9, 0.9, 0.9);
+const float ring_width = 0.06;
+const float inner_glow = 0.2;
+
+void main() {
+    // Distance from the center, the quad edge is at 1.0.
+    float r = length(vertex.uv - vec2(0.5)) * 2.0;
+    // The ring slows down as it reaches the radius.
+    float ring_r = 1.0 - (1.0 - vertex.progress) * (1.0 - vertex.progress);
+
+    float ring = 1.0 - smoothstep(0.0, ring_width, abs(r - ring_r));
+    float glow = inner_glow * smoothstep(0.0, ring_r, r) * (1.0 - step(ring_r, r));
+    float fade = 1.0 - vertex.progress * vertex.progress;
+
+    vec3 color = mix(vertex.light_color, ring_color, ring * 0.5);
+    out_color = vec4(color, max(ring, glow) * fade);
+}